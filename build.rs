@@ -0,0 +1,36 @@
+use std::{
+    env,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Embeds the git commit and the build timestamp into the binary, see `BuildInfo`.
+///
+/// The git commit is read from `$GIT_SHA` or determined with `git rev-parse HEAD`, it is empty if neither is available.
+/// The build timestamp is read from `$SOURCE_DATE_EPOCH` for reproducible builds, otherwise the current time is used.
+fn main() {
+    let git_sha = env::var("GIT_SHA")
+        .ok()
+        .filter(|git_sha| !git_sha.is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|git_sha| git_sha.trim().to_string())
+        })
+        .unwrap_or_default();
+    let build_timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default()
+        });
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp);
+}
@@ -0,0 +1,80 @@
+use async_graphql::{Enum, Error, Result};
+use bson::doc;
+use mongodb::Collection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::graphql::model::date_time::DateTime;
+
+/// API key of a machine client, e.g. an internal batch tool or a partner.
+///
+/// Only the SHA-256 hash of the key is stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    /// Hex-encoded SHA-256 hash of the key.
+    pub _id: String,
+    /// Unique name of the machine client.
+    pub name: String,
+    /// Scopes granted to the machine client.
+    pub scopes: Vec<ApiKeyScope>,
+    /// Identifier of the tenant the machine client acts in, `None` for keys created before tenants, which act in the default tenant.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Timestamp when the API key was created.
+    pub created_at: DateTime,
+}
+
+/// Scope of an API key.
+#[derive(Enum, Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Allows queries of all wishlists.
+    ReadWishlists,
+    /// Allows queries and mutations of all wishlists.
+    WriteWishlists,
+    /// Allows operations requiring role: `admin`.
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// Name of the scope in the GraphQL schema.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ReadWishlists => "READ_WISHLISTS",
+            ApiKeyScope::WriteWishlists => "WRITE_WISHLISTS",
+            ApiKeyScope::Admin => "ADMIN",
+        }
+    }
+}
+
+/// Hashes an API key for storage and lookup.
+///
+/// * `key` - Plaintext API key.
+pub fn hash_api_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Generates a random plaintext API key of 64 hex digits.
+pub fn generate_api_key() -> String {
+    format!("{}{}", bson::Uuid::new(), bson::Uuid::new()).replace('-', "")
+}
+
+/// Looks up the API key of the `X-Api-Key` HTTP header.
+///
+/// Returns an error if the key is unknown.
+///
+/// * `collection` - MongoDB collection of API keys.
+/// * `key` - Plaintext API key.
+pub async fn find_api_key(collection: &Collection<ApiKey>, key: &str) -> Result<ApiKey> {
+    match collection
+        .find_one(doc! {"_id": hash_api_key(key)}, None)
+        .await
+    {
+        Ok(Some(api_key)) => Ok(api_key),
+        Ok(None) => Err(Error::new("API key is unknown.")),
+        Err(_) => Err(Error::new("Retrieving API key failed in MongoDB.")),
+    }
+}
@@ -0,0 +1,335 @@
+use async_graphql::{Error, Result};
+use bson::{doc, DateTime};
+use futures::TryStreamExt;
+use log::warn;
+use mongodb::{
+    options::{FindOneOptions, FindOptions},
+    Collection,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::graphql::model::{uuid::Uuid, wishlist::Wishlist, wishlist_activity::WishlistActivity};
+use crate::graphql::mutation_input_structs::WishlistVersionSelector;
+use crate::graphql::pagination::ActivityCursor;
+use crate::tenant::TenantId;
+
+/// Entry of the audit history of a wishlist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    /// UUID of audit entry.
+    pub _id: Uuid,
+    /// UUID of wishlist the audit entry belongs to.
+    pub wishlist_id: Uuid,
+    /// UUID of user who performed the action, if performed by a user.
+    pub actor_user_id: Option<Uuid>,
+    /// Performed action.
+    pub action: AuditAction,
+    /// Timestamp when the action was performed.
+    pub created_at: DateTime,
+    /// Identifier of the tenant owning the wishlist.
+    pub tenant_id: String,
+    /// State of the wishlist after the action, each entry with snapshot is a version of the wishlist.
+    #[serde(default)]
+    pub snapshot: Option<WishlistSnapshot>,
+}
+
+impl AuditEntry {
+    /// Constructs an audit entry of an action performed now.
+    ///
+    /// * `wishlist_id` - UUID of wishlist the action was performed on.
+    /// * `actor_user_id` - UUID of user who performed the action.
+    /// * `action` - Performed action.
+    /// * `tenant_id` - Identifier of the tenant owning the wishlist.
+    pub fn new(
+        wishlist_id: Uuid,
+        actor_user_id: Option<Uuid>,
+        action: AuditAction,
+        tenant_id: String,
+    ) -> Self {
+        Self {
+            _id: Uuid::new(),
+            wishlist_id,
+            actor_user_id,
+            action,
+            created_at: DateTime::now(),
+            tenant_id,
+            snapshot: None,
+        }
+    }
+
+    /// Adds the state of the wishlist after the action.
+    ///
+    /// * `snapshot` - Name and product variants of the wishlist after the action.
+    pub fn with_snapshot(mut self, snapshot: WishlistSnapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+}
+
+/// Action on a wishlist recorded in the audit history.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Wishlist was created.
+    Created,
+    /// Wishlist was updated.
+    Updated,
+    /// Ownership of the wishlist was moved to another user.
+    Reassigned {
+        from_user_id: Uuid,
+        to_user_id: Uuid,
+        previous_name: String,
+        name: String,
+    },
+    /// Wishlist was restored to a previous version.
+    Restored { version: u64 },
+    /// A product variant of the wishlist was marked as purchased.
+    ItemPurchased { product_variant_id: Uuid },
+    /// Wishlist was patched by a bulk update of an admin.
+    BulkUpdated {
+        removed_product_variant_ids: Vec<Uuid>,
+        archived: Option<bool>,
+    },
+}
+
+/// Name and product variants of a wishlist at a point in time.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WishlistSnapshot {
+    /// Name of the wishlist.
+    pub name: String,
+    /// UUIDs of the product variants of the wishlist.
+    pub product_variant_ids: Vec<Uuid>,
+}
+
+impl From<&Wishlist> for WishlistSnapshot {
+    fn from(wishlist: &Wishlist) -> Self {
+        let mut product_variant_ids: Vec<Uuid> = wishlist
+            .internal_product_variants
+            .iter()
+            .map(|product_variant| product_variant._id)
+            .collect();
+        product_variant_ids.sort();
+        Self {
+            name: wishlist.name.clone(),
+            product_variant_ids,
+        }
+    }
+}
+
+/// Stores an audit entry, failures are logged as the audited action was already performed.
+///
+/// * `collection` - MongoDB collection of audit entries.
+/// * `audit_entry` - Audit entry to store.
+pub async fn record_audit_entry(collection: &Collection<AuditEntry>, audit_entry: &AuditEntry) {
+    if let Err(error) = collection.insert_one(audit_entry, None).await {
+        warn!(
+            "Recording audit entry of wishlist of id: `{}` failed: {}",
+            audit_entry.wishlist_id, error
+        );
+    }
+}
+
+/// Stores audit entries in a single insert, failures are logged as the audited actions were already performed.
+///
+/// * `collection` - MongoDB collection of audit entries.
+/// * `audit_entries` - Audit entries to store.
+pub async fn record_audit_entries(
+    collection: &Collection<AuditEntry>,
+    audit_entries: &[AuditEntry],
+) {
+    if audit_entries.is_empty() {
+        return;
+    }
+    if let Err(error) = collection.insert_many(audit_entries, None).await {
+        warn!(
+            "Recording {} audit entries failed: {}",
+            audit_entries.len(),
+            error
+        );
+    }
+}
+
+/// Records an audit entry with snapshot for each wishlist changed by a write to multiple wishlists.
+///
+/// Snapshots are taken from the current state of the wishlists, so restores of later versions start from it.
+/// Failures are logged as the audited action was already performed.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `audit_collection` - MongoDB collection of audit entries.
+/// * `ids` - UUIDs of the changed wishlists.
+/// * `actor_user_id` - UUID of user who performed the action, `None` for background jobs.
+pub async fn record_wishlist_snapshots(
+    collection: &Collection<Wishlist>,
+    audit_collection: &Collection<AuditEntry>,
+    ids: &[Uuid],
+    actor_user_id: Option<Uuid>,
+) {
+    let wishlists: Vec<Wishlist> = match collection.find(doc! {"_id": {"$in": ids}}, None).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(wishlists) => wishlists,
+            Err(error) => {
+                warn!("Retrieving snapshots of wishlists failed: {}", error);
+                return;
+            }
+        },
+        Err(error) => {
+            warn!("Retrieving snapshots of wishlists failed: {}", error);
+            return;
+        }
+    };
+    let audit_entries: Vec<AuditEntry> = wishlists
+        .iter()
+        .map(|wishlist| {
+            AuditEntry::new(
+                wishlist._id,
+                actor_user_id,
+                AuditAction::Updated,
+                wishlist.tenant_id.clone(),
+            )
+            .with_snapshot(WishlistSnapshot::from(wishlist))
+        })
+        .collect();
+    record_audit_entries(audit_collection, &audit_entries).await;
+}
+
+/// Finds a version of a wishlist in its audit history and returns its number and snapshot.
+///
+/// Versions are the audit entries with snapshot in chronological order, starting at 1.
+///
+/// * `collection` - MongoDB collection of audit entries.
+/// * `tenant_id` - Tenant owning the wishlist.
+/// * `wishlist_id` - UUID of wishlist.
+/// * `selector` - Number or timestamp of the version.
+pub async fn find_wishlist_version(
+    collection: &Collection<AuditEntry>,
+    tenant_id: &TenantId,
+    wishlist_id: Uuid,
+    selector: &WishlistVersionSelector,
+) -> Result<(u64, WishlistSnapshot)> {
+    let filter = tenant_id.scope(doc! {"wishlist_id": wishlist_id, "snapshot": {"$ne": null}});
+    let message = "Retrieving audit history of wishlist failed in MongoDB.";
+    let not_found_message = format!(
+        "Version of wishlist of id: `{}` not found in its audit history.",
+        wishlist_id
+    );
+    let (version, audit_entry) = match selector {
+        WishlistVersionSelector::Version(version) => {
+            let find_options = FindOneOptions::builder()
+                .sort(doc! {"created_at": 1, "_id": 1})
+                .skip(
+                    version
+                        .checked_sub(1)
+                        .ok_or(Error::new(&not_found_message))?,
+                )
+                .build();
+            let audit_entry = collection
+                .find_one(filter, find_options)
+                .await
+                .map_err(|_| Error::new(message))?;
+            (*version, audit_entry)
+        }
+        WishlistVersionSelector::Timestamp(timestamp) => {
+            let mut timestamp_filter = filter.clone();
+            timestamp_filter.insert("created_at", doc! {"$lte": timestamp.0});
+            let find_options = FindOneOptions::builder()
+                .sort(doc! {"created_at": -1, "_id": -1})
+                .build();
+            let audit_entry = collection
+                .find_one(timestamp_filter.clone(), find_options)
+                .await
+                .map_err(|_| Error::new(message))?;
+            let version = collection
+                .count_documents(timestamp_filter, None)
+                .await
+                .map_err(|_| Error::new(message))?;
+            (version, audit_entry)
+        }
+    };
+    match audit_entry.and_then(|audit_entry| audit_entry.snapshot) {
+        Some(snapshot) => Ok((version, snapshot)),
+        None => Err(Error::new(not_found_message)),
+    }
+}
+
+/// Retrieves the most recent changes of a wishlist, derived from its audit history.
+///
+/// Returns the changes, most recent first, and whether further changes follow them.
+/// Audit entries are read in a single query, each entry with snapshot is compared with the following entry with snapshot.
+///
+/// * `collection` - MongoDB collection of audit entries.
+/// * `tenant_id` - Tenant owning the wishlist.
+/// * `wishlist_id` - UUID of wishlist.
+/// * `first` - Maximum number of changes to retrieve.
+/// * `after` - Cursor of the change after which changes are retrieved.
+pub async fn find_wishlist_activity(
+    collection: &Collection<AuditEntry>,
+    tenant_id: &TenantId,
+    wishlist_id: Uuid,
+    first: usize,
+    after: Option<ActivityCursor>,
+) -> Result<(Vec<WishlistActivity>, bool)> {
+    let message = "Retrieving audit history of wishlist failed in MongoDB.";
+    let mut filter = tenant_id.scope(doc! {"wishlist_id": wishlist_id});
+    if let Some(after) = &after {
+        filter.extend(after.to_document());
+    }
+    let find_options = FindOptions::builder()
+        .sort(ActivityCursor::sorting_document())
+        .build();
+    let mut cursor = collection
+        .find(filter, find_options)
+        .await
+        .map_err(|_| Error::new(message))?;
+    let mut activities = vec![];
+    let mut waiting_audit_entries: Vec<AuditEntry> = vec![];
+    while activities.len() <= first {
+        let audit_entry = cursor.try_next().await.map_err(|_| Error::new(message))?;
+        let previous_snapshot = audit_entry
+            .as_ref()
+            .filter(|audit_entry| audit_entry.snapshot.is_some());
+        if audit_entry.is_none() || previous_snapshot.is_some() {
+            for (index, waiting_audit_entry) in waiting_audit_entries.iter().enumerate() {
+                let previous_audit_entry = previous_snapshot.filter(|_| index == 0);
+                activities.extend(derive_activities(
+                    waiting_audit_entry,
+                    previous_audit_entry,
+                    after.as_ref(),
+                ));
+            }
+            waiting_audit_entries.clear();
+        }
+        match audit_entry {
+            Some(audit_entry)
+                if audit_entry.snapshot.is_some() || !waiting_audit_entries.is_empty() =>
+            {
+                waiting_audit_entries.push(audit_entry)
+            }
+            Some(audit_entry) => {
+                activities.extend(derive_activities(&audit_entry, None, after.as_ref()))
+            }
+            None => break,
+        }
+    }
+    let has_next_page = activities.len() > first;
+    activities.truncate(first);
+    Ok((activities, has_next_page))
+}
+
+/// Derives the changes of an audit entry, skipping the changes up to the cursor if the cursor points into the entry.
+///
+/// * `audit_entry` - Audit entry to derive the changes of.
+/// * `previous_audit_entry` - Previous audit entry with snapshot of the same wishlist.
+/// * `after` - Cursor of the change after which changes are retrieved.
+fn derive_activities(
+    audit_entry: &AuditEntry,
+    previous_audit_entry: Option<&AuditEntry>,
+    after: Option<&ActivityCursor>,
+) -> impl Iterator<Item = WishlistActivity> {
+    let skipped = match after {
+        Some(after) if after.audit_entry_id == audit_entry._id => after.index as usize + 1,
+        _ => 0,
+    };
+    WishlistActivity::from_audit_entry(audit_entry, previous_audit_entry)
+        .into_iter()
+        .skip(skipped)
+}
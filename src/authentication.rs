@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    parser::{
+        parse_query,
+        types::{DocumentOperations, OperationType},
+    },
+    Error, Request, Result,
+};
+use axum::http::header::{HeaderMap, AUTHORIZATION};
+use mongodb::Collection;
+
+use crate::api_key::{find_api_key, ApiKey, ApiKeyScope};
+use crate::authorization::{
+    check_permissions, ApiKeyPrincipal, AuthorizedUserHeader, PermissiveRoles,
+};
+use crate::graphql::model::uuid::Uuid;
+use crate::oidc::OidcAuthenticator;
+use crate::tenant::TenantId;
+
+/// Authenticator of the callers of the HTTP endpoints, shared by the GraphQL, Server-Sent Events and export endpoints.
+///
+/// Machine clients are authenticated with the `X-Api-Key` header, which takes precedence over user authentication.
+/// In OpenID Connect mode, users are authenticated with the bearer token of the `Authorization` header
+/// and the `Authorized-User` header is never trusted. Otherwise, the `Authorized-User` header of the gateway is used.
+#[derive(Clone)]
+pub struct RequestAuthenticator {
+    /// Authenticator of OpenID Connect access tokens, replaces the `Authorized-User` header if present.
+    pub oidc_authenticator: Option<Arc<OidcAuthenticator>>,
+    /// MongoDB collection of API keys of machine clients.
+    pub api_key_collection: Collection<ApiKey>,
+    /// Tenant of API keys created before tenants were introduced.
+    pub default_tenant_id: TenantId,
+}
+
+/// Authenticated caller of a request.
+pub enum Principal {
+    /// User authenticated with the `Authorized-User` header or an access token.
+    User(AuthorizedUserHeader),
+    /// Machine client authenticated with its API key.
+    ApiKey(ApiKeyPrincipal),
+}
+
+/// Result of a successful authentication.
+pub struct Authentication {
+    /// Authenticated caller of the request.
+    pub principal: Principal,
+    /// Tenant bound to the credentials of the request, i.e. the tenant claim of an access token or the tenant of an API key.
+    pub credential_tenant_id: Option<TenantId>,
+}
+
+/// Reason why the caller of a request could not be authenticated.
+#[derive(Debug, Clone)]
+pub enum AuthenticationError {
+    /// The request contains no credentials.
+    Missing(String),
+    /// The request contains credentials, which were rejected.
+    Rejected(String),
+}
+
+impl AuthenticationError {
+    /// Returns the message describing why the authentication failed.
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Missing(message) | Self::Rejected(message) => message,
+        }
+    }
+}
+
+impl RequestAuthenticator {
+    /// Authenticates the caller of a request from its headers.
+    ///
+    /// * `headers` - Header map containing headers of request.
+    /// * `request` - GraphQL request, `None` for subscriptions and requests outside of GraphQL.
+    pub async fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        request: Option<&Request>,
+    ) -> Result<Authentication, AuthenticationError> {
+        let api_key = headers
+            .get("X-Api-Key")
+            .and_then(|api_key| api_key.to_str().ok());
+        match (api_key, &self.oidc_authenticator) {
+            (Some(api_key), _) => authenticate_api_key(&self.api_key_collection, api_key, request)
+                .await
+                .map(|(api_key_principal, tenant_id)| Authentication {
+                    principal: Principal::ApiKey(api_key_principal),
+                    credential_tenant_id: Some(
+                        tenant_id.unwrap_or_else(|| self.default_tenant_id.clone()),
+                    ),
+                })
+                .map_err(|error| {
+                    let message = format!("Authentication failed. {}", error.message);
+                    AuthenticationError::Rejected(message)
+                }),
+            (None, Some(oidc_authenticator)) => {
+                let token = headers
+                    .get(AUTHORIZATION)
+                    .and_then(|authorization| authorization.to_str().ok())
+                    .and_then(|authorization| authorization.strip_prefix("Bearer "))
+                    .ok_or_else(|| {
+                        AuthenticationError::Missing(
+                            "Authentication failed. Bearer token of the Authorization header is not set."
+                                .to_string(),
+                        )
+                    })?;
+                match oidc_authenticator.authenticate(token).await {
+                    Ok((authorized_user_header, tenant_id)) => Ok(Authentication {
+                        principal: Principal::User(authorized_user_header),
+                        credential_tenant_id: tenant_id,
+                    }),
+                    Err(error) => {
+                        let message = format!(
+                            "Authentication failed. Access token is invalid: {}",
+                            error.message
+                        );
+                        Err(AuthenticationError::Rejected(message))
+                    }
+                }
+            }
+            (None, None) => match AuthorizedUserHeader::try_from(headers) {
+                Ok(authorized_user_header) => Ok(Authentication {
+                    principal: Principal::User(authorized_user_header),
+                    credential_tenant_id: None,
+                }),
+                Err(error) if headers.contains_key("Authorized-User") => {
+                    Err(AuthenticationError::Rejected(error.message))
+                }
+                Err(error) => Err(AuthenticationError::Missing(error.message)),
+            },
+        }
+    }
+}
+
+impl Principal {
+    /// Checks if the principal may read the wishlists of the user of UUID.
+    ///
+    /// Users are checked by `check_permissions`,
+    /// machine clients require the API key scope `READ_WISHLISTS` or `WRITE_WISHLISTS`.
+    ///
+    /// * `id` - UUID of the user owning the wishlists.
+    /// * `permissive_roles` - Roles which permit access to the wishlists of all users.
+    pub fn check_read_permissions(
+        &self,
+        id: Uuid,
+        permissive_roles: &PermissiveRoles,
+    ) -> Result<()> {
+        match self {
+            Self::User(authorized_user_header) => {
+                check_permissions(authorized_user_header, Some(id), permissive_roles)
+            }
+            Self::ApiKey(api_key_principal)
+                if api_key_principal
+                    .scopes
+                    .contains(&ApiKeyScope::ReadWishlists)
+                    || api_key_principal
+                        .scopes
+                        .contains(&ApiKeyScope::WriteWishlists) =>
+            {
+                Ok(())
+            }
+            Self::ApiKey(api_key_principal) => {
+                let message = format!(
+                    "Authentication failed for API key: `{}`. Operation requires scope: `{}`.",
+                    api_key_principal.name,
+                    ApiKeyScope::ReadWishlists.as_str()
+                );
+                Err(Error::new(message))
+            }
+        }
+    }
+}
+
+/// Authenticates a machine client with its API key.
+///
+/// Determines the type of the requested operation, which is required to authorize the API key scopes.
+/// Returns the principal of the machine client and the tenant of its API key, if any.
+///
+/// * `collection` - MongoDB collection of API keys.
+/// * `api_key` - Plaintext API key of the `X-Api-Key` header.
+/// * `request` - GraphQL request, `None` for subscriptions and requests outside of GraphQL.
+async fn authenticate_api_key(
+    collection: &Collection<ApiKey>,
+    api_key: &str,
+    request: Option<&Request>,
+) -> Result<(ApiKeyPrincipal, Option<TenantId>)> {
+    let api_key = find_api_key(collection, api_key).await?;
+    let operation_type = match request {
+        Some(request) => {
+            let document = parse_query(&request.query)?;
+            match (&request.operation_name, &document.operations) {
+                (_, DocumentOperations::Single(operation)) => operation.node.ty,
+                (Some(operation_name), DocumentOperations::Multiple(operations)) => operations
+                    .get(operation_name.as_str())
+                    .map(|operation| operation.node.ty)
+                    .unwrap_or(OperationType::Mutation),
+                (None, DocumentOperations::Multiple(_)) => OperationType::Mutation,
+            }
+        }
+        None => OperationType::Subscription,
+    };
+    let tenant_id = api_key.tenant_id.map(TenantId);
+    Ok((
+        ApiKeyPrincipal {
+            name: api_key.name,
+            scopes: api_key.scopes,
+            operation_type,
+        },
+        tenant_id,
+    ))
+}
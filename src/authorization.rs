@@ -1,12 +1,21 @@
-use async_graphql::{Context, Error, Result};
+use crate::api_key::ApiKeyScope;
+use crate::authentication::AuthenticationError;
+use crate::graphql::model::uuid::Uuid;
+use crate::settings::Settings;
+use async_graphql::{parser::types::OperationType, Context, Error, Result};
 use axum::http::HeaderMap;
-use bson::Uuid;
+use base64::{
+    engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use percent_encoding::percent_decode_str;
 use serde::Deserialize;
+use std::str::FromStr;
 
 /// `Authorized-User` HTTP header.
 #[derive(Deserialize, Debug)]
 pub struct AuthorizedUserHeader {
-    id: Uuid,
+    pub id: Uuid,
     roles: Vec<Role>,
 }
 
@@ -16,80 +25,336 @@ impl TryFrom<&HeaderMap> for AuthorizedUserHeader {
 
     /// Tries to extract the `Authorized-User` header from a header map.
     ///
-    /// Returns a GraphQL error if the extraction fails.
+    /// The header contains JSON, either plain, URL-encoded or base64-encoded with the standard or URL-safe alphabet.
+    /// Returns a GraphQL error which distinguishes a missing header from a malformed header if the extraction fails.
     fn try_from(header_map: &HeaderMap) -> Result<Self, Self::Error> {
-        if let Some(authorized_user_header_value) = header_map.get("Authorized-User") {
-            if let Ok(authorized_user_header_str) = authorized_user_header_value.to_str() {
-                let authorized_user_header: AuthorizedUserHeader =
-                    serde_json::from_str(authorized_user_header_str)?;
-                return Ok(authorized_user_header);
-            }
-        }
-        Err(Error::new(
-            "Authorization failed. Authorized-User header is not set or could not be parsed.",
-        ))
+        let authorized_user_header_value = header_map.get("Authorized-User").ok_or_else(|| {
+            Error::new("Authorization failed. Authorized-User header is not set.")
+        })?;
+        let authorized_user_header_str = authorized_user_header_value
+            .to_str()
+            .map_err(|_| malformed_header_error("contains non-ASCII characters"))?;
+        let json = decode_authorized_user_header(authorized_user_header_str.trim())
+            .map_err(malformed_header_error)?;
+        serde_json::from_str(&json).map_err(|error| malformed_header_error(&error.to_string()))
+    }
+}
+
+/// Decodes the JSON of an `Authorized-User` header value.
+///
+/// Values starting with `{` are plain JSON, values starting with `%7B` are URL-encoded JSON,
+/// all other values are treated as base64-encoded JSON.
+///
+/// * `value` - Value of the `Authorized-User` header.
+fn decode_authorized_user_header(value: &str) -> Result<String, &'static str> {
+    if value.is_empty() {
+        return Err("is empty");
+    }
+    if value.starts_with('{') {
+        return Ok(value.to_string());
+    }
+    if value.len() >= 3 && value[..3].eq_ignore_ascii_case("%7B") {
+        return percent_decode_str(value)
+            .decode_utf8()
+            .map(|json| json.into_owned())
+            .map_err(|_| "URL-encoded value is not valid UTF-8");
+    }
+    let engine = match value.contains(['-', '_']) {
+        true => &URL_SAFE_NO_PAD,
+        false => &STANDARD_NO_PAD,
+    };
+    let bytes = engine
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| "value is neither JSON nor URL-encoded or base64-encoded JSON")?;
+    String::from_utf8(bytes).map_err(|_| "base64-decoded value is not valid UTF-8")
+}
+
+/// Constructs the GraphQL error of an `Authorized-User` header which is set but can not be parsed.
+///
+/// * `reason` - Reason why the header can not be parsed.
+fn malformed_header_error(reason: &str) -> Error {
+    let message = format!(
+        "Authorization failed. Authorized-User header is malformed: {}.",
+        reason
+    );
+    Error::new(message)
+}
+
+impl AuthorizedUserHeader {
+    /// Constructs an `Authorized-User` header from role names, e.g. of the claims of an OpenID Connect token.
+    ///
+    /// Unknown role names are kept as `Role::Other`.
+    ///
+    /// * `id` - UUID of the user.
+    /// * `role_names` - Names of the roles of the user.
+    pub fn from_role_names(id: Uuid, role_names: &[&str]) -> Self {
+        let roles = role_names
+            .iter()
+            .map(|role_name| Role::from(role_name.to_lowercase()))
+            .collect();
+        Self { id, roles }
     }
 }
 
 /// Role of user.
-#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
-#[serde(rename_all = "snake_case")]
-enum Role {
+///
+/// Roles unknown to the wishlist service, e.g. added by the identity service later on, are parsed as `Role::Other`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "String")]
+pub enum Role {
     Buyer,
     Admin,
     Employee,
+    /// Role unknown to the wishlist service, with its name.
+    Other(String),
+}
+
+impl From<String> for Role {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "buyer" => Self::Buyer,
+            "admin" => Self::Admin,
+            "employee" => Self::Employee,
+            _ => Self::Other(name),
+        }
+    }
 }
 
-impl Role {
-    /// Defines if user has a permissive role.
-    fn is_permissive(self) -> bool {
-        match self {
-            Self::Buyer => false,
-            Self::Admin => true,
-            Self::Employee => true,
+/// Roles which permit access to the wishlists of all users, parsed from comma-separated role names.
+///
+/// Defaults to `admin` and `employee`. Roles of `Role::Other` are only permissive if listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissiveRoles(pub Vec<Role>);
+
+impl Default for PermissiveRoles {
+    fn default() -> Self {
+        Self(vec![Role::Admin, Role::Employee])
+    }
+}
+
+impl FromStr for PermissiveRoles {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(PermissiveRoles(
+            s.split(',')
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .map(Role::from)
+                .collect(),
+        ))
+    }
+}
+
+impl PermissiveRoles {
+    /// Defines if a role is permissive.
+    ///
+    /// * `role` - Role to check.
+    fn contains(&self, role: &Role) -> bool {
+        self.0.contains(role)
+    }
+}
+
+/// Machine client authenticated with the `X-Api-Key` HTTP header.
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    /// Name of the machine client.
+    pub name: String,
+    /// Scopes granted to the machine client.
+    pub scopes: Vec<ApiKeyScope>,
+    /// Type of the requested GraphQL operation.
+    pub operation_type: OperationType,
+}
+
+impl ApiKeyPrincipal {
+    /// Checks if the machine client was granted a scope.
+    ///
+    /// * `scope` - Required scope.
+    fn check_scope(&self, scope: ApiKeyScope) -> Result<()> {
+        match self.scopes.contains(&scope) {
+            true => Ok(()),
+            false => {
+                let message = format!(
+                    "Authentication failed for API key: `{}`. Operation requires scope: `{}`.",
+                    self.name,
+                    scope.as_str()
+                );
+                Err(Error::new(message))
+            }
         }
     }
 }
 
 /// Authorize user of UUID for a context.
 ///
-/// * `context` - GraphQL context containing the `Authorized-User` header.
+/// Machine clients are authorized by the scopes of their API key instead:
+/// queries require `READ_WISHLISTS` or `WRITE_WISHLISTS`, mutations require `WRITE_WISHLISTS`.
+///
+/// Users are authorized by `check_permissions` with the permissive roles of the service settings.
+///
+/// * `context` - GraphQL context containing the `Authorized-User` header or the API key principal.
 /// * `id` - Option of UUID of the user to authorize.
 pub fn authorize_user(ctx: &Context, id: Option<Uuid>) -> Result<()> {
+    if let Some(api_key_principal) = ctx.data_opt::<ApiKeyPrincipal>() {
+        return match api_key_principal.operation_type {
+            OperationType::Query
+                if api_key_principal
+                    .scopes
+                    .contains(&ApiKeyScope::ReadWishlists) =>
+            {
+                Ok(())
+            }
+            _ => api_key_principal.check_scope(ApiKeyScope::WriteWishlists),
+        };
+    }
     match ctx.data::<AuthorizedUserHeader>() {
-        Ok(authorized_user_header) => check_permissions(&authorized_user_header, id),
-        Err(_) => Err(Error::new(
+        Ok(authorized_user_header) => check_permissions(
+            authorized_user_header,
+            id,
+            &ctx.data::<Settings>()?.permissive_roles,
+        ),
+        Err(_) => Err(authentication_error(ctx)),
+    }
+}
+
+/// Constructs the GraphQL error of a context whose caller is not authenticated as user.
+///
+/// Reports the `AuthenticationError` of the request, e.g. a malformed `Authorized-User` header, if it is known.
+///
+/// * `context` - GraphQL context containing the authentication error of the request.
+fn authentication_error(ctx: &Context) -> Error {
+    match ctx.data_opt::<AuthenticationError>() {
+        Some(authentication_error) => Error::new(authentication_error.message()),
+        None => Error::new(
             "Authentication failed. Authorized-User header is not set or could not be parsed.",
-        )),
+        ),
+    }
+}
+
+/// Returns the UUID of the user calling a context.
+///
+/// * `context` - GraphQL context containing the `Authorized-User` header.
+pub fn authorized_user_id(ctx: &Context) -> Result<Uuid> {
+    match ctx.data::<AuthorizedUserHeader>() {
+        Ok(authorized_user_header) => Ok(authorized_user_header.id),
+        Err(_) => Err(authentication_error(ctx)),
+    }
+}
+
+/// Authorize user of a context as admin.
+///
+/// Machine clients require the API key scope `ADMIN` instead.
+///
+/// * `context` - GraphQL context containing the `Authorized-User` header or the API key principal.
+pub fn authorize_admin(ctx: &Context) -> Result<()> {
+    if let Some(api_key_principal) = ctx.data_opt::<ApiKeyPrincipal>() {
+        return api_key_principal.check_scope(ApiKeyScope::Admin);
+    }
+    match ctx.data::<AuthorizedUserHeader>() {
+        Ok(authorized_user_header) => match authorized_user_header.roles.contains(&Role::Admin) {
+            true => Ok(()),
+            false => {
+                let message = format!(
+                    "Authentication failed for user of UUID: `{}`. Operation requires role: `admin`.",
+                    authorized_user_header.id
+                );
+                Err(Error::new(message))
+            }
+        },
+        Err(_) => Err(authentication_error(ctx)),
+    }
+}
+
+/// Returns whether the caller of a context is an admin, without failing otherwise.
+///
+/// Machine clients are admins if their API key was granted the scope `ADMIN`.
+///
+/// * `context` - GraphQL context containing the `Authorized-User` header or the API key principal.
+pub fn is_admin(ctx: &Context) -> bool {
+    match (
+        ctx.data_opt::<ApiKeyPrincipal>(),
+        ctx.data_opt::<AuthorizedUserHeader>(),
+    ) {
+        (Some(api_key_principal), _) => api_key_principal.scopes.contains(&ApiKeyScope::Admin),
+        (None, Some(authorized_user_header)) => authorized_user_header.roles.contains(&Role::Admin),
+        (None, None) => false,
     }
 }
 
 /// Check if user of UUID has a valid permission according to the `Authorized-User` header.
 ///
 /// Permission is valid if the user has `Role::Buyer` and the same UUID as provided in the function parameter.
-/// Permission is valid if the user has a permissive role: `user.is_permissive() == true`, regardless of the users UUID.
+/// Permission is valid if the user has a permissive role: `permissive_roles.contains(role) == true`, regardless of the users UUID.
 ///
 /// * `authorized_user_header` - `Authorized-User` header containing the users UUID and role.
 /// * `id` - Option of UUID of the user to authorize.
+/// * `permissive_roles` - Roles which permit access to the wishlists of all users.
 pub fn check_permissions(
     authorized_user_header: &AuthorizedUserHeader,
     id: Option<Uuid>,
+    permissive_roles: &PermissiveRoles,
 ) -> Result<()> {
     let id_contained_in_header = id
-        .and_then(|id| Some(authorized_user_header.id == id))
+        .map(|id| authorized_user_header.id == id)
         .unwrap_or(false);
     if authorized_user_header
         .roles
         .iter()
-        .any(|role| role.is_permissive())
+        .any(|role| permissive_roles.contains(role))
         || id_contained_in_header
     {
-        return Ok(());
+        Ok(())
     } else {
         let message = format!(
             "Authentication failed for user of UUID: `{}`. Operation not permitted.",
             authorized_user_header.id
         );
-        return Err(Error::new(message));
+        Err(Error::new(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+
+    const JSON: &str = r#"{"id":"123e4567-e89b-12d3-a456-426614174000","roles":[]}"#;
+
+    #[test]
+    fn decode_authorized_user_header_accepts_plain_json() {
+        assert_eq!(decode_authorized_user_header(JSON).unwrap(), JSON);
+    }
+
+    #[test]
+    fn decode_authorized_user_header_accepts_url_encoded_json() {
+        let encoded =
+            "%7B%22id%22%3A%22123e4567-e89b-12d3-a456-426614174000%22%2C%22roles%22%3A%5B%5D%7D";
+        assert_eq!(decode_authorized_user_header(encoded).unwrap(), JSON);
+        let lowercase_prefix = encoded.replacen("%7B", "%7b", 1);
+        assert_eq!(
+            decode_authorized_user_header(&lowercase_prefix).unwrap(),
+            JSON
+        );
+    }
+
+    #[test]
+    fn decode_authorized_user_header_accepts_base64_encoded_json() {
+        let padded = STANDARD.encode(JSON);
+        assert_eq!(decode_authorized_user_header(&padded).unwrap(), JSON);
+        let url_safe = URL_SAFE_NO_PAD.encode("{\"a\":\"??>\"}");
+        assert!(url_safe.contains(['-', '_']));
+        assert_eq!(
+            decode_authorized_user_header(&url_safe).unwrap(),
+            "{\"a\":\"??>\"}"
+        );
+    }
+
+    #[test]
+    fn decode_authorized_user_header_rejects_empty_and_malformed_values() {
+        assert_eq!(decode_authorized_user_header(""), Err("is empty"));
+        assert_eq!(
+            decode_authorized_user_header("not base64!"),
+            Err("value is neither JSON nor URL-encoded or base64-encoded JSON")
+        );
     }
 }
@@ -0,0 +1,88 @@
+use std::future::Future;
+
+use async_graphql::Result;
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::dapr_client::DaprClient;
+use crate::graphql::model::uuid::Uuid;
+use crate::settings::Settings;
+
+/// Cache shared between service instances, backed by a Dapr state store.
+///
+/// Works with any state store component configured in Dapr. Caching is disabled if no state store is configured.
+/// Failing cache operations are logged and treated as cache misses.
+#[derive(Clone)]
+pub struct StateCache {
+    dapr_client: DaprClient,
+    store_name: Option<String>,
+    ttl_secs: u64,
+}
+
+impl StateCache {
+    /// Constructs a state cache according to the service settings.
+    ///
+    /// * `dapr_client` - Dapr client used to access the state store.
+    /// * `settings` - Service settings defining the state store and the time to live of cached values.
+    pub fn new(dapr_client: DaprClient, settings: &Settings) -> Self {
+        Self {
+            dapr_client,
+            store_name: settings.cache_state_store.clone(),
+            ttl_secs: settings.cache_ttl_secs,
+        }
+    }
+
+    /// Returns the cached value of a key or loads and caches the value on a cache miss.
+    ///
+    /// * `key` - Key of the cached value.
+    /// * `load` - Future loading the value on a cache miss.
+    pub async fn get_or_load<T, F>(&self, key: &str, load: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T>>,
+    {
+        let store_name = match &self.store_name {
+            Some(store_name) => store_name,
+            None => return load.await,
+        };
+        match self.dapr_client.get_state::<T>(store_name, key).await {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => {}
+            Err(error) => warn!("{}", error.message),
+        }
+        let value = load.await?;
+        if let Err(error) = self
+            .dapr_client
+            .save_state(store_name, key, &value, self.ttl_secs)
+            .await
+        {
+            warn!("{}", error.message);
+        }
+        Ok(value)
+    }
+
+    /// Removes the cached value of a key.
+    ///
+    /// * `key` - Key of the cached value.
+    pub async fn invalidate(&self, key: &str) {
+        if let Some(store_name) = &self.store_name {
+            if let Err(error) = self.dapr_client.delete_state(store_name, key).await {
+                warn!("{}", error.message);
+            }
+        }
+    }
+}
+
+/// Cache key of a wishlist.
+///
+/// * `id` - UUID of wishlist.
+pub fn wishlist_key(id: Uuid) -> String {
+    format!("wishlist-{}", id)
+}
+
+/// Cache key of the result of a product variant lookup in the catalog service.
+///
+/// * `id` - UUID of product variant.
+pub fn catalog_product_variant_key(id: Uuid) -> String {
+    format!("catalog-product-variant-{}", id)
+}
@@ -0,0 +1,212 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+};
+use bson::doc;
+use futures::TryStreamExt;
+use mongodb::{options::FindOptions, Collection, Database};
+use serde::{Deserialize, Serialize};
+
+use crate::api_key::hash_api_key;
+use crate::graphql::model::{date_time::DateTime, uuid::Uuid, wishlist::Wishlist};
+use crate::tenant::{DatabaseRouter, TenantId};
+
+/// Name of the MongoDB collection of calendar tokens, which is stored in the default database.
+pub const CALENDAR_TOKEN_COLLECTION: &str = "calendar_tokens";
+
+/// Maximum length of the lines of an iCalendar document in octets, according to RFC 5545.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Token granting access to the calendar of a user, which calendar applications pass as query parameter.
+///
+/// Only the SHA-256 hash of the token is stored, a user has at most one token per tenant.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalendarToken {
+    /// Hex-encoded SHA-256 hash of the token.
+    pub _id: String,
+    /// UUID of user whose calendar the token grants access to.
+    pub user_id: Uuid,
+    /// Identifier of the tenant of the user.
+    pub tenant_id: String,
+    /// Timestamp when the token was created.
+    pub created_at: DateTime,
+}
+
+/// Service state of the calendar endpoint.
+#[derive(Clone)]
+pub struct CalendarState {
+    /// Resolver of the MongoDB database of the tenant of a calendar token.
+    pub database_router: DatabaseRouter,
+}
+
+/// Query parameters of the calendar endpoint.
+#[derive(Deserialize)]
+pub struct CalendarQuery {
+    /// Plaintext calendar token of the user.
+    pub token: String,
+}
+
+/// HTTP endpoint rendering the upcoming occasions of the wishlists of a user as iCalendar document.
+///
+/// Serves `/calendar/{userId}.ics?token={token}`, the token is created with the `createCalendarToken` mutation.
+/// Each wishlist with an occasion date of today or later, which is neither archived nor suspended, is rendered as all-day event.
+///
+/// * `state` - Database router used to access the wishlists of the tenant of the token.
+/// * `file_name` - File name of the calendar, the UUID of the user followed by `.ics`.
+/// * `query` - Query parameters containing the calendar token.
+pub async fn calendar(
+    State(state): State<CalendarState>,
+    Path(file_name): Path<String>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let user_id = file_name
+        .strip_suffix(".ics")
+        .and_then(|user_id| Uuid::parse_str(user_id).ok())
+        .ok_or_else(|| {
+            let message = format!("Calendar: `{}` not found.", file_name);
+            (StatusCode::NOT_FOUND, message)
+        })?;
+    let token_collection: Collection<CalendarToken> =
+        state
+            .database_router
+            .default_database()
+            .collection::<CalendarToken>(CALENDAR_TOKEN_COLLECTION);
+    let calendar_token = match token_collection
+        .find_one(doc! {"_id": hash_api_key(&query.token)}, None)
+        .await
+    {
+        Ok(Some(calendar_token)) if calendar_token.user_id == user_id => calendar_token,
+        Ok(_) => {
+            let message = "Calendar token is unknown or belongs to another user.".to_string();
+            return Err((StatusCode::UNAUTHORIZED, message));
+        }
+        Err(_) => {
+            let message = "Retrieving calendar token failed in MongoDB.".to_string();
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, message));
+        }
+    };
+    let tenant_id = TenantId(calendar_token.tenant_id);
+    let wishlists = find_upcoming_occasions(
+        state.database_router.database(&tenant_id),
+        &tenant_id,
+        user_id,
+    )
+    .await
+    .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    Ok((
+        [(CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        render_calendar(&wishlists),
+    ))
+}
+
+/// Retrieves the wishlists of a user with an occasion date of today or later, which are neither archived nor suspended.
+///
+/// * `db_client` - MongoDB database of the tenant.
+/// * `tenant_id` - Tenant of the user.
+/// * `user_id` - UUID of user.
+async fn find_upcoming_occasions(
+    db_client: &Database,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+) -> Result<Vec<Wishlist>, String> {
+    let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+    let start_of_today = DateTime::now().timestamp_millis() / 86_400_000 * 86_400_000;
+    let filter = tenant_id.scope(doc! {
+        "user._id": user_id,
+        "occasion_date": {"$gte": bson::DateTime::from_millis(start_of_today)},
+        "archived_at": null,
+        "suspended": {"$ne": true},
+    });
+    let find_options = FindOptions::builder()
+        .sort(doc! {"occasion_date": 1, "_id": 1})
+        .build();
+    let message = "Retrieving wishlists of user failed in MongoDB.".to_string();
+    match collection.find(filter, find_options).await {
+        Ok(cursor) => cursor.try_collect().await.map_err(|_| message),
+        Err(_) => Err(message),
+    }
+}
+
+/// Renders wishlists with occasion dates as iCalendar document according to RFC 5545.
+///
+/// * `wishlists` - Wishlists to render, wishlists without occasion date are skipped.
+fn render_calendar(wishlists: &[Wishlist]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//MiSArch//Wishlist//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for wishlist in wishlists {
+        if let Some(occasion_date) = &wishlist.occasion_date {
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}@wishlist.misarch", wishlist._id));
+            lines.push(format!(
+                "DTSTAMP:{}",
+                format_date_time(&wishlist.last_updated_at)
+            ));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", format_date(occasion_date)));
+            lines.push(format!("SUMMARY:{}", escape_text(&wishlist.name)));
+            if let Some(description) = &wishlist.description {
+                lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+/// Formats a timestamp as iCalendar date, e.g. `20240101`.
+///
+/// * `date_time` - Timestamp to format.
+fn format_date(date_time: &DateTime) -> String {
+    format_date_time(date_time).chars().take(8).collect()
+}
+
+/// Formats a timestamp as iCalendar UTC date-time, e.g. `20240101T120000Z`.
+///
+/// * `date_time` - Timestamp to format.
+fn format_date_time(date_time: &DateTime) -> String {
+    let rfc3339 = date_time.0.try_to_rfc3339_string().unwrap_or_default();
+    let digits: String = rfc3339
+        .chars()
+        .take(19)
+        .filter(|c| *c != '-' && *c != ':')
+        .collect();
+    format!("{}Z", digits)
+}
+
+/// Escapes text values according to RFC 5545.
+///
+/// * `text` - Text to escape.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line into lines of at most 75 octets, continued lines start with a space.
+///
+/// * `line` - Content line to fold.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for c in line.chars() {
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,185 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_graphql::{Error, ErrorExtensions, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{ContentFilterAction, Settings};
+
+/// Timeout of a call of the external moderation service.
+const MODERATION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Check of user-provided text for offending content.
+#[async_trait::async_trait]
+pub trait ContentFilter: Send + Sync {
+    /// Checks a text and returns the reason if the text is offending.
+    ///
+    /// * `text` - User-provided text to check.
+    async fn check(&self, text: &str) -> Option<String>;
+}
+
+/// Content filter matching the words of a text case-insensitively against a list of offending words.
+pub struct WordListFilter {
+    words: HashSet<String>,
+}
+
+impl WordListFilter {
+    /// Constructs a word list filter.
+    ///
+    /// * `words` - Offending words.
+    pub fn new(words: &[String]) -> Self {
+        Self {
+            words: words.iter().map(|word| word.to_lowercase()).collect(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ContentFilter for WordListFilter {
+    async fn check(&self, text: &str) -> Option<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .find(|word| self.words.contains(word))
+            .map(|word| format!("Contains the blocked word: `{}`.", word))
+    }
+}
+
+/// Request body of the external moderation service.
+#[derive(Serialize)]
+struct ModerationRequest<'a> {
+    text: &'a str,
+}
+
+/// Response body of the external moderation service.
+#[derive(Deserialize)]
+struct ModerationResponse {
+    /// Whether the text is offending.
+    flagged: bool,
+    /// Reason why the text is offending.
+    reason: Option<String>,
+}
+
+/// Content filter calling an external moderation service.
+///
+/// The service receives `{"text": ...}` and responds with `{"flagged": bool, "reason": string?}`.
+/// Failed calls are logged and the text is accepted, so an unavailable moderation service does not block all mutations.
+pub struct ModerationServiceFilter {
+    http_client: reqwest::Client,
+    url: String,
+}
+
+impl ModerationServiceFilter {
+    /// Constructs a moderation service filter.
+    ///
+    /// * `url` - URL of the moderation endpoint.
+    pub fn new(url: String) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            url,
+        }
+    }
+
+    /// Calls the moderation service.
+    ///
+    /// * `text` - User-provided text to check.
+    async fn moderate(&self, text: &str) -> reqwest::Result<ModerationResponse> {
+        self.http_client
+            .post(&self.url)
+            .timeout(MODERATION_TIMEOUT)
+            .json(&ModerationRequest { text })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ContentFilter for ModerationServiceFilter {
+    async fn check(&self, text: &str) -> Option<String> {
+        match self.moderate(text).await {
+            Ok(response) if response.flagged => Some(
+                response
+                    .reason
+                    .unwrap_or("Flagged by moderation service.".to_string()),
+            ),
+            Ok(_) => None,
+            Err(error) => {
+                warn!(
+                    "Calling moderation service: `{}` failed, accepting text: {}",
+                    self.url, error
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Content filters applied to user-provided text of wishlists before it is stored.
+///
+/// Offending text is rejected or flagged for review, according to the configured action.
+#[derive(Clone)]
+pub struct ContentPolicy {
+    filters: Vec<Arc<dyn ContentFilter>>,
+    action: ContentFilterAction,
+}
+
+impl ContentPolicy {
+    /// Constructs the content policy with the word list and moderation service of the service settings.
+    ///
+    /// * `settings` - Service settings defining the content filters.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut content_policy = Self {
+            filters: vec![],
+            action: settings.content_filter_action,
+        };
+        if !settings.content_filter_words.0.is_empty() {
+            content_policy =
+                content_policy.with_filter(WordListFilter::new(&settings.content_filter_words.0));
+        }
+        if let Some(content_moderation_url) = &settings.content_moderation_url {
+            content_policy = content_policy
+                .with_filter(ModerationServiceFilter::new(content_moderation_url.clone()));
+        }
+        content_policy
+    }
+
+    /// Adds a content filter, which is applied after the previously added filters.
+    ///
+    /// * `filter` - Content filter to add.
+    pub fn with_filter(mut self, filter: impl ContentFilter + 'static) -> Self {
+        self.filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Checks user-provided texts and returns whether any text is flagged for review.
+    ///
+    /// Fails with the error code `CONTENT_REJECTED` if a text is offending and offending content is rejected.
+    ///
+    /// * `texts` - Names of the fields and texts to check.
+    pub async fn check(&self, texts: &[(&str, &str)]) -> Result<bool> {
+        let mut flagged = false;
+        for (field, text) in texts {
+            for filter in &self.filters {
+                if let Some(reason) = filter.check(text).await {
+                    match self.action {
+                        ContentFilterAction::Reject => {
+                            let message = format!("Field `{}` was rejected: {}", field, reason);
+                            return Err(Error::new(message).extend_with(|_, extensions| {
+                                extensions.set("code", "CONTENT_REJECTED");
+                                extensions.set("field", *field);
+                            }));
+                        }
+                        ContentFilterAction::Flag => {
+                            warn!("Field `{}` was flagged: {}", field, reason);
+                            flagged = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(flagged)
+    }
+}
@@ -0,0 +1,283 @@
+use std::{collections::HashMap, env};
+
+use async_graphql::{Error, Result};
+use log::info;
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Name of the Dapr pub/sub component used by the wishlist service.
+const PUBSUB_NAME: &str = "pubsub";
+
+/// Default HTTP port of the Dapr sidecar.
+const DEFAULT_DAPR_HTTP_PORT: &str = "3500";
+
+/// Entry of a Dapr bulk publish request.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkPublishEntry<'a, T: Serialize> {
+    /// Identifier of the entry, unique within the request.
+    entry_id: String,
+    /// Event data.
+    event: &'a T,
+    /// Content type of the event data.
+    content_type: &'static str,
+}
+
+/// Item of a Dapr state store save request.
+#[derive(Serialize)]
+struct StateItem<'a, T: Serialize> {
+    /// Key of the value.
+    key: &'a str,
+    /// Value.
+    value: &'a T,
+    /// Metadata of the item.
+    metadata: StateItemMetadata,
+}
+
+/// Metadata of a Dapr state store item.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StateItemMetadata {
+    /// Time to live of the item in seconds.
+    ttl_in_seconds: String,
+}
+
+/// HTTP client to interact with the Dapr sidecar.
+#[derive(Clone)]
+pub struct DaprClient {
+    http_client: reqwest::Client,
+    base_url: String,
+}
+
+impl DaprClient {
+    /// Constructs a Dapr client for the sidecar port defined in `$DAPR_HTTP_PORT`.
+    ///
+    /// Falls back to the default Dapr HTTP port `3500`.
+    pub fn from_env() -> Self {
+        let port = env::var("DAPR_HTTP_PORT").unwrap_or(DEFAULT_DAPR_HTTP_PORT.to_string());
+        Self {
+            http_client: reqwest::Client::new(),
+            base_url: format!("http://localhost:{}", port),
+        }
+    }
+
+    /// Publishes an event to a topic of the Dapr pub/sub component.
+    ///
+    /// * `topic` - Topic to publish the event to.
+    /// * `data` - Event data, serialized as JSON.
+    pub async fn publish_event<T: Serialize>(&self, topic: &str, data: &T) -> Result<()> {
+        let url = format!("{}/v1.0/publish/{}/{}", self.base_url, PUBSUB_NAME, topic);
+        let response = self
+            .http_client
+            .post(url)
+            .json(data)
+            .send()
+            .await
+            .map_err(|_| Error::new(format!("Publishing event to topic: `{}` failed.", topic)))?;
+        match response.status().is_success() {
+            true => {
+                info!("Published event to topic: `{}`.", topic);
+                Ok(())
+            }
+            false => {
+                let message = format!(
+                    "Publishing event to topic: `{}` failed with status: `{}`.",
+                    topic,
+                    response.status()
+                );
+                Err(Error::new(message))
+            }
+        }
+    }
+
+    /// Publishes multiple events to a topic of the Dapr pub/sub component with a single bulk publish request.
+    ///
+    /// * `topic` - Topic to publish the events to.
+    /// * `events` - Event data, serialized as JSON.
+    pub async fn publish_events<T: Serialize>(&self, topic: &str, events: &[T]) -> Result<()> {
+        let url = format!(
+            "{}/v1.0-alpha1/publish/bulk/{}/{}",
+            self.base_url, PUBSUB_NAME, topic
+        );
+        let entries: Vec<BulkPublishEntry<T>> = events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| BulkPublishEntry {
+                entry_id: index.to_string(),
+                event,
+                content_type: "application/json",
+            })
+            .collect();
+        let message = format!(
+            "Publishing {} events to topic: `{}` failed.",
+            events.len(),
+            topic
+        );
+        let response = self
+            .http_client
+            .post(url)
+            .json(&entries)
+            .send()
+            .await
+            .map_err(|_| Error::new(&message))?;
+        match response.status().is_success() {
+            true => {
+                info!("Published {} events to topic: `{}`.", events.len(), topic);
+                Ok(())
+            }
+            false => Err(Error::new(format!(
+                "{} Status: `{}`.",
+                message,
+                response.status()
+            ))),
+        }
+    }
+
+    /// Retrieves a value of a Dapr state store.
+    ///
+    /// Returns `None` if the key is not present in the state store.
+    ///
+    /// * `store_name` - Name of the Dapr state store component.
+    /// * `key` - Key of the value.
+    pub async fn get_state<T: DeserializeOwned>(
+        &self,
+        store_name: &str,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let url = format!("{}/v1.0/state/{}/{}", self.base_url, store_name, key);
+        let message = format!(
+            "Retrieving key: `{}` of state store: `{}` failed.",
+            key, store_name
+        );
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|_| Error::new(&message))?;
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .json::<T>()
+                .await
+                .map(Some)
+                .map_err(|_| Error::new(message)),
+            _ => Err(Error::new(message)),
+        }
+    }
+
+    /// Saves a value in a Dapr state store.
+    ///
+    /// * `store_name` - Name of the Dapr state store component.
+    /// * `key` - Key of the value.
+    /// * `value` - Value, serialized as JSON.
+    /// * `ttl_secs` - Time to live of the value in seconds.
+    pub async fn save_state<T: Serialize>(
+        &self,
+        store_name: &str,
+        key: &str,
+        value: &T,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        let url = format!("{}/v1.0/state/{}", self.base_url, store_name);
+        let message = format!(
+            "Saving key: `{}` in state store: `{}` failed.",
+            key, store_name
+        );
+        let items = [StateItem {
+            key,
+            value,
+            metadata: StateItemMetadata {
+                ttl_in_seconds: ttl_secs.to_string(),
+            },
+        }];
+        match self.http_client.post(url).json(&items).send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            _ => Err(Error::new(message)),
+        }
+    }
+
+    /// Deletes a value of a Dapr state store.
+    ///
+    /// * `store_name` - Name of the Dapr state store component.
+    /// * `key` - Key of the value.
+    pub async fn delete_state(&self, store_name: &str, key: &str) -> Result<()> {
+        let url = format!("{}/v1.0/state/{}/{}", self.base_url, store_name, key);
+        let message = format!(
+            "Deleting key: `{}` of state store: `{}` failed.",
+            key, store_name
+        );
+        match self.http_client.delete(url).send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            _ => Err(Error::new(message)),
+        }
+    }
+
+    /// Retrieves a secret of a Dapr secret store.
+    ///
+    /// Returns the values of the secret by name, as secrets can consist of multiple values.
+    ///
+    /// * `store_name` - Name of the Dapr secret store component.
+    /// * `key` - Key of the secret.
+    pub async fn get_secret(&self, store_name: &str, key: &str) -> Result<HashMap<String, String>> {
+        let url = format!("{}/v1.0/secrets/{}/{}", self.base_url, store_name, key);
+        let message = format!(
+            "Retrieving secret: `{}` of secret store: `{}` failed.",
+            key, store_name
+        );
+        match self.http_client.get(url).send().await {
+            Ok(response) if response.status().is_success() => response
+                .json::<HashMap<String, String>>()
+                .await
+                .map_err(|_| Error::new(message)),
+            _ => Err(Error::new(message)),
+        }
+    }
+
+    /// Checks the health of the Dapr sidecar.
+    pub async fn check_health(&self) -> Result<()> {
+        let url = format!("{}/v1.0/healthz", self.base_url);
+        match self.http_client.get(url).send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => {
+                let message = format!(
+                    "Dapr sidecar is unhealthy with status: `{}`.",
+                    response.status()
+                );
+                Err(Error::new(message))
+            }
+            Err(_) => Err(Error::new("Dapr sidecar is not reachable.")),
+        }
+    }
+
+    /// Invokes a method of another service through Dapr service invocation.
+    ///
+    /// Sends `body` as JSON via HTTP POST and deserializes the JSON response.
+    ///
+    /// * `app_id` - Dapr app id of the invoked service.
+    /// * `method` - Method (HTTP path) of the invoked service.
+    /// * `body` - Request body.
+    pub async fn invoke_service<T: Serialize, R: DeserializeOwned>(
+        &self,
+        app_id: &str,
+        method: &str,
+        body: &T,
+    ) -> Result<R> {
+        let url = format!("{}/v1.0/invoke/{}/method/{}", self.base_url, app_id, method);
+        let message = format!(
+            "Invoking method: `{}` of service: `{}` failed.",
+            method, app_id
+        );
+        let response = self
+            .http_client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|_| Error::new(&message))?;
+        if !response.status().is_success() {
+            return Err(Error::new(message));
+        }
+        response.json::<R>().await.map_err(|_| Error::new(message))
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use bson::{doc, Document};
+use log::{info, warn};
+use mongodb::{options::IndexOptions, Database, IndexModel};
+
+use crate::settings::Settings;
+
+/// Creates the MongoDB indexes of the wishlist service.
+///
+/// Creating an index which already exists has no effect. Failures are logged, as the service works without indexes.
+///
+/// * `db_client` - MongoDB database client.
+/// * `settings` - Service settings defining index options.
+pub async fn create_indexes(db_client: &Database, settings: &Settings) {
+    let wishlist_indexes = vec![
+        IndexModel::builder()
+            .keys(doc! {"tenant_id": 1, "user._id": 1, "item_count": 1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"tenant_id": 1, "user._id": 1, "last_updated_at": -1, "_id": -1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"expires_at": 1})
+            .options(IndexOptions::builder().sparse(true).build())
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"target_prices.product_variant_id": 1})
+            .options(IndexOptions::builder().sparse(true).build())
+            .build(),
+    ];
+    create_collection_indexes(db_client, "wishlists", wishlist_indexes).await;
+    let idempotency_key_indexes = vec![IndexModel::builder()
+        .keys(doc! {"created_at": 1})
+        .options(
+            IndexOptions::builder()
+                .expire_after(Duration::from_secs(settings.idempotency_key_ttl_secs))
+                .build(),
+        )
+        .build()];
+    create_collection_indexes(db_client, "idempotency_keys", idempotency_key_indexes).await;
+    let api_key_indexes = vec![IndexModel::builder()
+        .keys(doc! {"name": 1})
+        .options(IndexOptions::builder().unique(true).build())
+        .build()];
+    create_collection_indexes(db_client, "api_keys", api_key_indexes).await;
+    let creation_throttle_indexes = vec![
+        IndexModel::builder()
+            .keys(doc! {"tenant_id": 1, "creator": 1, "creation": 1, "created_at": 1})
+            .build(),
+        IndexModel::builder()
+            .keys(doc! {"created_at": 1})
+            .options(
+                IndexOptions::builder()
+                    .expire_after(Duration::from_secs(settings.creation_throttle_window_secs))
+                    .build(),
+            )
+            .build(),
+    ];
+    create_collection_indexes(db_client, "creation_throttle", creation_throttle_indexes).await;
+    let calendar_token_indexes = vec![IndexModel::builder()
+        .keys(doc! {"tenant_id": 1, "user_id": 1})
+        .build()];
+    create_collection_indexes(db_client, "calendar_tokens", calendar_token_indexes).await;
+    let audit_entry_indexes = vec![IndexModel::builder()
+        .keys(doc! {"tenant_id": 1, "wishlist_id": 1, "created_at": -1, "_id": -1})
+        .build()];
+    create_collection_indexes(db_client, "audit_entries", audit_entry_indexes).await;
+    let digest_subscription_indexes = vec![IndexModel::builder()
+        .keys(doc! {"last_digest_at": 1})
+        .build()];
+    create_collection_indexes(
+        db_client,
+        "digest_subscriptions",
+        digest_subscription_indexes,
+    )
+    .await;
+}
+
+/// Records the documents before and after changes of wishlists, which change streams use to determine added product variants.
+///
+/// Requires MongoDB 6.0 or newer. Failures are logged, as only the detection of added product variants depends on it.
+///
+/// * `db_client` - MongoDB database client.
+pub async fn enable_change_stream_images(db_client: &Database) {
+    let command = doc! {
+        "collMod": "wishlists",
+        "changeStreamPreAndPostImages": {"enabled": true},
+    };
+    match db_client.run_command(command, None).await {
+        Ok(_) => info!("Enabled change stream images of collection: `wishlists`."),
+        Err(error) => warn!(
+            "Enabling change stream images of collection: `wishlists` failed: {}",
+            error
+        ),
+    }
+}
+
+/// Creates indexes of a MongoDB collection.
+///
+/// * `db_client` - MongoDB database client.
+/// * `collection_name` - Name of collection.
+/// * `indexes` - Indexes to create.
+async fn create_collection_indexes(
+    db_client: &Database,
+    collection_name: &str,
+    indexes: Vec<IndexModel>,
+) {
+    let collection = db_client.collection::<Document>(collection_name);
+    match collection.create_indexes(indexes, None).await {
+        Ok(_) => info!("Created indexes of collection: `{}`.", collection_name),
+        Err(error) => warn!(
+            "Creating indexes of collection: `{}` failed: {}",
+            collection_name, error
+        ),
+    }
+}
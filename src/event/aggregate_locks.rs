@@ -0,0 +1,63 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::graphql::model::uuid::Uuid;
+
+/// Locks of aggregates with events in progress, referenced by the UUID of the aggregate.
+type LockMap = Arc<Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>>;
+
+/// Serializes the handling of events of the same aggregate, the user or product variant an event refers to.
+///
+/// Events of the same aggregate are handled one after another in the order they arrive, as Tokio mutexes are fair.
+/// Events of different aggregates are handled concurrently.
+/// The lock of an aggregate is removed as soon as no event of the aggregate is in progress.
+#[derive(Clone, Default)]
+pub struct AggregateLocks {
+    locks: LockMap,
+}
+
+impl AggregateLocks {
+    /// Constructs the aggregate locks without locked aggregates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits until all earlier events of the aggregate are handled and locks the aggregate until the guard is dropped.
+    ///
+    /// * `id` - UUID of the aggregate.
+    pub async fn lock(&self, id: Uuid) -> AggregateGuard {
+        let mut guard = AggregateGuard {
+            locks: self.locks.clone(),
+            id,
+            lock_guard: None,
+        };
+        let lock = self.locks.lock().unwrap().entry(id).or_default().clone();
+        guard.lock_guard = Some(lock.lock_owned().await);
+        guard
+    }
+}
+
+/// Lock of an aggregate, which is released when dropped.
+pub struct AggregateGuard {
+    locks: LockMap,
+    id: Uuid,
+    lock_guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for AggregateGuard {
+    /// Releases the lock and removes it if no other event of the aggregate waits for it.
+    fn drop(&mut self) {
+        self.lock_guard.take();
+        let mut locks = self.locks.lock().unwrap();
+        if locks
+            .get(&self.id)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.id);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use async_graphql::Result;
+use bson::{doc, DateTime};
+use futures::TryStreamExt;
+use log::{info, warn};
+use mongodb::{
+    change_stream::event::{ChangeStreamEvent, ResumeToken},
+    options::{ChangeStreamOptions, FullDocumentType, ReplaceOptions},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::dapr_client::DaprClient;
+use crate::event::outgoing_events::{WishlistChangeEventData, WISHLIST_CDC_TOPIC};
+use crate::graphql::model::{uuid::Uuid, wishlist::Wishlist};
+
+/// Delay before the change stream is reopened after a failure.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Identifier of the checkpoint of the change stream of the wishlist collection.
+const WISHLIST_CHECKPOINT_ID: &str = "wishlists";
+
+/// Position in the change stream up to which changes were published.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeDataCaptureCheckpoint {
+    /// Name of the collection whose change stream is checkpointed.
+    pub _id: String,
+    /// Resume token of the last published change.
+    pub resume_token: ResumeToken,
+    /// Timestamp when the checkpoint was last updated.
+    pub updated_at: DateTime,
+}
+
+/// Spawns a task publishing all changes of wishlist documents to the change-data-capture topic.
+///
+/// Changes are published one at a time in the order of the change stream.
+/// After each published change, the resume token is stored, so publishing resumes after the last published change after restarts and failures.
+///
+/// * `db_client` - MongoDB database containing the wishlists and the checkpoint.
+/// * `dapr_client` - Dapr client used to publish the changes.
+pub fn spawn_change_data_capture(db_client: Database, dapr_client: DaprClient) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(error) = publish_changes(&db_client, &dapr_client).await {
+                warn!(
+                    "Change data capture of database: `{}` failed: {}",
+                    db_client.name(),
+                    error.message
+                );
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    });
+}
+
+/// Watches the change stream of the wishlist collection and publishes its changes until the stream fails or ends.
+///
+/// * `db_client` - MongoDB database containing the wishlists and the checkpoint.
+/// * `dapr_client` - Dapr client used to publish the changes.
+async fn publish_changes(db_client: &Database, dapr_client: &DaprClient) -> Result<()> {
+    let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+    let checkpoint_collection: Collection<ChangeDataCaptureCheckpoint> =
+        db_client.collection::<ChangeDataCaptureCheckpoint>("change_data_capture_checkpoints");
+    let checkpoint = checkpoint_collection
+        .find_one(doc! {"_id": WISHLIST_CHECKPOINT_ID}, None)
+        .await?;
+    let options = ChangeStreamOptions::builder()
+        .full_document(Some(FullDocumentType::UpdateLookup))
+        .start_after(checkpoint.map(|checkpoint| checkpoint.resume_token))
+        .build();
+    let mut change_stream = collection.watch(None, options).await?;
+    info!(
+        "Publishing changes of wishlists of database: `{}`.",
+        db_client.name()
+    );
+    while let Some(event) = change_stream.try_next().await? {
+        dapr_client
+            .publish_event(WISHLIST_CDC_TOPIC, &change_event_data(&event))
+            .await?;
+        let checkpoint = ChangeDataCaptureCheckpoint {
+            _id: WISHLIST_CHECKPOINT_ID.to_string(),
+            resume_token: event.id,
+            updated_at: DateTime::now(),
+        };
+        checkpoint_collection
+            .replace_one(
+                doc! {"_id": WISHLIST_CHECKPOINT_ID},
+                &checkpoint,
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Maps a change of the wishlist collection to the event data of the change-data-capture topic.
+///
+/// * `event` - Change of the change stream.
+fn change_event_data(event: &ChangeStreamEvent<Wishlist>) -> WishlistChangeEventData {
+    let wishlist_id = event
+        .document_key
+        .as_ref()
+        .and_then(|document_key| document_key.get("_id"))
+        .and_then(|id| bson::from_bson::<Uuid>(id.clone()).ok());
+    let changed_at = event.wall_time.or_else(|| {
+        event
+            .cluster_time
+            .map(|cluster_time| DateTime::from_millis(i64::from(cluster_time.time) * 1000))
+    });
+    WishlistChangeEventData {
+        operation_type: event.operation_type.clone(),
+        wishlist_id,
+        wishlist: event.full_document.clone(),
+        changed_at: changed_at.map(Into::into),
+    }
+}
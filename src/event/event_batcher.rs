@@ -0,0 +1,135 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_graphql::{Error, Result};
+use log::warn;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+
+use crate::dapr_client::DaprClient;
+
+/// Batches outbound events into Dapr bulk publish requests.
+///
+/// Events of a topic are published when the maximum batch size is reached or the flush interval elapsed,
+/// whichever comes first. Intended for bulk operations affecting many wishlists, e.g. cleanup jobs.
+#[derive(Clone)]
+pub struct EventBatcher {
+    sender: UnboundedSender<BatcherMessage>,
+}
+
+/// Message to the task publishing the batches.
+enum BatcherMessage {
+    /// Event data to publish to a topic.
+    Event(String, Value),
+    /// Request to publish all pending events, acknowledged when published.
+    Flush(oneshot::Sender<()>),
+}
+
+impl EventBatcher {
+    /// Spawns the task publishing the batches and returns a handle to enqueue events.
+    ///
+    /// * `dapr_client` - Dapr client used to publish the batches.
+    /// * `max_batch_size` - Number of events of a topic which triggers a flush.
+    /// * `flush_interval` - Interval in which all pending events are published.
+    pub fn spawn(dapr_client: DaprClient, max_batch_size: usize, flush_interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_batcher(
+            dapr_client,
+            receiver,
+            max_batch_size,
+            flush_interval,
+        ));
+        Self { sender }
+    }
+
+    /// Enqueues an event to be published to a topic with the next batch.
+    ///
+    /// * `topic` - Topic to publish the event to.
+    /// * `data` - Event data, serialized as JSON.
+    pub fn publish<T: Serialize>(&self, topic: &str, data: &T) -> Result<()> {
+        let value = serde_json::to_value(data)?;
+        self.sender
+            .send(BatcherMessage::Event(topic.to_string(), value))
+            .map_err(|_| Error::new("Event batcher is not running."))
+    }
+
+    /// Publishes all pending events right away and waits until they are published, e.g. before shutdown.
+    pub async fn flush(&self) {
+        let (acknowledgement_sender, acknowledgement_receiver) = oneshot::channel();
+        if self
+            .sender
+            .send(BatcherMessage::Flush(acknowledgement_sender))
+            .is_ok()
+        {
+            let _ = acknowledgement_receiver.await;
+        }
+    }
+}
+
+/// Receives enqueued events and publishes them in batches until all handles are dropped.
+///
+/// Flush requests publish all pending events before they are acknowledged.
+///
+/// * `dapr_client` - Dapr client used to publish the batches.
+/// * `receiver` - Receiver of enqueued events.
+/// * `max_batch_size` - Number of events of a topic which triggers a flush.
+/// * `flush_interval` - Interval in which all pending events are published.
+async fn run_batcher(
+    dapr_client: DaprClient,
+    mut receiver: UnboundedReceiver<BatcherMessage>,
+    max_batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut pending_events: HashMap<String, Vec<Value>> = HashMap::new();
+    let mut interval = tokio::time::interval(flush_interval);
+    loop {
+        tokio::select! {
+            maybe_message = receiver.recv() => match maybe_message {
+                Some(BatcherMessage::Event(topic, event)) => {
+                    let events = pending_events.entry(topic.clone()).or_default();
+                    events.push(event);
+                    if events.len() >= max_batch_size {
+                        let events = std::mem::take(events);
+                        publish_batch(&dapr_client, &topic, events).await;
+                    }
+                }
+                Some(BatcherMessage::Flush(acknowledgement_sender)) => {
+                    flush(&dapr_client, &mut pending_events).await;
+                    let _ = acknowledgement_sender.send(());
+                }
+                None => {
+                    flush(&dapr_client, &mut pending_events).await;
+                    return;
+                }
+            },
+            _ = interval.tick() => flush(&dapr_client, &mut pending_events).await,
+        }
+    }
+}
+
+/// Publishes all pending events.
+///
+/// * `dapr_client` - Dapr client used to publish the batches.
+/// * `pending_events` - Pending events by topic.
+async fn flush(dapr_client: &DaprClient, pending_events: &mut HashMap<String, Vec<Value>>) {
+    for (topic, events) in pending_events.drain() {
+        publish_batch(dapr_client, &topic, events).await;
+    }
+}
+
+/// Publishes a batch of events to a topic, logging failures.
+///
+/// * `dapr_client` - Dapr client used to publish the batch.
+/// * `topic` - Topic to publish the events to.
+/// * `events` - Events to publish.
+async fn publish_batch(dapr_client: &DaprClient, topic: &str, events: Vec<Value>) {
+    if events.is_empty() {
+        return;
+    }
+    if let Err(error) = dapr_client.publish_events(topic, &events).await {
+        warn!("{}", error.message);
+    }
+}
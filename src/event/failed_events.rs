@@ -0,0 +1,112 @@
+use axum::http::StatusCode;
+use bson::doc;
+use log::warn;
+use mongodb::{options::UpdateOptions, Collection};
+
+use crate::graphql::model::{date_time::DateTime, failed_event::FailedEvent};
+
+use super::http_event_service::{handle_event, Event, EventData, HttpEventServiceState};
+
+/// Name of the MongoDB collection of failed events, which is stored in the default database.
+pub const FAILED_EVENT_COLLECTION: &str = "failed_events";
+
+/// Records a failed attempt to handle an event, so admins can inspect and reprocess it.
+///
+/// Repeated failures of the same event increase the attempts of its record. Events without identifier are not recorded.
+/// Failed recordings are logged, as the event is redelivered by Dapr anyway.
+///
+/// * `collection` - MongoDB collection of failed events.
+/// * `event` - Event whose handling failed.
+/// * `status` - Status code the handling failed with.
+pub async fn record_failed_event(
+    collection: &Collection<FailedEvent>,
+    event: &Event,
+    status: StatusCode,
+) {
+    let id = match &event.id {
+        Some(id) => id,
+        None => return,
+    };
+    let data = match bson::to_document(&event.data) {
+        Ok(data) => data,
+        Err(error) => {
+            warn!(
+                "Serializing data of failed event: `{}` failed: {}",
+                id, error
+            );
+            return;
+        }
+    };
+    let current_timestamp = DateTime::now();
+    let update_options = UpdateOptions::builder().upsert(true).build();
+    if let Err(error) = collection
+        .update_one(
+            doc! {"_id": id},
+            doc! {
+                "$inc": {"attempts": 1},
+                "$set": {
+                    "error": format!("Handling failed with status: `{}`.", status),
+                    "last_failed_at": current_timestamp,
+                },
+                "$setOnInsert": {
+                    "topic": &event.topic,
+                    "subject_id": event.data.id,
+                    "data": data,
+                    "first_failed_at": current_timestamp,
+                },
+            },
+            update_options,
+        )
+        .await
+    {
+        warn!("Recording failed event: `{}` failed: {}", id, error);
+    }
+}
+
+/// Removes the record of an event which was handled successfully after failed attempts.
+///
+/// * `collection` - MongoDB collection of failed events.
+/// * `event` - Event which was handled successfully.
+pub async fn resolve_failed_event(collection: &Collection<FailedEvent>, event: &Event) {
+    if let Some(id) = &event.id {
+        if let Err(error) = collection.delete_one(doc! {"_id": id}, None).await {
+            warn!("Removing failed event: `{}` failed: {}", id, error);
+        }
+    }
+}
+
+/// Handles a recorded failed event again.
+///
+/// The record is removed if the event is handled successfully, otherwise the failed attempt is recorded.
+///
+/// * `state` - Service state containing database connections.
+/// * `failed_event` - Recorded failed event to reprocess.
+pub async fn reprocess_failed_event(
+    state: &HttpEventServiceState,
+    failed_event: FailedEvent,
+) -> Result<(), StatusCode> {
+    let data: EventData = bson::from_document(failed_event.data).map_err(|error| {
+        warn!(
+            "Deserializing data of failed event: `{}` failed: {}",
+            failed_event._id, error
+        );
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    let event = Event {
+        id: Some(failed_event._id),
+        topic: failed_event.topic,
+        data,
+        time: None,
+    };
+    let collection = state.failed_event_collection.clone();
+    match handle_event(state.clone(), &event).await {
+        Ok(()) => {
+            resolve_failed_event(&collection, &event).await;
+            Ok(())
+        }
+        Err(status) => {
+            record_failed_event(&collection, &event, status).await;
+            Err(status)
+        }
+    }
+}
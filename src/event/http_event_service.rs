@@ -1,10 +1,34 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::cache::{wishlist_key, StateCache};
+use crate::dapr_client::DaprClient;
+use crate::graphql::model::{date_time::DateTime, uuid::Uuid, wishlist::Wishlist};
 use axum::{debug_handler, extract::State, http::StatusCode, Json};
-use bson::Uuid;
-use log::info;
-use mongodb::Collection;
+use bson::{doc, Document};
+use log::{info, warn};
+use mongodb::{options::UpdateOptions, Collection};
 use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::{FaultInjector, FaultTarget};
+use crate::graphql::model::failed_event::FailedEvent;
+use crate::graphql::model::{
+    foreign_types::ProductVariant, product_variant_metadata::Money, user::User,
+};
+use crate::metrics::event_metrics::EventMetrics;
 
-use crate::graphql::model::{foreign_types::ProductVariant, user::User};
+use super::aggregate_locks::AggregateLocks;
+use super::failed_events::{record_failed_event, resolve_failed_event};
+use super::outgoing_events::{
+    WishlistTargetPriceReachedEventData, WISHLIST_TARGET_PRICE_REACHED_TOPIC,
+};
 
 /// Data to send to Dapr in order to describe a subscription.
 #[derive(Serialize)]
@@ -31,21 +55,75 @@ impl Default for TopicEventResponse {
 /// Relevant part of Dapr event wrapped in a cloud envelope.
 #[derive(Deserialize, Debug)]
 pub struct Event {
+    /// CloudEvent identifier of the event, used to record failed events.
+    #[serde(default)]
+    pub id: Option<String>,
     pub topic: String,
     pub data: EventData,
+    /// RFC3339 timestamp when the event was published, used to determine the lag of event processing.
+    #[serde(default)]
+    pub time: Option<String>,
+}
+
+impl Event {
+    /// Returns the duration since the event was published, `None` if the event has no valid timestamp.
+    fn lag(&self) -> Option<Duration> {
+        let published_at = bson::DateTime::parse_rfc3339_str(self.time.as_deref()?).ok()?;
+        let lag_millis = bson::DateTime::now().timestamp_millis() - published_at.timestamp_millis();
+        Some(Duration::from_millis(lag_millis.max(0) as u64))
+    }
 }
 
 /// Relevant part of Dapr event data.
-#[derive(Deserialize, Debug)]
+///
+/// Product variant events of the catalog and the inventory may contain the metadata of the product variant, other events only contain the UUID.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct EventData {
     pub id: Uuid,
+    /// Name of the product variant.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// URL of an image of the product variant.
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Retail price of the product variant in the minor unit of its currency.
+    #[serde(default)]
+    pub retail_price: Option<u64>,
+    /// ISO 4217 code of the currency of the retail price.
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Whether the product variant is available in the inventory.
+    #[serde(default)]
+    pub available: Option<bool>,
 }
 
 /// Service state containing database connections.
+///
+/// Product variants and users are replicated to the collections of all tenant databases.
+/// Users are disabled and re-enabled in the wishlist collections of all tenant databases.
 #[derive(Clone)]
 pub struct HttpEventServiceState {
-    pub product_variant_collection: Collection<ProductVariant>,
-    pub user_collection: Collection<User>,
+    pub product_variant_collections: Vec<Collection<ProductVariant>>,
+    pub user_collections: Vec<Collection<User>>,
+    pub wishlist_collections: Vec<Collection<Wishlist>>,
+    pub state_cache: StateCache,
+    /// Currency of catalog events which do not specify a currency.
+    pub catalog_currency: String,
+    /// Metrics of the processing of events.
+    pub event_metrics: EventMetrics,
+    /// Collection of failed events of the default database.
+    pub failed_event_collection: Collection<FailedEvent>,
+    /// Locks serializing the handling of events of the same user or product variant.
+    pub aggregate_locks: AggregateLocks,
+    /// Permits of events in progress, limiting the number of events handled concurrently.
+    pub in_flight_permits: Arc<Semaphore>,
+    /// Whether the service is shutting down, new events are then rejected for redelivery to another instance.
+    pub draining: Arc<AtomicBool>,
+    /// Dapr client used to publish target price notifications.
+    pub dapr_client: DaprClient,
+    #[cfg(feature = "fault-injection")]
+    pub fault_injector: FaultInjector,
 }
 
 /// HTTP endpoint to list topic subsciptions.
@@ -60,11 +138,43 @@ pub async fn list_topic_subscriptions() -> Result<Json<Vec<Pubsub>>, StatusCode>
         topic: "catalog/product-variant/created".to_string(),
         route: "/on-topic-event".to_string(),
     };
-    Ok(Json(vec![pubsub_user, pubsub_product_variant]))
+    let pubsub_product_variant_updated = Pubsub {
+        pubsubname: "pubsub".to_string(),
+        topic: "catalog/product-variant/updated".to_string(),
+        route: "/on-topic-event".to_string(),
+    };
+    let pubsub_product_variant_availability = Pubsub {
+        pubsubname: "pubsub".to_string(),
+        topic: "inventory/product-variant/availability-updated".to_string(),
+        route: "/on-topic-event".to_string(),
+    };
+    let pubsub_user_disabled = Pubsub {
+        pubsubname: "pubsub".to_string(),
+        topic: "user/user/disabled".to_string(),
+        route: "/on-topic-event".to_string(),
+    };
+    let pubsub_user_enabled = Pubsub {
+        pubsubname: "pubsub".to_string(),
+        topic: "user/user/enabled".to_string(),
+        route: "/on-topic-event".to_string(),
+    };
+    Ok(Json(vec![
+        pubsub_user,
+        pubsub_product_variant,
+        pubsub_product_variant_updated,
+        pubsub_product_variant_availability,
+        pubsub_user_disabled,
+        pubsub_user_enabled,
+    ]))
 }
 
 /// HTTP endpoint to receive events.
 ///
+/// Records the received events, their lag, the duration of their handling and failures per topic.
+/// Failed events are recorded for inspection and reprocessing by admins, until they are handled successfully.
+/// Events exceeding the maximum number of events in progress are rejected with `429 Too Many Requests`, which Dapr redelivers later.
+/// Events received during shutdown are rejected with `503 Service Unavailable`, so Dapr redelivers them to another instance.
+///
 /// * `state` - Service state containing database connections.
 /// * `event` - Event handled by endpoint.
 #[debug_handler(state = HttpEventServiceState)]
@@ -73,40 +183,347 @@ pub async fn on_topic_event(
     Json(event): Json<Event>,
 ) -> Result<Json<TopicEventResponse>, StatusCode> {
     info!("{:?}", event);
+    let event_metrics = state.event_metrics.clone();
+    if state.draining.load(Ordering::SeqCst) {
+        warn!(
+            "Service is shutting down, rejecting event of topic: `{}` for retry.",
+            event.topic
+        );
+        event_metrics.record_rejected(&event.topic);
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let _in_flight_permit = match state.in_flight_permits.clone().try_acquire_owned() {
+        Ok(in_flight_permit) => in_flight_permit,
+        Err(_) => {
+            warn!(
+                "Too many events in progress, rejecting event of topic: `{}` for retry.",
+                event.topic
+            );
+            event_metrics.record_rejected(&event.topic);
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+    };
+    let failed_event_collection = state.failed_event_collection.clone();
+    event_metrics.record_received(&event.topic, event.lag());
+    let started_at = Instant::now();
+    let result = handle_event(state, &event).await;
+    event_metrics.record_handled(&event.topic, started_at.elapsed(), result.is_ok());
+    match result {
+        Ok(()) => resolve_failed_event(&failed_event_collection, &event).await,
+        Err(status) => record_failed_event(&failed_event_collection, &event, status).await,
+    }
+    result.map(|()| Json(TopicEventResponse::default()))
+}
+
+/// Handles an event according to its topic.
+///
+/// Waits until earlier events of the same user or product variant are handled, so events of an aggregate are handled in order.
+///
+/// * `state` - Service state containing database connections.
+/// * `event` - Event to handle.
+pub async fn handle_event(state: HttpEventServiceState, event: &Event) -> Result<(), StatusCode> {
+    let _aggregate_guard = state.aggregate_locks.lock(event.data.id).await;
+
+    #[cfg(feature = "fault-injection")]
+    if let Err(error) = state
+        .fault_injector
+        .inject(FaultTarget::EventHandlers)
+        .await
+    {
+        info!("{}", error.message);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
 
     match event.topic.as_str() {
-        "catalog/product-variant/created" => {
-            add_product_variant_to_mongodb(state.product_variant_collection, event.data.id).await?
+        "catalog/product-variant/created"
+        | "catalog/product-variant/updated"
+        | "inventory/product-variant/availability-updated" => {
+            let upsert = event.topic.starts_with("catalog/");
+            for collection in &state.product_variant_collections {
+                upsert_product_variant_in_mongodb(
+                    collection,
+                    &event.data,
+                    &state.catalog_currency,
+                    upsert,
+                )
+                .await?
+            }
+            if let Some(retail_price) = event.data.retail_price {
+                let current_price = Money {
+                    amount: retail_price,
+                    currency: event
+                        .data
+                        .currency
+                        .clone()
+                        .unwrap_or(state.catalog_currency.clone()),
+                };
+                for collection in &state.wishlist_collections {
+                    notify_target_prices_reached(
+                        collection,
+                        &state.state_cache,
+                        &state.dapr_client,
+                        event.data.id,
+                        &current_price,
+                    )
+                    .await?
+                }
+            }
         }
-        "user/user/created" => add_user_to_mongodb(state.user_collection, event.data.id).await?,
-        _ => return Err(StatusCode::INTERNAL_SERVER_ERROR)
+        "user/user/created" => {
+            for collection in state.user_collections {
+                add_user_to_mongodb(collection, event.data.id).await?
+            }
+        }
+        "user/user/disabled" | "user/user/enabled" => {
+            let suspended = event.topic == "user/user/disabled";
+            for collection in &state.wishlist_collections {
+                suspend_wishlists_of_user(collection, &state.state_cache, event.data.id, suspended)
+                    .await?
+            }
+        }
+        _ => return Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
-    Ok(Json(TopicEventResponse::default()))
+    Ok(())
 }
 
-/// Add a newly created product variant to MongoDB.
+/// Adds a created product variant to MongoDB or updates the catalog and inventory metadata of an existing product variant.
 ///
-/// * `collection` - MongoDB collection to add newly created product variant to.
-/// * `id` - UUID of newly created product variant.
-pub async fn add_product_variant_to_mongodb(
-    collection: Collection<ProductVariant>,
-    id: Uuid,
+/// Only the metadata contained in the event is updated, the price is stored with the timestamp of its receipt.
+/// Price drops within the same currency and availability changes are recorded for the wishlist digest.
+///
+/// * `collection` - MongoDB collection of product variants.
+/// * `event_data` - Data of the product variant event.
+/// * `default_currency` - Currency of the retail price if the event does not specify a currency.
+/// * `upsert` - Whether unknown product variants are added, only the catalog creates product variants.
+pub async fn upsert_product_variant_in_mongodb(
+    collection: &Collection<ProductVariant>,
+    event_data: &EventData,
+    default_currency: &str,
+    upsert: bool,
 ) -> Result<(), StatusCode> {
-    let product_variant = ProductVariant { _id: id };
-    match collection.insert_one(product_variant, None).await {
+    let current_timestamp = DateTime::now();
+    let mut metadata = Document::new();
+    if let Some(name) = &event_data.name {
+        metadata.insert("name", doc! {"$literal": name});
+    }
+    if let Some(image_url) = &event_data.image_url {
+        metadata.insert("image_url", doc! {"$literal": image_url});
+    }
+    if let Some(retail_price) = event_data.retail_price {
+        let currency = event_data.currency.as_deref().unwrap_or(default_currency);
+        let price_dropped = doc! {"$and": [
+            {"$eq": ["$current_price.currency", {"$literal": currency}]},
+            {"$gt": ["$current_price.amount", retail_price as i64]},
+        ]};
+        metadata.insert(
+            "last_price_drop",
+            doc! {"$cond": [
+                price_dropped,
+                {"previous_price": "$current_price", "dropped_at": current_timestamp},
+                "$last_price_drop",
+            ]},
+        );
+        metadata.insert(
+            "current_price",
+            doc! {"amount": retail_price as i64, "currency": {"$literal": currency}},
+        );
+        metadata.insert("price_updated_at", current_timestamp);
+    }
+    if let Some(available) = event_data.available {
+        metadata.insert(
+            "availability_changed_at",
+            doc! {"$cond": [
+                {"$and": [
+                    {"$ne": [{"$type": "$available"}, "missing"]},
+                    {"$ne": ["$available", available]},
+                ]},
+                current_timestamp,
+                "$availability_changed_at",
+            ]},
+        );
+        metadata.insert("available", available);
+    }
+    let update_options = UpdateOptions::builder().upsert(upsert).build();
+    let result = match metadata.is_empty() {
+        true => {
+            collection
+                .update_one(
+                    doc! {"_id": event_data.id},
+                    doc! {"$setOnInsert": {"_id": event_data.id}},
+                    update_options,
+                )
+                .await
+        }
+        false => {
+            collection
+                .update_one(
+                    doc! {"_id": event_data.id},
+                    vec![doc! {"$set": metadata}],
+                    update_options,
+                )
+                .await
+        }
+    };
+    match result {
         Ok(_) => Ok(()),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
+/// Notifies the users whose target price of a product variant is above its current catalog price.
+///
+/// Each target price is notified once per price drop: it is marked as notified after the event is published,
+/// and the mark is reset as soon as the price rises to the target price again.
+/// Target prices of other currencies than the current price are ignored.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `state_cache` - Cache of wishlists, invalidated for wishlists with changed target prices.
+/// * `dapr_client` - Dapr client used to publish the notifications.
+/// * `product_variant_id` - UUID of the product variant whose price was received.
+/// * `current_price` - Current catalog price of the product variant.
+pub async fn notify_target_prices_reached(
+    collection: &Collection<Wishlist>,
+    state_cache: &StateCache,
+    dapr_client: &DaprClient,
+    product_variant_id: Uuid,
+    current_price: &Money,
+) -> Result<(), StatusCode> {
+    let amount = current_price.amount as i64;
+    let rearm_filter = doc! {"target_prices": {"$elemMatch": {
+        "product_variant_id": product_variant_id,
+        "price.currency": &current_price.currency,
+        "price.amount": {"$lte": amount},
+        "notified_at": {"$ne": null},
+    }}};
+    let rearmed_ids = distinct_wishlist_ids(collection, rearm_filter.clone()).await?;
+    let rearm_options = UpdateOptions::builder()
+        .array_filters(vec![doc! {
+            "target.product_variant_id": product_variant_id,
+            "target.price.currency": &current_price.currency,
+            "target.price.amount": {"$lte": amount},
+        }])
+        .build();
+    collection
+        .update_many(
+            rearm_filter,
+            doc! {"$set": {"target_prices.$[target].notified_at": null}},
+            rearm_options,
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for id in rearmed_ids {
+        state_cache.invalidate(&wishlist_key(id)).await;
+    }
+    let reached_filter = doc! {
+        "internal_product_variants._id": product_variant_id,
+        "target_prices": {"$elemMatch": {
+            "product_variant_id": product_variant_id,
+            "price.currency": &current_price.currency,
+            "price.amount": {"$gt": amount},
+            "notified_at": null,
+        }},
+    };
+    let wishlist_ids = distinct_wishlist_ids(collection, reached_filter).await?;
+    for wishlist_id in wishlist_ids {
+        let wishlist = match collection.find_one(doc! {"_id": wishlist_id}, None).await {
+            Ok(Some(wishlist)) => wishlist,
+            Ok(None) => continue,
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+        };
+        let target_price = match wishlist.target_prices.iter().find(|target_price| {
+            target_price.product_variant_id == product_variant_id
+                && target_price.notified_at.is_none()
+        }) {
+            Some(target_price) => target_price,
+            None => continue,
+        };
+        let event_data = WishlistTargetPriceReachedEventData {
+            wishlist_id,
+            user_id: wishlist.user._id,
+            product_variant_id,
+            target_price: target_price.price.clone(),
+            current_price: current_price.clone(),
+        };
+        if let Err(error) = dapr_client
+            .publish_event(WISHLIST_TARGET_PRICE_REACHED_TOPIC, &event_data)
+            .await
+        {
+            warn!("{}", error.message);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        collection
+            .update_one(
+                doc! {"_id": wishlist_id, "target_prices.product_variant_id": product_variant_id},
+                doc! {"$set": {"target_prices.$.notified_at": DateTime::now()}},
+                None,
+            )
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        state_cache.invalidate(&wishlist_key(wishlist_id)).await;
+    }
+    Ok(())
+}
+
+/// Returns the UUIDs of the wishlists matching a filter.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `filter` - Filter of the wishlists.
+async fn distinct_wishlist_ids(
+    collection: &Collection<Wishlist>,
+    filter: Document,
+) -> Result<Vec<Uuid>, StatusCode> {
+    match collection.distinct("_id", filter, None).await {
+        Ok(ids) => Ok(ids
+            .into_iter()
+            .filter_map(|id| bson::from_bson(id).ok())
+            .collect()),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 /// Add a newly created user to MongoDB.
 ///
+/// Users already added by the reconciliation of the foreign projections are kept, so redelivered events succeed.
+///
 /// * `collection` - MongoDB collection to add newly created user to.
 /// * `id` - UUID of newly created user.
 pub async fn add_user_to_mongodb(collection: Collection<User>, id: Uuid) -> Result<(), StatusCode> {
-    let user = User { _id: id };
-    match collection.insert_one(user, None).await {
+    let update_options = UpdateOptions::builder().upsert(true).build();
+    match collection
+        .update_one(
+            doc! {"_id": id},
+            doc! {"$setOnInsert": {"_id": id}},
+            update_options,
+        )
+        .await
+    {
         Ok(_) => Ok(()),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
+
+/// Suspends the wishlists of a disabled user or lifts the suspension when the user is re-enabled.
+///
+/// Suspended wishlists are excluded from calendar feeds and can only be modified by admins.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `state_cache` - Cache of wishlists, invalidated for the wishlists of the user.
+/// * `user_id` - UUID of disabled or re-enabled user.
+/// * `suspended` - Whether the wishlists are suspended.
+pub async fn suspend_wishlists_of_user(
+    collection: &Collection<Wishlist>,
+    state_cache: &StateCache,
+    user_id: Uuid,
+    suspended: bool,
+) -> Result<(), StatusCode> {
+    let filter = doc! {"user._id": user_id};
+    let ids = distinct_wishlist_ids(collection, filter.clone()).await?;
+    collection
+        .update_many(filter, doc! {"$set": {"suspended": suspended}}, None)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for id in ids {
+        state_cache.invalidate(&wishlist_key(id)).await;
+    }
+    Ok(())
+}
@@ -1 +1,6 @@
+pub mod aggregate_locks;
+pub mod change_data_capture;
+pub mod event_batcher;
+pub mod failed_events;
 pub mod http_event_service;
+pub mod outgoing_events;
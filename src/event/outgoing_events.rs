@@ -0,0 +1,201 @@
+use crate::graphql::model::{
+    date_time::DateTime, product_variant_metadata::Money, uuid::Uuid, wishlist::Wishlist,
+};
+use mongodb::change_stream::event::OperationType;
+use serde::Serialize;
+
+/// Topic of the command event requesting the shopping cart service to add the items of a wishlist.
+pub const ADD_WISHLIST_TO_CART_TOPIC: &str = "wishlist/wishlist/add-to-cart";
+
+/// Command event data to add the product variants of a wishlist to the shopping cart of its user.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AddWishlistToCartEventData {
+    /// UUID to correlate the command with the resulting shopping cart changes.
+    pub correlation_id: Uuid,
+    /// UUID of the user owning the wishlist and the shopping cart.
+    pub user_id: Uuid,
+    /// UUID of the wishlist whose items are added.
+    pub wishlist_id: Uuid,
+    /// Shopping cart items to add.
+    pub shopping_cart_items: Vec<ShoppingCartItemEventData>,
+}
+
+/// Shopping cart item of a command event.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShoppingCartItemEventData {
+    /// UUID of the product variant to add.
+    pub product_variant_id: Uuid,
+    /// Quantity of the product variant to add.
+    pub count: u64,
+}
+
+/// Topic of the event notifying that a wishlist expired and was archived.
+pub const WISHLIST_EXPIRED_TOPIC: &str = "wishlist/wishlist/expired";
+
+/// Event data of an expired and archived wishlist.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistExpiredEventData {
+    /// UUID of the expired wishlist.
+    pub id: Uuid,
+    /// UUID of the user owning the wishlist.
+    pub user_id: Uuid,
+    /// Timestamp when the wishlist expired.
+    pub expires_at: DateTime,
+}
+
+/// Topic of the change-data-capture stream of wishlist documents.
+pub const WISHLIST_CDC_TOPIC: &str = "wishlist/cdc";
+
+/// Event data of a change of a wishlist document, published in the order of the changes.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistChangeEventData {
+    /// Type of the change, e.g. `insert`, `update` or `delete`.
+    pub operation_type: OperationType,
+    /// UUID of the changed wishlist, `None` for changes of the whole collection.
+    pub wishlist_id: Option<Uuid>,
+    /// Wishlist document after the change, `None` if the wishlist was deleted.
+    pub wishlist: Option<Wishlist>,
+    /// Timestamp when the change was performed.
+    pub changed_at: Option<DateTime>,
+}
+
+/// Topic of the periodic digest of the wishlist activity of a user, consumed by the notification service.
+pub const WISHLIST_DIGEST_TOPIC: &str = "wishlist/digest";
+
+/// Event data of the digest of the wishlist activity of a user within a period.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistDigestEventData {
+    /// UUID of the user the digest is intended for.
+    pub user_id: Uuid,
+    /// Start of the digest period, exclusive.
+    pub period_start: DateTime,
+    /// End of the digest period, inclusive.
+    pub period_end: DateTime,
+    /// Wishlists with activity within the period or an occasion within the next period.
+    pub wishlists: Vec<WishlistDigestEntryEventData>,
+}
+
+/// Wishlist of a digest event.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistDigestEntryEventData {
+    /// UUID of the wishlist.
+    pub wishlist_id: Uuid,
+    /// Name of the wishlist.
+    pub name: String,
+    /// Number of product variants in the wishlist.
+    pub item_count: u64,
+    /// Whether the wishlist was created within the period.
+    pub created: bool,
+    /// Whether the wishlist was updated within the period.
+    pub updated: bool,
+    /// Whether the wishlist was archived within the period.
+    pub archived: bool,
+    /// Date of the occasion of the wishlist, if it is within the next period.
+    pub occasion_date: Option<DateTime>,
+    /// Product variants of the wishlist whose catalog price dropped within the period.
+    pub price_drops: Vec<DigestPriceDropEventData>,
+    /// Product variants of the wishlist whose availability changed within the period.
+    pub availability_changes: Vec<DigestAvailabilityChangeEventData>,
+}
+
+/// Price drop of a product variant of a digest event.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestPriceDropEventData {
+    /// UUID of the product variant.
+    pub product_variant_id: Uuid,
+    /// Price before the drop.
+    pub previous_price: Money,
+    /// Current catalog price.
+    pub current_price: Money,
+}
+
+/// Availability change of a product variant of a digest event.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestAvailabilityChangeEventData {
+    /// UUID of the product variant.
+    pub product_variant_id: Uuid,
+    /// Whether the product variant is available now.
+    pub available: bool,
+}
+
+/// Topic of the event notifying that a wishlist was patched by a bulk update of an admin.
+pub const WISHLIST_BULK_UPDATED_TOPIC: &str = "wishlist/wishlist/bulk-updated";
+
+/// Event data of a wishlist patched by a bulk update.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistBulkUpdatedEventData {
+    /// UUID of the patched wishlist.
+    pub id: Uuid,
+    /// UUID of the user owning the wishlist.
+    pub user_id: Uuid,
+    /// UUIDs of the product variants removed from the wishlist.
+    pub removed_product_variant_ids: Vec<Uuid>,
+    /// Whether the wishlist was archived or restored, `None` if the archival was not changed.
+    pub archived: Option<bool>,
+}
+
+/// Topic of the event notifying that a product variant of a wishlist was purchased, used to measure wishlist-to-purchase conversion.
+pub const WISHLIST_ITEM_PURCHASED_TOPIC: &str = "wishlist/item/purchased";
+
+/// Event data of a product variant of a wishlist marked as purchased.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistItemPurchasedEventData {
+    /// UUID of the wishlist.
+    pub wishlist_id: Uuid,
+    /// UUID of the user owning the wishlist.
+    pub user_id: Uuid,
+    /// UUID of the purchased product variant.
+    pub product_variant_id: Uuid,
+    /// Timestamp when the product variant was purchased.
+    pub purchased_at: DateTime,
+    /// Timestamp when the wishlist was created.
+    pub wishlist_created_at: DateTime,
+}
+
+/// Topic of the event notifying a user that the price of a product variant of their wishlist dropped below its target price.
+pub const WISHLIST_TARGET_PRICE_REACHED_TOPIC: &str = "wishlist/item/target-price-reached";
+
+/// Event data of a product variant whose catalog price dropped below the target price set in a wishlist.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistTargetPriceReachedEventData {
+    /// UUID of the wishlist.
+    pub wishlist_id: Uuid,
+    /// UUID of the user owning the wishlist, who is notified.
+    pub user_id: Uuid,
+    /// UUID of the product variant.
+    pub product_variant_id: Uuid,
+    /// Target price set by the user.
+    pub target_price: Money,
+    /// Current catalog price of the product variant.
+    pub current_price: Money,
+}
+
+/// Topic of the event notifying that a wishlist reached a milestone size, used by marketing automation to nudge the user.
+pub const WISHLIST_MILESTONE_REACHED_TOPIC: &str = "wishlist/wishlist/milestone-reached";
+
+/// Event data of a wishlist whose number of product variants reached a milestone size.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistMilestoneReachedEventData {
+    /// UUID of the wishlist.
+    pub wishlist_id: Uuid,
+    /// UUID of the user owning the wishlist.
+    pub user_id: Uuid,
+    /// Reached milestone size.
+    pub milestone: u64,
+    /// Number of product variants of the wishlist after the change reaching the milestone.
+    pub item_count: u64,
+    /// Timestamp when the milestone was reached.
+    pub reached_at: DateTime,
+}
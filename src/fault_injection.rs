@@ -0,0 +1,113 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_graphql::{Error, Result};
+use log::warn;
+use serde::Deserialize;
+
+/// Faults injected into a part of the service.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Faults {
+    /// Artificial latency in milliseconds added to each call.
+    pub latency_millis: u64,
+    /// Ratio of calls which fail, between `0.0` and `1.0`.
+    pub error_rate: f64,
+}
+
+/// Fault configuration of a chaos experiment, provided by the MiSArch experiment-config sidecar.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase", default)]
+struct FaultInjectionConfig {
+    /// Faults injected into GraphQL resolvers.
+    resolvers: Faults,
+    /// Faults injected into handlers of incoming events.
+    event_handlers: Faults,
+}
+
+/// Part of the service faults are injected into.
+#[derive(Debug, Clone, Copy)]
+pub enum FaultTarget {
+    /// Root fields of GraphQL operations.
+    Resolvers,
+    /// Handlers of incoming events.
+    EventHandlers,
+}
+
+/// Injects artificial latency and errors of chaos experiments at runtime.
+///
+/// No faults are injected until the experiment-config sidecar provides a configuration.
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    config: Arc<RwLock<FaultInjectionConfig>>,
+}
+
+impl FaultInjector {
+    /// Constructs a fault injector, which periodically polls its configuration from the experiment-config sidecar.
+    ///
+    /// Failed polls are logged and keep the previous configuration. Never injects faults if no URL is set.
+    ///
+    /// * `experiment_config_url` - URL of the fault configuration of the experiment-config sidecar.
+    /// * `poll_interval` - Interval in which the configuration is polled.
+    pub fn spawn(experiment_config_url: Option<String>, poll_interval: Duration) -> Self {
+        let fault_injector = Self::default();
+        if let Some(experiment_config_url) = experiment_config_url {
+            let config = fault_injector.config.clone();
+            tokio::spawn(async move {
+                let http_client = reqwest::Client::new();
+                let mut interval = tokio::time::interval(poll_interval);
+                loop {
+                    interval.tick().await;
+                    match fetch_config(&http_client, &experiment_config_url).await {
+                        Ok(fetched_config) => *config.write().unwrap() = fetched_config,
+                        Err(error) => warn!(
+                            "Polling fault configuration from: `{}` failed: {}",
+                            experiment_config_url, error.message
+                        ),
+                    }
+                }
+            });
+        }
+        fault_injector
+    }
+
+    /// Delays by the configured latency, then fails according to the configured error rate.
+    ///
+    /// * `target` - Part of the service the fault is injected into.
+    pub async fn inject(&self, target: FaultTarget) -> Result<()> {
+        let config = *self.config.read().unwrap();
+        let faults = match target {
+            FaultTarget::Resolvers => config.resolvers,
+            FaultTarget::EventHandlers => config.event_handlers,
+        };
+        if faults.latency_millis > 0 {
+            tokio::time::sleep(Duration::from_millis(faults.latency_millis)).await;
+        }
+        match rand::random::<f64>() < faults.error_rate {
+            true => {
+                let message = format!("Fault injected into {:?} by chaos experiment.", target);
+                Err(Error::new(message))
+            }
+            false => Ok(()),
+        }
+    }
+}
+
+/// Fetches the fault configuration from the experiment-config sidecar.
+///
+/// * `http_client` - HTTP client used to fetch the configuration.
+/// * `experiment_config_url` - URL of the fault configuration.
+async fn fetch_config(
+    http_client: &reqwest::Client,
+    experiment_config_url: &str,
+) -> Result<FaultInjectionConfig> {
+    Ok(http_client
+        .get(experiment_config_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
@@ -0,0 +1,125 @@
+use async_graphql::{Context, Error, ErrorExtensions, Result};
+use bson::{doc, DateTime};
+use mongodb::{options::FindOneOptions, Collection, Database};
+use serde::{Deserialize, Serialize};
+
+use crate::authorization::{is_admin, ApiKeyPrincipal, AuthorizedUserHeader};
+use crate::settings::Settings;
+use crate::tenant::tenant_id;
+
+use super::model::uuid::Uuid;
+
+/// Name of the MongoDB collection of throttled creations.
+pub const CREATION_THROTTLE_COLLECTION: &str = "creation_throttle";
+
+/// Creation which is limited per user and sliding window.
+#[derive(Debug, Clone, Copy)]
+pub enum ThrottledCreation {
+    /// Creation of a wishlist, including imports.
+    Wishlist,
+    /// Creation of a calendar token.
+    CalendarToken,
+}
+
+impl ThrottledCreation {
+    /// Name of the creation, as stored in MongoDB.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Wishlist => "wishlist",
+            Self::CalendarToken => "calendar_token",
+        }
+    }
+}
+
+/// Creation counted by the sliding window of its creator.
+///
+/// Records expire according to the TTL index on `created_at`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreationRecord {
+    /// UUID of the record.
+    pub _id: Uuid,
+    /// Identifier of the tenant of the creator.
+    pub tenant_id: String,
+    /// Creator, the UUID of a user or the name of an API key.
+    pub creator: String,
+    /// Name of the throttled creation.
+    pub creation: String,
+    /// Timestamp of the creation.
+    pub created_at: DateTime,
+}
+
+/// Limits the creations of a caller within a sliding window, to prevent spam.
+///
+/// Fails with the error code `THROTTLED` and the extension `retryAfterSeconds` if the caller reached the limit.
+/// Admins are not throttled. Throttled attempts are not counted.
+///
+/// * `ctx` - GraphQL context containing the database client, settings and the caller.
+/// * `creation` - Creation the caller attempts.
+pub async fn throttle_creation(ctx: &Context<'_>, creation: ThrottledCreation) -> Result<()> {
+    if is_admin(ctx) {
+        return Ok(());
+    }
+    let creator = match (
+        ctx.data_opt::<AuthorizedUserHeader>(),
+        ctx.data_opt::<ApiKeyPrincipal>(),
+    ) {
+        (Some(authorized_user_header), _) => authorized_user_header.id.to_string(),
+        (None, Some(api_key_principal)) => format!("api-key-{}", api_key_principal.name),
+        (None, None) => return Ok(()),
+    };
+    let settings = ctx.data::<Settings>()?;
+    let db_client = ctx.data::<Database>()?;
+    let collection: Collection<CreationRecord> =
+        db_client.collection::<CreationRecord>(CREATION_THROTTLE_COLLECTION);
+    let record = CreationRecord {
+        _id: Uuid::new(),
+        tenant_id: tenant_id(ctx)?.0.clone(),
+        creator,
+        creation: creation.as_str().to_string(),
+        created_at: DateTime::now(),
+    };
+    let message = "Throttling creation failed in MongoDB.";
+    collection
+        .insert_one(&record, None)
+        .await
+        .map_err(|_| Error::new(message))?;
+    let window_millis = settings.creation_throttle_window_secs as i64 * 1000;
+    let window_start = DateTime::from_millis(record.created_at.timestamp_millis() - window_millis);
+    let window_filter = doc! {
+        "tenant_id": &record.tenant_id,
+        "creator": &record.creator,
+        "creation": &record.creation,
+        "created_at": {"$gt": window_start},
+    };
+    let creation_count = collection
+        .count_documents(window_filter.clone(), None)
+        .await
+        .map_err(|_| Error::new(message))?;
+    if creation_count <= settings.creation_throttle_limit {
+        return Ok(());
+    }
+    let _ = collection.delete_one(doc! {"_id": record._id}, None).await;
+    let oldest_creation_options = FindOneOptions::builder()
+        .sort(doc! {"created_at": 1})
+        .build();
+    let retry_after_secs = match collection
+        .find_one(window_filter, oldest_creation_options)
+        .await
+    {
+        Ok(Some(oldest_record)) => {
+            let retry_at_millis = oldest_record.created_at.timestamp_millis() + window_millis;
+            (retry_at_millis - record.created_at.timestamp_millis()).max(0) / 1000 + 1
+        }
+        _ => settings.creation_throttle_window_secs as i64,
+    };
+    let message = format!(
+        "Too many creations of type: `{}`, at most {} are allowed within {} seconds.",
+        creation.as_str(),
+        settings.creation_throttle_limit,
+        settings.creation_throttle_window_secs
+    );
+    Err(Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", "THROTTLED");
+        extensions.set("retryAfterSeconds", retry_after_secs);
+    }))
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, io::Read};
+
+use csv::{ReaderBuilder, StringRecord, Trim};
+
+use super::model::uuid::Uuid;
+use super::mutation_payload_structs::CsvRowError;
+
+/// Maximum number of characters of the note of a row.
+const MAX_NOTE_LENGTH: usize = 500;
+
+/// Name of the first column, identifying the optional header row.
+const PRODUCT_VARIANT_ID_COLUMN: &str = "product_variant_id";
+
+/// Valid row of a wishlist CSV file.
+#[derive(Debug)]
+pub struct CsvRow {
+    /// Number of the row in the file, starting at 1.
+    pub row: u64,
+    /// UUID of the product variant of the row.
+    pub product_variant_id: Uuid,
+}
+
+/// Parses a wishlist CSV file with rows of the form `product_variant_id[,quantity,note]`.
+///
+/// An optional header row starting with `product_variant_id` and empty rows are skipped.
+/// Quantities must be positive integers and notes must not exceed 500 characters.
+/// Product variants listed in multiple rows are reported for each repeated row.
+/// Fails without reading the rest of the file once it contains more than the maximum number of rows.
+///
+/// * `reader` - Content of the CSV file.
+/// * `max_rows` - Maximum number of rows listing product variants, unlimited if `None`.
+pub fn parse_wishlist_csv(
+    reader: impl Read,
+    max_rows: Option<u64>,
+) -> Result<(Vec<CsvRow>, Vec<CsvRowError>), String> {
+    let mut csv_reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(reader);
+    let mut rows = vec![];
+    let mut row_errors = vec![];
+    let mut first_rows: HashMap<Uuid, u64> = HashMap::new();
+    let mut row_count = 0;
+    for (index, record) in csv_reader.records().enumerate() {
+        let row = index as u64 + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                row_errors.push(CsvRowError {
+                    row,
+                    message: format!("Row could not be read: {}.", error),
+                });
+                continue;
+            }
+        };
+        if record.iter().all(str::is_empty)
+            || (row == 1 && record.get(0) == Some(PRODUCT_VARIANT_ID_COLUMN))
+        {
+            continue;
+        }
+        row_count += 1;
+        if max_rows.is_some_and(|max_rows| row_count > max_rows) {
+            return Err(format!(
+                "File exceeds the maximum number of product variants per wishlist: `{}`.",
+                max_rows.unwrap_or_default()
+            ));
+        }
+        match parse_record(&record) {
+            Ok(product_variant_id) => match first_rows.get(&product_variant_id) {
+                Some(first_row) => row_errors.push(CsvRowError {
+                    row,
+                    message: format!(
+                        "Product variant with the UUID: `{}` is already listed in row: `{}`.",
+                        product_variant_id, first_row
+                    ),
+                }),
+                None => {
+                    first_rows.insert(product_variant_id, row);
+                    rows.push(CsvRow {
+                        row,
+                        product_variant_id,
+                    });
+                }
+            },
+            Err(message) => row_errors.push(CsvRowError { row, message }),
+        }
+    }
+    Ok((rows, row_errors))
+}
+
+/// Parses and validates the columns of a row, returning the UUID of its product variant.
+///
+/// * `record` - Columns of the row.
+fn parse_record(record: &StringRecord) -> Result<Uuid, String> {
+    if record.len() > 3 {
+        return Err(format!(
+            "Row has {} columns, expected at most 3: `product_variant_id,quantity,note`.",
+            record.len()
+        ));
+    }
+    let product_variant_id = record.get(0).unwrap_or_default();
+    let product_variant_id = Uuid::parse_str(product_variant_id)
+        .map_err(|_| format!("`{}` is not a valid UUID.", product_variant_id))?;
+    if let Some(quantity) = record.get(1).filter(|quantity| !quantity.is_empty()) {
+        match quantity.parse::<u64>() {
+            Ok(quantity) if quantity > 0 => {}
+            _ => {
+                return Err(format!(
+                    "Quantity: `{}` is not a positive integer.",
+                    quantity
+                ))
+            }
+        }
+    }
+    if let Some(note) = record.get(2) {
+        if note.chars().count() > MAX_NOTE_LENGTH {
+            return Err(format!(
+                "Note exceeds the maximum length of {} characters.",
+                MAX_NOTE_LENGTH
+            ));
+        }
+    }
+    Ok(product_variant_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+    const SECOND_ID: &str = "123e4567-e89b-12d3-a456-426614174001";
+
+    #[test]
+    fn parse_wishlist_csv_skips_header_and_empty_rows() {
+        let csv = format!(
+            "product_variant_id,quantity,note\n{},2,for mum\n,,\n{}\n",
+            FIRST_ID, SECOND_ID
+        );
+        let (rows, row_errors) = parse_wishlist_csv(csv.as_bytes(), None).unwrap();
+        assert!(row_errors.is_empty());
+        let parsed: Vec<(u64, String)> = rows
+            .iter()
+            .map(|row| (row.row, row.product_variant_id.to_string()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![(2, FIRST_ID.to_string()), (4, SECOND_ID.to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_wishlist_csv_reports_invalid_and_duplicate_rows() {
+        let csv = format!(
+            "{id}\nnot-a-uuid\n{id},0\n{id}\n{other},1,note,extra\n",
+            id = FIRST_ID,
+            other = SECOND_ID
+        );
+        let (rows, row_errors) = parse_wishlist_csv(csv.as_bytes(), None).unwrap();
+        assert_eq!(rows.len(), 1);
+        let errors: Vec<(u64, &str)> = row_errors
+            .iter()
+            .map(|error| (error.row, error.message.as_str()))
+            .collect();
+        assert_eq!(errors[0], (2, "`not-a-uuid` is not a valid UUID."));
+        assert_eq!(errors[1], (3, "Quantity: `0` is not a positive integer."));
+        assert_eq!(errors[2].0, 4);
+        assert!(errors[2].1.contains("is already listed in row: `1`"));
+        assert_eq!(errors[3].0, 5);
+        assert!(errors[3].1.starts_with("Row has 4 columns"));
+    }
+
+    #[test]
+    fn parse_wishlist_csv_rejects_too_long_notes() {
+        let csv = format!("{},1,{}\n", FIRST_ID, "x".repeat(MAX_NOTE_LENGTH + 1));
+        let (rows, row_errors) = parse_wishlist_csv(csv.as_bytes(), None).unwrap();
+        assert!(rows.is_empty());
+        assert_eq!(row_errors.len(), 1);
+    }
+
+    #[test]
+    fn parse_wishlist_csv_fails_above_maximum_number_of_rows() {
+        let csv = format!("{}\n{}\n", FIRST_ID, SECOND_ID);
+        assert!(parse_wishlist_csv(csv.as_bytes(), Some(2)).is_ok());
+        let error = parse_wishlist_csv(csv.as_bytes(), Some(1)).err().unwrap();
+        assert_eq!(
+            error,
+            "File exceeds the maximum number of product variants per wishlist: `1`."
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::{any::type_name, collections::HashMap, marker::PhantomData};
+
+use async_graphql::{dataloader::Loader, Error};
+use bson::doc;
+use futures::TryStreamExt;
+use mongodb::{Collection, Database};
+use serde::de::DeserializeOwned;
+
+use super::model::{
+    product_variant_metadata::ProductVariantMetadata, user::User, uuid::Uuid, wishlist::Wishlist,
+};
+
+/// Object stored in a MongoDB collection under its UUID.
+pub trait StoredObject: DeserializeOwned + Clone + Send + Sync + Unpin + 'static {
+    /// Name of the MongoDB collection of the object.
+    const COLLECTION_NAME: &'static str;
+
+    /// Returns the UUID of the object.
+    fn id(&self) -> Uuid;
+}
+
+impl StoredObject for Wishlist {
+    const COLLECTION_NAME: &'static str = "wishlists";
+
+    fn id(&self) -> Uuid {
+        self._id
+    }
+}
+
+impl StoredObject for User {
+    const COLLECTION_NAME: &'static str = "users";
+
+    fn id(&self) -> Uuid {
+        self._id
+    }
+}
+
+impl StoredObject for ProductVariantMetadata {
+    const COLLECTION_NAME: &'static str = "product_variants";
+
+    fn id(&self) -> Uuid {
+        self._id
+    }
+}
+
+/// Loads objects: `T` of multiple UUIDs with a single MongoDB `$in` query, used to batch entity resolution.
+pub struct ObjectLoader<T: StoredObject> {
+    collection: Collection<T>,
+    object_type: PhantomData<T>,
+}
+
+impl<T: StoredObject> ObjectLoader<T> {
+    /// Constructs an object loader.
+    ///
+    /// * `db_client` - MongoDB database containing the objects.
+    pub fn new(db_client: &Database) -> Self {
+        Self {
+            collection: db_client.collection::<T>(T::COLLECTION_NAME),
+            object_type: PhantomData,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: StoredObject> Loader<Uuid> for ObjectLoader<T> {
+    type Value = T;
+    type Error = Error;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, T>, Error> {
+        let message = format!("Retrieving {} failed in MongoDB.", type_name::<T>());
+        let objects: Vec<T> = match self
+            .collection
+            .find(doc! {"_id": {"$in": keys}}, None)
+            .await
+        {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| Error::new(&message))?,
+            Err(_) => return Err(Error::new(message)),
+        };
+        Ok(objects
+            .into_iter()
+            .map(|object| (object.id(), object))
+            .collect())
+    }
+}
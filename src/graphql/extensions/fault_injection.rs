@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ServerError, ServerResult, Value,
+};
+
+use crate::fault_injection::{FaultInjector, FaultTarget};
+
+/// GraphQL extension injecting the faults of chaos experiments into the root fields of operations.
+pub struct FaultInjection(pub FaultInjector);
+
+impl ExtensionFactory for FaultInjection {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(FaultInjectionExtension(self.0.clone()))
+    }
+}
+
+struct FaultInjectionExtension(FaultInjector);
+
+#[async_trait::async_trait]
+impl Extension for FaultInjectionExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.path_node.parent.is_none() && !info.is_for_introspection {
+            self.0
+                .inject(FaultTarget::Resolvers)
+                .await
+                .map_err(|error| ServerError::new(error.message, None))?;
+        }
+        next.run(ctx, info).await
+    }
+}
@@ -0,0 +1,8 @@
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
+pub mod operation_allow_list;
+pub mod operation_logger;
+pub mod operation_metrics;
+pub mod panic_guard;
+pub mod response_size_budget;
+pub mod validation_error_code;
@@ -0,0 +1,109 @@
+use std::{collections::HashSet, fs, io, path::Path, sync::Arc};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery},
+    parser::types::{ExecutableDocument, Selection},
+    ServerError, ServerResult, Variables,
+};
+
+/// Root fields used by the federation gateway, which are allowed regardless of the allow-list.
+const FEDERATION_FIELDS: [&str; 3] = ["_service", "_entities", "__typename"];
+
+/// GraphQL extension which only executes pre-registered GraphQL documents.
+///
+/// Documents are compared with normalized whitespace.
+/// Operations which only select federation fields (`_service`, `_entities`) are always allowed,
+/// as the gateway generates them dynamically.
+pub struct OperationAllowList {
+    allowed_documents: Arc<HashSet<String>>,
+}
+
+impl OperationAllowList {
+    /// Loads the allowed GraphQL documents from all `.graphql` files of a directory.
+    ///
+    /// * `directory` - Directory containing one GraphQL document per file.
+    pub fn from_directory(directory: &Path) -> io::Result<Self> {
+        let mut allowed_documents = HashSet::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "graphql")
+            {
+                let document = fs::read_to_string(&path)?;
+                allowed_documents.insert(normalize_document(&document));
+            }
+        }
+        Ok(Self {
+            allowed_documents: Arc::new(allowed_documents),
+        })
+    }
+
+    /// Number of allowed GraphQL documents.
+    pub fn len(&self) -> usize {
+        self.allowed_documents.len()
+    }
+}
+
+impl ExtensionFactory for OperationAllowList {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(OperationAllowListExtension {
+            allowed_documents: self.allowed_documents.clone(),
+        })
+    }
+}
+
+/// Per-request state of the operation allow-list.
+struct OperationAllowListExtension {
+    allowed_documents: Arc<HashSet<String>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for OperationAllowListExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        if self.allowed_documents.contains(&normalize_document(query))
+            || only_selects_federation_fields(&document)
+        {
+            Ok(document)
+        } else {
+            Err(ServerError::new(
+                "Operation is not part of the operation allow-list.",
+                None,
+            ))
+        }
+    }
+}
+
+/// Normalizes a GraphQL document by collapsing all whitespace into single spaces.
+///
+/// * `document` - GraphQL document to normalize.
+fn normalize_document(document: &str) -> String {
+    document.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Checks if all operations of a document only select federation root fields.
+///
+/// * `document` - Parsed GraphQL document.
+fn only_selects_federation_fields(document: &ExecutableDocument) -> bool {
+    document.operations.iter().all(|(_, operation)| {
+        operation
+            .node
+            .selection_set
+            .node
+            .items
+            .iter()
+            .all(|selection| match &selection.node {
+                Selection::Field(field) => {
+                    FEDERATION_FIELDS.contains(&field.node.name.node.as_str())
+                }
+                _ => false,
+            })
+    })
+}
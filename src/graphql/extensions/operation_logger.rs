@@ -0,0 +1,104 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery},
+    parser::types::ExecutableDocument,
+    Response, ServerResult, Variables,
+};
+use log::{log, Level};
+use serde_json::{json, Value};
+
+use crate::authorization::AuthorizedUserHeader;
+
+/// Placeholder of redacted variable values.
+const REDACTED: &str = "[REDACTED]";
+
+/// GraphQL extension logging one structured line per executed operation.
+///
+/// The line contains the operation name, the UUID of the calling user, the variables with redacted values,
+/// the duration and the result status of the operation.
+pub struct OperationLogger {
+    /// Log level of the lines, must be emitted by the logger.
+    pub level: Level,
+}
+
+impl ExtensionFactory for OperationLogger {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(OperationLoggerExtension {
+            level: self.level,
+            redacted_variables: Mutex::default(),
+        })
+    }
+}
+
+/// Per-request state of the operation logger.
+struct OperationLoggerExtension {
+    /// Log level of the line.
+    level: Level,
+    /// Variables of the request with redacted values.
+    redacted_variables: Mutex<Value>,
+}
+
+#[async_trait::async_trait]
+impl Extension for OperationLoggerExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        if let Ok(variables_json) = variables.clone().into_value().into_json() {
+            *self.redacted_variables.lock().unwrap() = redact(variables_json);
+        }
+        next.run(ctx, query, variables).await
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let start = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+        let user_id = ctx
+            .data_opt::<AuthorizedUserHeader>()
+            .map(|authorized_user_header| authorized_user_header.id.to_string());
+        let errors: Vec<&str> = response
+            .errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect();
+        let log_line = json!({
+            "operation_name": operation_name,
+            "user_id": user_id,
+            "variables": *self.redacted_variables.lock().unwrap(),
+            "duration_ms": start.elapsed().as_secs_f64() * 1000.0,
+            "status": if response.is_ok() { "ok" } else { "error" },
+            "errors": errors,
+        });
+        log!(self.level, "{}", log_line);
+        response
+    }
+}
+
+/// Replaces all values of a JSON value with a placeholder, keeping object keys and list structure.
+///
+/// * `value` - JSON value to redact.
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (key, redact(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        Value::Null => Value::Null,
+        _ => Value::String(REDACTED.to_string()),
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery},
+    parser::types::{ExecutableDocument, OperationType},
+    Response, ServerResult, Variables,
+};
+
+use crate::metrics::graphql_metrics::GraphQLMetrics;
+
+/// Operation name recorded for operations without name.
+const ANONYMOUS_OPERATION: &str = "anonymous";
+
+/// Operation name recorded for all operations if operation names are not recorded.
+const OTHER_OPERATION: &str = "other";
+
+/// GraphQL extension recording the number and duration of executed operations per operation name and type.
+///
+/// Operations whose document can not be parsed or validated are not executed and therefore not recorded.
+/// The name of the executed operation of the document is recorded, not the operation name sent by the client.
+pub struct OperationMetrics {
+    /// Metrics the operations are recorded in.
+    pub metrics: GraphQLMetrics,
+    /// Whether operation names are recorded, otherwise all operations are recorded as `other`.
+    ///
+    /// Operation names are chosen by clients, so they should only be recorded if the operation allow-list bounds them.
+    pub record_operation_names: bool,
+}
+
+impl ExtensionFactory for OperationMetrics {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(OperationMetricsExtension {
+            metrics: self.metrics.clone(),
+            record_operation_names: self.record_operation_names,
+            operation_types: Mutex::default(),
+        })
+    }
+}
+
+/// Per-request state of the operation metrics.
+struct OperationMetricsExtension {
+    metrics: GraphQLMetrics,
+    /// Whether operation names are recorded.
+    record_operation_names: bool,
+    /// Names and types of the operations of the parsed document.
+    operation_types: Mutex<Vec<(Option<String>, OperationType)>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for OperationMetricsExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        *self.operation_types.lock().unwrap() = document
+            .operations
+            .iter()
+            .map(|(name, operation)| (name.map(|name| name.to_string()), operation.node.ty))
+            .collect();
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let start = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+        let operation = self
+            .operation_types
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(name, _)| operation_name.is_none() || name.as_deref() == operation_name)
+            .map(|(name, operation_type)| (name.clone(), operation_type.to_string()));
+        if let Some((name, operation_type)) = operation {
+            let name = match self.record_operation_names {
+                true => name.as_deref().unwrap_or(ANONYMOUS_OPERATION),
+                false => OTHER_OPERATION,
+            };
+            self.metrics
+                .record_executed(name, &operation_type, start.elapsed());
+        }
+        response
+    }
+}
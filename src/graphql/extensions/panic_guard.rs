@@ -0,0 +1,46 @@
+use std::{panic::AssertUnwindSafe, sync::Arc};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ServerError, ServerResult, Value,
+};
+use futures::FutureExt;
+
+use crate::panic_handling::{report_panic, INTERNAL, INTERNAL_ERROR_MESSAGE};
+
+/// GraphQL extension catching panics of resolvers.
+///
+/// A panicking resolver results in an error with the code `INTERNAL` and a correlation UUID identifying the panic in the logs.
+/// Other fields of the operation are resolved as usual.
+pub struct PanicGuard;
+
+impl ExtensionFactory for PanicGuard {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PanicGuardExtension)
+    }
+}
+
+struct PanicGuardExtension;
+
+#[async_trait::async_trait]
+impl Extension for PanicGuardExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let location = format!("field: `{}`", info.path_node);
+        match AssertUnwindSafe(next.run(ctx, info)).catch_unwind().await {
+            Ok(result) => result,
+            Err(panic) => {
+                let correlation_id = report_panic("resolver", &location, panic.as_ref());
+                let mut error = ServerError::new(INTERNAL_ERROR_MESSAGE, None);
+                let extensions = error.extensions.get_or_insert_with(Default::default);
+                extensions.set("code", INTERNAL);
+                extensions.set("correlationId", correlation_id.0.to_string());
+                Err(error)
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery},
+    parser::types::{ExecutableDocument, Field, Selection, SelectionSet},
+    Name, Positioned, ServerError, ServerResult, Value, Variables,
+};
+
+/// GraphQL extension which rejects operations whose estimated number of returned nodes exceeds a budget.
+///
+/// Complements depth and complexity limits by accounting for page sizes across nested connections:
+/// every field counts as one node, multiplied by the page sizes of all enclosing connections.
+/// A field is a connection if it has a `first` or `last` argument or selects `nodes`,
+/// connections without page size argument are estimated with the default page size.
+pub struct ResponseSizeBudget {
+    /// Maximum estimated number of nodes of a response.
+    pub max_nodes: u64,
+    /// Page size of connections without `first` or `last` argument.
+    pub default_page_size: u64,
+}
+
+impl ExtensionFactory for ResponseSizeBudget {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ResponseSizeBudgetExtension {
+            max_nodes: self.max_nodes,
+            default_page_size: self.default_page_size,
+        })
+    }
+}
+
+/// Per-request state of the response size budget.
+struct ResponseSizeBudgetExtension {
+    max_nodes: u64,
+    default_page_size: u64,
+}
+
+#[async_trait::async_trait]
+impl Extension for ResponseSizeBudgetExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let estimator = NodeEstimator {
+            document: &document,
+            variables,
+            default_page_size: self.default_page_size,
+        };
+        for (_, operation) in document.operations.iter() {
+            let estimated_nodes = estimator.selection_set_nodes(
+                &operation.node.selection_set,
+                1,
+                &mut HashSet::new(),
+            );
+            if estimated_nodes > self.max_nodes {
+                let message = format!(
+                    "Operation would return an estimated {} nodes, which exceeds the response size budget of {} nodes. Request smaller pages or fewer nested connections.",
+                    estimated_nodes, self.max_nodes
+                );
+                return Err(ServerError::new(message, Some(operation.pos)));
+            }
+        }
+        Ok(document)
+    }
+}
+
+/// Estimates the number of nodes returned for the selections of a GraphQL document.
+struct NodeEstimator<'a> {
+    document: &'a ExecutableDocument,
+    variables: &'a Variables,
+    default_page_size: u64,
+}
+
+impl NodeEstimator<'_> {
+    /// Estimates the number of nodes of a selection set.
+    ///
+    /// Fragment spreads already expanded on the current path are skipped, so fragment cycles terminate.
+    ///
+    /// * `selection_set` - Selection set to estimate.
+    /// * `multiplier` - Number of times the selection set is returned, the product of the enclosing page sizes.
+    /// * `fragment_path` - Names of the fragments expanded on the current path.
+    fn selection_set_nodes(
+        &self,
+        selection_set: &Positioned<SelectionSet>,
+        multiplier: u64,
+        fragment_path: &mut HashSet<Name>,
+    ) -> u64 {
+        selection_set
+            .node
+            .items
+            .iter()
+            .fold(0u64, |nodes, selection| {
+                let selection_nodes = match &selection.node {
+                    Selection::Field(field) => self.field_nodes(field, multiplier, fragment_path),
+                    Selection::InlineFragment(inline_fragment) => self.selection_set_nodes(
+                        &inline_fragment.node.selection_set,
+                        multiplier,
+                        fragment_path,
+                    ),
+                    Selection::FragmentSpread(fragment_spread) => {
+                        let name = &fragment_spread.node.fragment_name.node;
+                        match self.document.fragments.get(name) {
+                            Some(fragment) if fragment_path.insert(name.clone()) => {
+                                let fragment_nodes = self.selection_set_nodes(
+                                    &fragment.node.selection_set,
+                                    multiplier,
+                                    fragment_path,
+                                );
+                                fragment_path.remove(name);
+                                fragment_nodes
+                            }
+                            _ => 0,
+                        }
+                    }
+                };
+                nodes.saturating_add(selection_nodes)
+            })
+    }
+
+    /// Estimates the number of nodes of a field and its selections.
+    ///
+    /// * `field` - Field to estimate.
+    /// * `multiplier` - Number of times the field is returned.
+    /// * `fragment_path` - Names of the fragments expanded on the current path.
+    fn field_nodes(
+        &self,
+        field: &Positioned<Field>,
+        multiplier: u64,
+        fragment_path: &mut HashSet<Name>,
+    ) -> u64 {
+        let children_multiplier = match self.page_size(&field.node) {
+            Some(page_size) => multiplier.saturating_mul(page_size),
+            None => multiplier,
+        };
+        multiplier.saturating_add(self.selection_set_nodes(
+            &field.node.selection_set,
+            children_multiplier,
+            fragment_path,
+        ))
+    }
+
+    /// Returns the page size of a field if it is a connection.
+    ///
+    /// * `field` - Field to check.
+    fn page_size(&self, field: &Field) -> Option<u64> {
+        let page_size_argument = field
+            .get_argument("first")
+            .or_else(|| field.get_argument("last"));
+        match page_size_argument {
+            Some(argument) => {
+                let value = argument
+                    .node
+                    .clone()
+                    .into_const_with(|name| self.variables.get(&name).cloned().ok_or(()));
+                let page_size = match value {
+                    Ok(Value::Number(number)) => number.as_u64(),
+                    _ => None,
+                };
+                Some(page_size.unwrap_or(self.default_page_size))
+            }
+            None => field
+                .selection_set
+                .node
+                .items
+                .iter()
+                .any(|selection| {
+                    matches!(&selection.node, Selection::Field(child) if child.node.name.node == "nodes")
+                })
+                .then_some(self.default_page_size),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation},
+    ServerError, ValidationResult,
+};
+
+/// Error code of errors raised while validating a GraphQL document against the schema.
+const VALIDATION_FAILED: &str = "VALIDATION_FAILED";
+
+/// GraphQL extension adding the error code `VALIDATION_FAILED` to validation errors.
+///
+/// Validation errors include arguments with malformed scalar values, e.g. invalid UUIDs, and name the affected argument.
+pub struct ValidationErrorCode;
+
+impl ExtensionFactory for ValidationErrorCode {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ValidationErrorCodeExtension)
+    }
+}
+
+struct ValidationErrorCodeExtension;
+
+#[async_trait::async_trait]
+impl Extension for ValidationErrorCodeExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        next.run(ctx).await.map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|mut error| {
+                    error
+                        .extensions
+                        .get_or_insert_with(Default::default)
+                        .set("code", VALIDATION_FAILED);
+                    error
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,160 @@
+use async_graphql::{Error, Result};
+use reqwest::Url;
+
+use crate::graphql::model::date_time::DateTime;
+use crate::settings::Settings;
+
+/// Checks if a cover image URL is an absolute HTTP(S) URL of an allowed host.
+///
+/// All hosts are allowed if the service settings do not restrict the cover image hosts.
+///
+/// * `settings` - Service settings defining the allowed cover image hosts.
+/// * `cover_image_url` - Cover image URL to validate.
+pub fn validate_cover_image_url(settings: &Settings, cover_image_url: &str) -> Result<()> {
+    let url = Url::parse(cover_image_url).map_err(|_| {
+        Error::new(format!(
+            "Cover image URL: `{}` is not a valid URL.",
+            cover_image_url
+        ))
+    })?;
+    if !matches!(url.scheme(), "http" | "https") {
+        let message = format!(
+            "Cover image URL: `{}` must use the scheme `http` or `https`.",
+            cover_image_url
+        );
+        return Err(Error::new(message));
+    }
+    let allowed_hosts = &settings.cover_image_allowed_hosts.0;
+    match url.host_str() {
+        Some(host)
+            if allowed_hosts.is_empty()
+                || allowed_hosts
+                    .iter()
+                    .any(|allowed_host| allowed_host == host) =>
+        {
+            Ok(())
+        }
+        _ => {
+            let message = format!(
+                "Cover image URL: `{}` does not belong to an allowed host.",
+                cover_image_url
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Checks if an icon is a single emoji or, if the service settings define allowed icons, one of the allowed icons.
+///
+/// * `settings` - Service settings defining the allowed icons.
+/// * `icon` - Icon to validate.
+pub fn validate_icon(settings: &Settings, icon: &str) -> Result<()> {
+    let allowed_icons = &settings.allowed_icons.0;
+    let is_valid = match allowed_icons.is_empty() {
+        true => is_single_emoji(icon),
+        false => allowed_icons
+            .iter()
+            .any(|allowed_icon| allowed_icon == icon),
+    };
+    match is_valid {
+        true => Ok(()),
+        false => {
+            let message = format!(
+                "Icon: `{}` is not a single emoji or not an allowed icon.",
+                icon
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Checks if a string consists of exactly one emoji, including emoji modifiers, flags and ZWJ sequences.
+///
+/// * `s` - String to check.
+fn is_single_emoji(s: &str) -> bool {
+    let mut emoji_count = 0;
+    let mut regional_indicator_count = 0;
+    let mut follows_zero_width_joiner = false;
+    for c in s.chars() {
+        match c {
+            '\u{200D}' => {
+                follows_zero_width_joiner = true;
+                continue;
+            }
+            // Variation selector, keycap, skin tone modifiers and tag characters modify the preceding emoji.
+            '\u{FE0F}' | '\u{20E3}' | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}' => {}
+            // Flags consist of two regional indicators.
+            '\u{1F1E6}'..='\u{1F1FF}' => {
+                if regional_indicator_count % 2 == 0 && !follows_zero_width_joiner {
+                    emoji_count += 1;
+                }
+                regional_indicator_count += 1;
+            }
+            c if is_pictographic(c) => {
+                if !follows_zero_width_joiner {
+                    emoji_count += 1;
+                }
+            }
+            _ => return false,
+        }
+        follows_zero_width_joiner = false;
+    }
+    emoji_count == 1
+}
+
+/// Checks if a character belongs to the Unicode blocks containing emoji.
+///
+/// * `c` - Character to check.
+fn is_pictographic(c: char) -> bool {
+    matches!(c,
+        '\u{00A9}'
+        | '\u{00AE}'
+        | '\u{203C}'
+        | '\u{2049}'
+        | '\u{2122}'
+        | '\u{2139}'
+        | '\u{2190}'..='\u{21FF}'
+        | '\u{2300}'..='\u{23FF}'
+        | '\u{24C2}'
+        | '\u{25AA}'..='\u{25FE}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{3030}'
+        | '\u{303D}'
+        | '\u{3297}'
+        | '\u{3299}'
+        | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+/// Checks if a color is a hex color of the form `#RGB` or `#RRGGBB`.
+///
+/// * `color` - Color to validate.
+pub fn validate_color(color: &str) -> Result<()> {
+    let is_valid = match color.strip_prefix('#') {
+        Some(hex_digits) => {
+            matches!(hex_digits.len(), 3 | 6) && hex_digits.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    };
+    match is_valid {
+        true => Ok(()),
+        false => {
+            let message = format!(
+                "Color: `{}` is not a hex color of the form `#RGB` or `#RRGGBB`.",
+                color
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Checks if an expiration timestamp lies in the future.
+///
+/// * `expires_at` - Expiration timestamp to validate.
+pub fn validate_expires_at(expires_at: &DateTime) -> Result<()> {
+    match *expires_at > DateTime::now() {
+        true => Ok(()),
+        false => Err(Error::new("Expiration timestamp must lie in the future.")),
+    }
+}
@@ -0,0 +1,179 @@
+use std::future::Future;
+
+use async_graphql::{Context, Error, Result};
+use bson::{doc, Bson, DateTime};
+use log::warn;
+use mongodb::{
+    error::{ErrorKind, WriteFailure},
+    Collection, Database,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::authorization::{ApiKeyPrincipal, AuthorizedUserHeader};
+use crate::tenant::tenant_id;
+
+/// MongoDB error code of duplicate key errors.
+pub const DUPLICATE_KEY_ERROR_CODE: i32 = 11000;
+
+/// Value of the `Idempotency-Key` HTTP header of a request.
+#[derive(Debug, Clone)]
+pub struct IdempotencyKey(pub String);
+
+/// Result of a mutation stored for an idempotency key.
+///
+/// Records expire according to the TTL index on `created_at`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Idempotency key, scoped to tenant, user and mutation.
+    pub _id: String,
+    /// Identifier of the tenant the mutation was requested for.
+    pub tenant_id: String,
+    /// SHA-256 hash of the arguments of the mutation, a key can not be reused with other arguments.
+    #[serde(default)]
+    pub request_hash: String,
+    /// Serialized result of the mutation, `None` while the mutation is in progress.
+    pub result: Option<Bson>,
+    /// Timestamp when the mutation was first requested.
+    pub created_at: DateTime,
+}
+
+/// Runs a mutation at most once per idempotency key of the request.
+///
+/// Returns the stored result if the mutation already succeeded for the idempotency key with the same arguments,
+/// reusing the idempotency key with other arguments is rejected.
+/// Runs the mutation without idempotency guarantees if the request has no `Idempotency-Key` header.
+/// Failed mutations are not stored, so they can be retried with the same idempotency key.
+/// If the result of a successful mutation can not be stored, the idempotency key is released and the result is returned.
+///
+/// * `ctx` - GraphQL context containing the idempotency key, the database client and the caller.
+/// * `mutation_name` - Name of mutation, used to scope the idempotency key.
+/// * `mutation` - Mutation to run.
+pub async fn with_idempotency<T, F>(
+    ctx: &Context<'_>,
+    mutation_name: &str,
+    mutation: F,
+) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<T>>,
+{
+    let idempotency_key = match ctx.data_opt::<IdempotencyKey>() {
+        Some(idempotency_key) => idempotency_key,
+        None => return mutation.await,
+    };
+    let db_client = ctx.data::<Database>()?;
+    let collection: Collection<IdempotencyRecord> =
+        db_client.collection::<IdempotencyRecord>("idempotency_keys");
+    let user_id = match (
+        ctx.data_opt::<AuthorizedUserHeader>(),
+        ctx.data_opt::<ApiKeyPrincipal>(),
+    ) {
+        (Some(authorized_user_header), _) => authorized_user_header.id.to_string(),
+        (None, Some(api_key_principal)) => format!("api-key-{}", api_key_principal.name),
+        (None, None) => String::new(),
+    };
+    let tenant_id = tenant_id(ctx)?;
+    let id = format!(
+        "{}:{}:{}:{}",
+        tenant_id.0, user_id, mutation_name, idempotency_key.0
+    );
+    let request_hash = hash_arguments(ctx)?;
+    if let Some(stored_result) =
+        reserve_idempotency_key(&collection, &tenant_id.0, &id, &request_hash).await?
+    {
+        return Ok(bson::from_bson(stored_result)?);
+    }
+    match mutation.await {
+        Ok(result) => {
+            let update_result = match bson::to_bson(&result) {
+                Ok(result_bson) => collection
+                    .update_one(
+                        doc! {"_id": &id},
+                        doc! {"$set": {"result": result_bson}},
+                        None,
+                    )
+                    .await
+                    .map_err(|error| error.to_string()),
+                Err(error) => Err(error.to_string()),
+            };
+            if let Err(error) = update_result {
+                warn!(
+                    "Storing result of idempotency key: `{}` failed, releasing the key: {}",
+                    idempotency_key.0, error
+                );
+                let _ = collection.delete_one(doc! {"_id": &id}, None).await;
+            }
+            Ok(result)
+        }
+        Err(error) => {
+            let _ = collection.delete_one(doc! {"_id": &id}, None).await;
+            Err(error)
+        }
+    }
+}
+
+/// Hashes the arguments of the mutation of a context, with variables resolved.
+///
+/// * `ctx` - GraphQL context of the mutation.
+fn hash_arguments(ctx: &Context<'_>) -> Result<String> {
+    let arguments = serde_json::to_vec(&ctx.field().arguments()?)?;
+    Ok(Sha256::digest(arguments)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Reserves an idempotency key for a mutation in progress.
+///
+/// Returns the stored result if the idempotency key was already used by a successful mutation with the same arguments.
+/// Returns an error if the idempotency key was used with other arguments or a mutation with the idempotency key is still in progress.
+///
+/// * `collection` - MongoDB collection of idempotency records.
+/// * `tenant_id` - Identifier of the tenant the mutation is requested for.
+/// * `id` - Idempotency key, scoped to tenant, user and mutation.
+/// * `request_hash` - Hash of the arguments of the mutation.
+async fn reserve_idempotency_key(
+    collection: &Collection<IdempotencyRecord>,
+    tenant_id: &str,
+    id: &str,
+    request_hash: &str,
+) -> Result<Option<Bson>> {
+    let record = IdempotencyRecord {
+        _id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        request_hash: request_hash.to_string(),
+        result: None,
+        created_at: DateTime::now(),
+    };
+    match collection.insert_one(record, None).await {
+        Ok(_) => Ok(None),
+        Err(error) if is_duplicate_key_error(&error) => {
+            match collection.find_one(doc! {"_id": id}, None).await {
+                Ok(Some(record)) if record.request_hash != request_hash => Err(Error::new(
+                    "Idempotency key was already used for a mutation with other arguments.",
+                )),
+                Ok(Some(IdempotencyRecord {
+                    result: Some(result),
+                    ..
+                })) => Ok(Some(result)),
+                Ok(_) => Err(Error::new(
+                    "A mutation with the same idempotency key is in progress.",
+                )),
+                Err(_) => Err(Error::new("Retrieving idempotency key failed in MongoDB.")),
+            }
+        }
+        Err(_) => Err(Error::new("Storing idempotency key failed in MongoDB.")),
+    }
+}
+
+/// Checks if a MongoDB error is caused by a duplicate key.
+///
+/// * `error` - MongoDB error to check.
+pub fn is_duplicate_key_error(error: &mongodb::error::Error) -> bool {
+    matches!(
+        error.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(write_error))
+            if write_error.code == DUPLICATE_KEY_ERROR_CODE
+    )
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read},
+};
+
+use super::model::uuid::Uuid;
+use super::mutation_payload_structs::UploadLineError;
+
+/// Valid line of an uploaded file of product variant UUIDs.
+#[derive(Debug)]
+pub struct UploadLine {
+    /// Number of the line in the file, starting at 1.
+    pub line: u64,
+    /// UUID of the product variant of the line.
+    pub product_variant_id: Uuid,
+}
+
+/// Parses an uploaded file with one product variant UUID per line.
+///
+/// Empty lines are skipped, surrounding whitespace is ignored.
+/// Product variants listed in multiple lines are reported for each repeated line.
+///
+/// * `reader` - Content of the file.
+pub fn parse_product_variant_id_lines(
+    reader: impl Read,
+) -> (Vec<UploadLine>, Vec<UploadLineError>) {
+    let mut lines = vec![];
+    let mut line_errors = vec![];
+    let mut first_lines: HashMap<Uuid, u64> = HashMap::new();
+    for (index, content) in BufReader::new(reader).lines().enumerate() {
+        let line = index as u64 + 1;
+        let content = match content {
+            Ok(content) => content,
+            Err(error) => {
+                line_errors.push(UploadLineError {
+                    line,
+                    message: format!("Line could not be read: {}.", error),
+                });
+                continue;
+            }
+        };
+        let content = content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let product_variant_id = match Uuid::parse_str(content) {
+            Ok(product_variant_id) => product_variant_id,
+            Err(_) => {
+                line_errors.push(UploadLineError {
+                    line,
+                    message: format!("`{}` is not a valid UUID.", content),
+                });
+                continue;
+            }
+        };
+        match first_lines.get(&product_variant_id) {
+            Some(first_line) => line_errors.push(UploadLineError {
+                line,
+                message: format!(
+                    "Product variant with the UUID: `{}` is already listed in line: `{}`.",
+                    product_variant_id, first_line
+                ),
+            }),
+            None => {
+                first_lines.insert(product_variant_id, line);
+                lines.push(UploadLine {
+                    line,
+                    product_variant_id,
+                });
+            }
+        }
+    }
+    (lines, line_errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+    const SECOND_ID: &str = "123e4567-e89b-12d3-a456-426614174001";
+
+    #[test]
+    fn parse_product_variant_id_lines_skips_empty_lines_and_trims_whitespace() {
+        let content = format!("  {}  \n\n{}\n", FIRST_ID, SECOND_ID);
+        let (lines, line_errors) = parse_product_variant_id_lines(content.as_bytes());
+        assert!(line_errors.is_empty());
+        let parsed: Vec<(u64, String)> = lines
+            .iter()
+            .map(|line| (line.line, line.product_variant_id.to_string()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![(1, FIRST_ID.to_string()), (3, SECOND_ID.to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_product_variant_id_lines_reports_invalid_and_duplicate_lines() {
+        let content = format!("{}\nnot-a-uuid\n{}\n", FIRST_ID, FIRST_ID);
+        let (lines, line_errors) = parse_product_variant_id_lines(content.as_bytes());
+        assert_eq!(lines.len(), 1);
+        assert_eq!(line_errors.len(), 2);
+        assert_eq!(line_errors[0].line, 2);
+        assert_eq!(line_errors[0].message, "`not-a-uuid` is not a valid UUID.");
+        assert_eq!(line_errors[1].line, 3);
+        assert!(line_errors[1]
+            .message
+            .ends_with("is already listed in line: `1`."));
+    }
+}
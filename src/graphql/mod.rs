@@ -1,4 +1,15 @@
+pub mod creation_throttle;
+pub mod csv_import;
+pub mod data_loaders;
+pub mod extensions;
+pub mod field_validation;
+pub mod idempotency;
+pub mod item_upload;
 pub mod model;
 pub mod mutation;
 pub mod mutation_input_structs;
+pub mod mutation_payload_structs;
+pub mod mutation_validation;
+pub mod pagination;
 pub mod query;
+pub mod subscription;
@@ -0,0 +1,41 @@
+use async_graphql::SimpleObject;
+use serde::{Serialize, Serializer};
+
+use super::date_time::DateTime;
+
+/// Version and git metadata of the running build, embedded at compile time.
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    /// Version of the wishlist service.
+    pub version: String,
+    /// Hash of the git commit the service was built from, `null` if unknown.
+    pub git_sha: Option<String>,
+    /// Timestamp of the build.
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub built_at: DateTime,
+}
+
+impl BuildInfo {
+    /// Returns the build info of the running binary.
+    pub fn current() -> Self {
+        let git_sha = env!("BUILD_GIT_SHA");
+        let build_timestamp: i64 = env!("BUILD_TIMESTAMP").parse().unwrap_or_default();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: (!git_sha.is_empty()).then(|| git_sha.to_string()),
+            built_at: DateTime::from_millis(build_timestamp * 1000),
+        }
+    }
+}
+
+/// Serializes a timestamp as RFC3339 string.
+///
+/// * `date_time` - Timestamp to serialize.
+/// * `serializer` - Serializer to serialize with.
+fn serialize_rfc3339<S: Serializer>(
+    date_time: &DateTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    bson::serde_helpers::serialize_bson_datetime_as_rfc3339_string(&date_time.0, serializer)
+}
@@ -0,0 +1,6 @@
+use super::{super::failed_event::FailedEvent, typed_connection::typed_connection};
+
+typed_connection! {
+    /// A connection of failed events.
+    FailedEventConnection(FailedEvent)
+}
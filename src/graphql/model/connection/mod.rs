@@ -1,3 +1,6 @@
 pub mod base_connection;
+pub mod failed_event_connection;
 pub mod product_variant_connection;
+pub mod typed_connection;
+pub mod wishlist_activity_connection;
 pub mod wishlist_connection;
@@ -0,0 +1,48 @@
+/// Generates a connection type of a node type, named for GraphQL, with the conversion from `BaseConnection`.
+///
+/// `BaseConnection` is generic, so each node type needs its own connection type to prevent GraphQL naming conflicts.
+/// Connections with additional fields, like `WishlistConnection`, are written by hand.
+///
+/// ```ignore
+/// typed_connection! {
+///     /// A connection of product variants.
+///     ProductVariantConnection(ProductVariant)
+/// }
+/// ```
+macro_rules! typed_connection {
+    ($(#[$meta:meta])* $connection:ident($node:ty)) => {
+        $(#[$meta])*
+        #[derive(async_graphql::SimpleObject)]
+        #[graphql(shareable)]
+        pub struct $connection {
+            /// The resulting entities.
+            pub nodes: Vec<$node>,
+            /// Whether this connection has a next page.
+            pub has_next_page: bool,
+            /// Whether this connection has a previous page.
+            pub has_previous_page: bool,
+            /// The total amount of items in this connection.
+            pub total_count: u64,
+        }
+
+        /// Implementation of conversion from `BaseConnection` to the typed connection.
+        ///
+        /// Prevents GraphQL naming conflicts.
+        impl From<$crate::graphql::model::connection::base_connection::BaseConnection<$node>>
+            for $connection
+        {
+            fn from(
+                value: $crate::graphql::model::connection::base_connection::BaseConnection<$node>,
+            ) -> Self {
+                Self {
+                    nodes: value.nodes,
+                    has_next_page: value.has_next_page,
+                    has_previous_page: value.has_previous_page,
+                    total_count: value.total_count,
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use typed_connection;
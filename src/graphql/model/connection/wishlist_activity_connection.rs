@@ -0,0 +1,19 @@
+use async_graphql::SimpleObject;
+
+use super::super::wishlist_activity::WishlistActivity;
+
+/// A connection of changes in the activity feed of a wishlist.
+///
+/// Has no total count, as the changes are derived from the audit history while they are retrieved.
+#[derive(SimpleObject)]
+#[graphql(shareable)]
+pub struct WishlistActivityConnection {
+    /// The resulting entities.
+    pub nodes: Vec<WishlistActivity>,
+    /// Whether this connection has a next page.
+    pub has_next_page: bool,
+    /// Whether this connection has a previous page.
+    pub has_previous_page: bool,
+    /// Cursor of the last change, which can be passed as `after` to retrieve the following changes.
+    pub end_cursor: Option<String>,
+}
@@ -1,6 +1,7 @@
 use async_graphql::SimpleObject;
 
 use super::{super::wishlist::Wishlist, base_connection::BaseConnection};
+use crate::graphql::pagination::LastUpdatedCursor;
 
 /// A connection of wishlists.
 #[derive(SimpleObject)]
@@ -10,19 +11,54 @@ pub struct WishlistConnection {
     pub nodes: Vec<Wishlist>,
     /// Whether this connection has a next page.
     pub has_next_page: bool,
+    /// Whether this connection has a previous page.
+    pub has_previous_page: bool,
     /// The total amount of items in this connection.
     pub total_count: u64,
+    /// Cursor of the last wishlist, which can be passed as `updatedBefore` to retrieve the following wishlists.
+    ///
+    /// `null` unless wishlists are in the keyset order of descending last update.
+    pub end_cursor: Option<String>,
+    /// Cursor of the first wishlist, which can be passed as `before` to retrieve the preceding wishlists.
+    ///
+    /// `null` unless wishlists are in the keyset order of descending last update.
+    pub start_cursor: Option<String>,
+}
+
+impl WishlistConnection {
+    /// Converts a page of wishlists in the keyset order of `LastUpdatedCursor::sorting_document` and adds its cursors.
+    ///
+    /// * `value` - Page of wishlists in keyset order.
+    pub fn from_keyset_page(value: BaseConnection<Wishlist>) -> Self {
+        let cursor_of = |wishlist: &Wishlist| {
+            LastUpdatedCursor {
+                last_updated_at: wishlist.last_updated_at,
+                id: wishlist._id,
+            }
+            .to_string()
+        };
+        let end_cursor = value.nodes.last().map(cursor_of);
+        let start_cursor = value.nodes.first().map(cursor_of);
+        Self {
+            end_cursor,
+            start_cursor,
+            ..value.into()
+        }
+    }
 }
 
 /// Implementation of conversion from `BaseConnection<Wishlist>` to `WishlistConnection`.
 ///
-/// Prevents GraphQL naming conflicts.
+/// Prevents GraphQL naming conflicts. Cursors are `null`, see `WishlistConnection::from_keyset_page`.
 impl From<BaseConnection<Wishlist>> for WishlistConnection {
     fn from(value: BaseConnection<Wishlist>) -> Self {
         Self {
             nodes: value.nodes,
             has_next_page: value.has_next_page,
+            has_previous_page: value.has_previous_page,
             total_count: value.total_count,
+            end_cursor: None,
+            start_cursor: None,
         }
     }
 }
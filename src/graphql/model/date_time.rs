@@ -0,0 +1,64 @@
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use bson::Bson;
+use serde::{Deserialize, Serialize};
+
+/// Timestamp, represented as RFC3339 string in GraphQL and as BSON datetime in MongoDB.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(transparent)]
+pub struct DateTime(pub bson::DateTime);
+
+impl DateTime {
+    /// Returns the current timestamp.
+    pub fn now() -> Self {
+        Self(bson::DateTime::now())
+    }
+
+    /// Constructs a timestamp from milliseconds since the Unix epoch.
+    ///
+    /// * `millis` - Milliseconds since the Unix epoch.
+    pub fn from_millis(millis: i64) -> Self {
+        Self(bson::DateTime::from_millis(millis))
+    }
+
+    /// Returns the milliseconds since the Unix epoch.
+    pub fn timestamp_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+}
+
+/// A date-time string in RFC3339 format, e.g. `2024-01-01T12:00:00.000Z`.
+#[Scalar(
+    name = "DateTime",
+    specified_by_url = "https://datatracker.ietf.org/doc/html/rfc3339"
+)]
+impl ScalarType for DateTime {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::String(s) => bson::DateTime::parse_rfc3339_str(s)
+                .map(DateTime)
+                .map_err(|_| {
+                    InputValueError::custom(format!("`{}` is not an RFC3339 date-time.", s))
+                }),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self.0.try_to_rfc3339_string() {
+            Ok(rfc3339_string) => Value::String(rfc3339_string),
+            Err(_) => Value::Null,
+        }
+    }
+}
+
+impl From<bson::DateTime> for DateTime {
+    fn from(value: bson::DateTime) -> Self {
+        Self(value)
+    }
+}
+
+impl From<DateTime> for Bson {
+    fn from(value: DateTime) -> Self {
+        Bson::DateTime(value.0)
+    }
+}
@@ -0,0 +1,27 @@
+use async_graphql::SimpleObject;
+use bson::Document;
+use serde::{Deserialize, Serialize};
+
+use super::{date_time::DateTime, uuid::Uuid};
+
+/// Event whose handling failed, kept until it is handled successfully.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+pub struct FailedEvent {
+    /// Identifier of the event, the CloudEvent id of the received event.
+    pub _id: String,
+    /// Topic the event was published on.
+    pub topic: String,
+    /// UUID of the entity the event refers to.
+    pub subject_id: Uuid,
+    /// Data of the event, used to reprocess the event.
+    #[graphql(skip)]
+    pub data: Document,
+    /// Error of the last failed attempt to handle the event.
+    pub error: String,
+    /// Number of failed attempts to handle the event.
+    pub attempts: u32,
+    /// Timestamp of the first failed attempt to handle the event.
+    pub first_failed_at: DateTime,
+    /// Timestamp of the last failed attempt to handle the event.
+    pub last_failed_at: DateTime,
+}
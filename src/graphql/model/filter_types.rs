@@ -0,0 +1,279 @@
+use async_graphql::{InputObject, SimpleObject};
+use bson::{doc, Bson, Document};
+
+use super::{date_time::DateTime, uuid::Uuid};
+
+/// Specifies which wishlists are retrieved.
+///
+/// All specified conditions must hold. Filters are composed with `and`, `or` and `not`, e.g. `{or: [{minItems: 10}, {nameContains: "birthday"}]}`.
+#[derive(SimpleObject, InputObject, Default)]
+pub struct WishlistFilterInput {
+    /// Minimum number of product variants in wishlist.
+    pub min_items: Option<u32>,
+    /// Maximum number of product variants in wishlist.
+    pub max_items: Option<u32>,
+    /// Only wishlists created after this timestamp.
+    pub created_after: Option<DateTime>,
+    /// Only wishlists created before this timestamp.
+    pub created_before: Option<DateTime>,
+    /// Only wishlists last updated after this timestamp.
+    pub updated_after: Option<DateTime>,
+    /// Only wishlists last updated before this timestamp.
+    pub updated_before: Option<DateTime>,
+    /// Only wishlists with an occasion date after this timestamp.
+    pub occasion_after: Option<DateTime>,
+    /// Only wishlists with an occasion date before this timestamp.
+    pub occasion_before: Option<DateTime>,
+    /// Only wishlists whose name contains this text, ignoring case.
+    pub name_contains: Option<String>,
+    /// Only archived wishlists if `true`, only wishlists which are not archived if `false`.
+    pub archived: Option<bool>,
+    /// Only suspended wishlists if `true`, only wishlists which are not suspended if `false`.
+    pub suspended: Option<bool>,
+    /// Only wishlists matching all of these filters.
+    pub and: Option<Vec<WishlistFilterInput>>,
+    /// Only wishlists matching at least one of these filters, ignored if empty.
+    pub or: Option<Vec<WishlistFilterInput>>,
+    /// Only wishlists not matching this filter.
+    pub not: Option<Box<WishlistFilterInput>>,
+}
+
+impl WishlistFilterInput {
+    /// Builds MongoDB filter document, which is combined with other filters of a wishlist query.
+    ///
+    /// Nested filters are compiled recursively into a single `$and` operator,
+    /// so the top-level `$or` of cursor and change filters can be added to the document.
+    pub fn to_document(&self) -> Document {
+        let mut filter = Document::new();
+        insert_range(
+            &mut filter,
+            "item_count",
+            self.min_items,
+            self.max_items,
+            "$gte",
+            "$lte",
+        );
+        insert_range(
+            &mut filter,
+            "created_at",
+            self.created_after,
+            self.created_before,
+            "$gt",
+            "$lt",
+        );
+        insert_range(
+            &mut filter,
+            "last_updated_at",
+            self.updated_after,
+            self.updated_before,
+            "$gt",
+            "$lt",
+        );
+        insert_range(
+            &mut filter,
+            "occasion_date",
+            self.occasion_after,
+            self.occasion_before,
+            "$gt",
+            "$lt",
+        );
+        if let Some(name_contains) = &self.name_contains {
+            filter.insert(
+                "name",
+                doc! {"$regex": escape_regex(name_contains), "$options": "i"},
+            );
+        }
+        match self.archived {
+            Some(true) => filter.insert("archived_at", doc! {"$ne": null}),
+            Some(false) => filter.insert("archived_at", Bson::Null),
+            None => None,
+        };
+        match self.suspended {
+            Some(true) => filter.insert("suspended", true),
+            Some(false) => filter.insert("suspended", doc! {"$ne": true}),
+            None => None,
+        };
+        let mut combined_filters = self.and.as_deref().map(to_documents).unwrap_or_default();
+        if let Some(or) = self.or.as_ref().filter(|or| !or.is_empty()) {
+            combined_filters.push(doc! {"$or": to_documents(or)});
+        }
+        if let Some(not) = &self.not {
+            combined_filters.push(doc! {"$nor": [not.to_document()]});
+        }
+        if !combined_filters.is_empty() {
+            filter.insert("$and", combined_filters);
+        }
+        filter
+    }
+}
+
+/// Inserts the filter of a field between two optional bounds, nothing is inserted without bounds.
+///
+/// * `filter` - Filter document to insert the field filter into.
+/// * `field` - Name of the MongoDB field.
+/// * `lower` - Lower bound of the field.
+/// * `upper` - Upper bound of the field.
+/// * `lower_operator` - Comparison operator of the lower bound.
+/// * `upper_operator` - Comparison operator of the upper bound.
+fn insert_range<T: Into<Bson>>(
+    filter: &mut Document,
+    field: &str,
+    lower: Option<T>,
+    upper: Option<T>,
+    lower_operator: &str,
+    upper_operator: &str,
+) {
+    let mut field_filter = Document::new();
+    if let Some(lower) = lower {
+        field_filter.insert(lower_operator, lower);
+    }
+    if let Some(upper) = upper {
+        field_filter.insert(upper_operator, upper);
+    }
+    if !field_filter.is_empty() {
+        filter.insert(field, field_filter);
+    }
+}
+
+/// Builds MongoDB filter documents of nested wishlist filters.
+///
+/// * `filters` - Nested wishlist filters.
+fn to_documents(filters: &[WishlistFilterInput]) -> Vec<Document> {
+    filters
+        .iter()
+        .map(WishlistFilterInput::to_document)
+        .collect()
+}
+
+/// Escapes the metacharacters of a regular expression, so text is matched literally.
+///
+/// * `text` - Text to escape.
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Specifies which product variants of a wishlist are retrieved.
+#[derive(SimpleObject, InputObject, Default)]
+pub struct ProductVariantFilterInput {
+    /// Only product variants which are available according to the inventory. Product variants without known availability are considered available.
+    pub only_available: Option<bool>,
+    /// Only product variants marked as purchased if `true`, only product variants which are not purchased if `false`.
+    pub purchased: Option<bool>,
+}
+
+/// Specifies which wishlists of the tenant are patched by a bulk update.
+#[derive(InputObject, Default)]
+pub struct BulkWishlistFilterInput {
+    /// Only wishlists of this user.
+    pub user_id: Option<Uuid>,
+    /// Only wishlists containing this product variant.
+    pub product_variant_id: Option<Uuid>,
+    /// Only archived wishlists if `true`, only wishlists which are not archived if `false`.
+    pub archived: Option<bool>,
+    /// Only wishlists matching the wishlist filter.
+    pub wishlist_filter: Option<WishlistFilterInput>,
+}
+
+impl BulkWishlistFilterInput {
+    /// Builds MongoDB filter document, which is scoped to the tenant of the bulk update.
+    pub fn to_document(&self) -> Document {
+        let mut filter = self
+            .wishlist_filter
+            .as_ref()
+            .map(WishlistFilterInput::to_document)
+            .unwrap_or_default();
+        if let Some(user_id) = self.user_id {
+            filter.insert("user._id", user_id);
+        }
+        if let Some(product_variant_id) = self.product_variant_id {
+            filter.insert("internal_product_variants._id", product_variant_id);
+        }
+        match self.archived {
+            Some(true) => filter.insert("archived_at", doc! {"$ne": null}),
+            Some(false) => filter.insert("archived_at", Bson::Null),
+            None => None,
+        };
+        filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_regex_escapes_metacharacters() {
+        assert_eq!(escape_regex("a.b*(c)"), "a\\.b\\*\\(c\\)");
+        assert_eq!(escape_regex("[x]|{y}^$"), "\\[x\\]\\|\\{y\\}\\^\\$");
+        assert_eq!(escape_regex("birthday"), "birthday");
+    }
+
+    #[test]
+    fn to_document_of_empty_filter_is_empty() {
+        assert_eq!(WishlistFilterInput::default().to_document(), doc! {});
+    }
+
+    #[test]
+    fn to_document_combines_ranges_and_flags() {
+        let filter = WishlistFilterInput {
+            min_items: Some(2),
+            max_items: Some(5),
+            name_contains: Some("a+b".to_string()),
+            archived: Some(false),
+            suspended: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.to_document(),
+            doc! {
+                "item_count": {"$gte": 2, "$lte": 5},
+                "name": {"$regex": "a\\+b", "$options": "i"},
+                "archived_at": Bson::Null,
+                "suspended": true,
+            }
+        );
+    }
+
+    #[test]
+    fn to_document_compiles_nested_filters_into_and() {
+        let filter = WishlistFilterInput {
+            and: Some(vec![WishlistFilterInput {
+                min_items: Some(1),
+                ..Default::default()
+            }]),
+            or: Some(vec![WishlistFilterInput {
+                archived: Some(true),
+                ..Default::default()
+            }]),
+            not: Some(Box::new(WishlistFilterInput {
+                suspended: Some(false),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.to_document(),
+            doc! {"$and": [
+                {"item_count": {"$gte": 1}},
+                {"$or": [{"archived_at": {"$ne": null}}]},
+                {"$nor": [{"suspended": {"$ne": true}}]},
+            ]}
+        );
+    }
+
+    #[test]
+    fn to_document_ignores_empty_or() {
+        let filter = WishlistFilterInput {
+            or: Some(vec![]),
+            ..Default::default()
+        };
+        assert_eq!(filter.to_document(), doc! {});
+    }
+}
@@ -1,16 +1,96 @@
-use async_graphql::SimpleObject;
-use bson::{doc, Bson, Uuid};
+use async_graphql::{dataloader::DataLoader, ComplexObject, Context, Error, Result, SimpleObject};
+use bson::{doc, Bson};
+use futures::TryStreamExt;
+use mongodb::{options::FindOptions, Collection, Database};
 use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, hash::Hash};
 
+use crate::authorization::authorized_user_id;
+use crate::graphql::data_loaders::ObjectLoader;
+use crate::tenant::tenant_id;
+
+use super::{
+    product_variant_metadata::{Money, ProductVariantMetadata},
+    uuid::Uuid,
+    wishlist::Wishlist,
+    wishlist_membership::WishlistMembership,
+};
+
 /// Foreign type of a product variant.
 #[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Copy, Clone, SimpleObject)]
-#[graphql(unresolvable)]
+#[graphql(complex)]
 pub struct ProductVariant {
     /// UUID of the product variant.
     pub _id: Uuid,
 }
 
+#[ComplexObject]
+impl ProductVariant {
+    /// Retrieves whether and in which wishlists the calling user has the product variant.
+    async fn in_wishlist_of_user<'a>(&self, ctx: &Context<'a>) -> Result<WishlistMembership> {
+        let user_id = authorized_user_id(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let filter = tenant_id(ctx)?.scope(doc! {
+            "user._id": user_id,
+            "internal_product_variants._id": self._id,
+        });
+        let find_options = FindOptions::builder().sort(doc! {"_id": 1}).build();
+        let message = "Retrieving wishlists of user failed in MongoDB.";
+        let wishlists: Vec<Wishlist> = match collection.find(filter, find_options).await {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| Error::new(message))?,
+            Err(_) => return Err(Error::new(message)),
+        };
+        Ok(WishlistMembership {
+            is_wishlisted: !wishlists.is_empty(),
+            wishlists,
+        })
+    }
+
+    /// Name of the product variant from the catalog, for clients which can not resolve it through federation.
+    async fn name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        Ok(self.metadata(ctx).await?.and_then(|metadata| metadata.name))
+    }
+
+    /// URL of an image of the product variant from the catalog, for clients which can not resolve it through federation.
+    async fn image_url<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        Ok(self
+            .metadata(ctx)
+            .await?
+            .and_then(|metadata| metadata.image_url))
+    }
+
+    /// Current retail price of the product variant from the catalog, for clients which can not resolve it through federation.
+    async fn current_price<'a>(&self, ctx: &Context<'a>) -> Result<Option<Money>> {
+        Ok(self
+            .metadata(ctx)
+            .await?
+            .and_then(|metadata| metadata.current_price))
+    }
+
+    /// Whether the product variant is available according to the inventory, `null` if its availability is unknown.
+    async fn available<'a>(&self, ctx: &Context<'a>) -> Result<Option<bool>> {
+        Ok(self
+            .metadata(ctx)
+            .await?
+            .and_then(|metadata| metadata.available))
+    }
+}
+
+impl ProductVariant {
+    /// Loads the catalog and inventory metadata of the product variant with the data loader of the request.
+    ///
+    /// * `ctx` - GraphQL context containing the data loader.
+    pub async fn metadata(&self, ctx: &Context<'_>) -> Result<Option<ProductVariantMetadata>> {
+        ctx.data::<DataLoader<ObjectLoader<ProductVariantMetadata>>>()?
+            .load_one(self._id)
+            .await
+    }
+}
+
 impl PartialOrd for ProductVariant {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self._id.partial_cmp(&other._id)
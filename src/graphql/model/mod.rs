@@ -1,5 +1,20 @@
+pub mod build_info;
 pub mod connection;
+pub mod date_time;
+pub mod failed_event;
+pub mod filter_types;
 pub mod foreign_types;
 pub mod order_types;
+pub mod ownable;
+pub mod product_variant_metadata;
+pub mod purchased_item;
+pub mod quota;
+pub mod statistics;
+pub mod target_price;
 pub mod user;
+pub mod user_preferences;
+pub mod uuid;
 pub mod wishlist;
+pub mod wishlist_activity;
+pub mod wishlist_item_added;
+pub mod wishlist_membership;
@@ -1,20 +1,16 @@
 use async_graphql::{Enum, InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
 
 /// GraphQL order direction.
-#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize, Debug)]
 pub enum OrderDirection {
     /// Ascending order direction.
+    #[default]
     Asc,
     /// Descending order direction.
     Desc,
 }
 
-impl Default for OrderDirection {
-    fn default() -> Self {
-        Self::Asc
-    }
-}
-
 /// Implements conversion to `i32` for MongoDB document sorting.
 impl From<OrderDirection> for i32 {
     fn from(value: OrderDirection) -> Self {
@@ -25,92 +21,97 @@ impl From<OrderDirection> for i32 {
     }
 }
 
-/// Describes the fields that a wishlist can be ordered by.
-#[derive(Enum, Copy, Clone, Eq, PartialEq)]
-pub enum WishlistOrderField {
-    /// Orders by "id".
-    Id,
-    /// Orders by "user_id".
-    UserId,
-    /// Orders by "name".
-    Name,
-    /// Orders by "created_at".
-    CreatedAt,
-    /// Orders by "last_updated_at".
-    LastUpdatedAt,
-}
-
-impl WishlistOrderField {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            WishlistOrderField::Id => "_id",
-            WishlistOrderField::UserId => "user._id",
-            WishlistOrderField::Name => "name",
-            WishlistOrderField::CreatedAt => "created_at",
-            WishlistOrderField::LastUpdatedAt => "last_updated_at",
+/// Generates the ordering input of a connection: an enum of the fields to order by and an input of field and direction.
+///
+/// Each field is mapped to the path of its MongoDB field, the field marked with `#[default]` is the default order.
+/// Ordering inputs of new connections are declared in this module with the macro.
+///
+/// ```ignore
+/// order_input! {
+///     /// Describes the fields that a foreign types can be ordered by.
+///     CommonOrderField {
+///         /// Orders by "id".
+///         #[default]
+///         Id => "_id",
+///     }
+///     /// Specifies the order of foreign types.
+///     CommonOrderInput
+/// }
+/// ```
+macro_rules! order_input {
+    (
+        $(#[$field_meta:meta])*
+        $field:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $path:literal),+ $(,)?
+        }
+        $(#[$input_meta:meta])*
+        $input:ident
+    ) => {
+        $(#[$field_meta])*
+        #[derive(Enum, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize, Debug)]
+        pub enum $field {
+            $($(#[$variant_meta])* $variant),+
         }
-    }
-}
-
-impl Default for WishlistOrderField {
-    fn default() -> Self {
-        Self::Id
-    }
-}
-
-/// Specifies the order of wishlists.
-#[derive(SimpleObject, InputObject)]
-pub struct WishlistOrderInput {
-    /// Order direction of wishlists.
-    pub direction: Option<OrderDirection>,
-    /// Field that wishlists should be ordered by.
-    pub field: Option<WishlistOrderField>,
-}
 
-impl Default for WishlistOrderInput {
-    fn default() -> Self {
-        Self {
-            direction: Some(Default::default()),
-            field: Some(Default::default()),
+        impl $field {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($field::$variant => $path),+
+                }
+            }
         }
-    }
-}
 
-/// Describes the fields that a foreign types can be ordered by.
-#[derive(Enum, Copy, Clone, Eq, PartialEq)]
-pub enum CommonOrderField {
-    /// Orders by "id".
-    Id,
-}
+        #[derive(SimpleObject, InputObject, Clone, Serialize, Deserialize, Debug)]
+        $(#[$input_meta])*
+        pub struct $input {
+            /// Order direction of entities.
+            pub direction: Option<OrderDirection>,
+            /// Field that entities should be ordered by.
+            pub field: Option<$field>,
+        }
 
-impl CommonOrderField {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            CommonOrderField::Id => "_id",
+        impl Default for $input {
+            fn default() -> Self {
+                Self {
+                    direction: Some(Default::default()),
+                    field: Some(Default::default()),
+                }
+            }
         }
-    }
+    };
 }
 
-impl Default for CommonOrderField {
-    fn default() -> Self {
-        Self::Id
+order_input! {
+    /// Describes the fields that a wishlist can be ordered by.
+    WishlistOrderField {
+        /// Orders by "id".
+        #[default]
+        Id => "_id",
+        /// Orders by "user_id".
+        UserId => "user._id",
+        /// Orders by "name".
+        Name => "name",
+        /// Orders by "created_at".
+        CreatedAt => "created_at",
+        /// Orders by "last_updated_at".
+        LastUpdatedAt => "last_updated_at",
+        /// Orders by "item_count".
+        ItemCount => "item_count",
     }
+    /// Specifies the order of wishlists.
+    #[graphql(name = "WishlistOrder", input_name = "WishlistOrderInput")]
+    WishlistOrderInput
 }
 
-/// Specifies the order of foreign types.
-#[derive(SimpleObject, InputObject)]
-pub struct CommonOrderInput {
-    /// Order direction of wishlists.
-    pub direction: Option<OrderDirection>,
-    /// Field that wishlists should be ordered by.
-    pub field: Option<CommonOrderField>,
-}
-
-impl Default for CommonOrderInput {
-    fn default() -> Self {
-        Self {
-            direction: Some(Default::default()),
-            field: Some(Default::default()),
-        }
+order_input! {
+    /// Describes the fields that a foreign types can be ordered by.
+    CommonOrderField {
+        /// Orders by "id".
+        #[default]
+        Id => "_id",
+        /// Orders product variants by their current catalog price, product variants without known price come last.
+        CurrentPrice => "current_price.amount",
     }
+    /// Specifies the order of foreign types.
+    CommonOrderInput
 }
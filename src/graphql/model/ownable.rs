@@ -0,0 +1,27 @@
+use async_graphql::Interface;
+
+use super::{date_time::DateTime, uuid::Uuid, wishlist::Wishlist};
+
+/// Resource owned by a user, shared with the user-owned types of the other MiSArch subgraphs.
+#[derive(Interface)]
+#[allow(clippy::duplicated_attributes)]
+#[graphql(
+    field(
+        name = "owner_id",
+        ty = "Uuid",
+        desc = "UUID of the user owning the resource."
+    ),
+    field(
+        name = "created_at",
+        ty = "&DateTime",
+        desc = "Timestamp when the resource was created."
+    ),
+    field(
+        name = "last_updated_at",
+        ty = "&DateTime",
+        desc = "Timestamp when the resource was last updated."
+    )
+)]
+pub enum Ownable {
+    Wishlist(Wishlist),
+}
@@ -0,0 +1,64 @@
+use async_graphql::{InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+use super::{date_time::DateTime, uuid::Uuid};
+
+/// Catalog and inventory metadata of a product variant, projected from events into the `product_variants` collection.
+///
+/// Fields are `None` until the catalog or the inventory published them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProductVariantMetadata {
+    /// UUID of the product variant.
+    pub _id: Uuid,
+    /// Name of the product variant.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// URL of an image of the product variant.
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Current retail price of the product variant.
+    #[serde(default)]
+    pub current_price: Option<Money>,
+    /// Timestamp when the price was last received from the catalog.
+    #[serde(default)]
+    pub price_updated_at: Option<DateTime>,
+    /// Whether the product variant is available, according to the last inventory event.
+    #[serde(default)]
+    pub available: Option<bool>,
+    /// Last drop of the price of the product variant.
+    #[serde(default)]
+    pub last_price_drop: Option<PriceDrop>,
+    /// Timestamp when the availability of the product variant last changed.
+    #[serde(default)]
+    pub availability_changed_at: Option<DateTime>,
+}
+
+/// Drop of the catalog price of a product variant.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceDrop {
+    /// Price before the drop.
+    pub previous_price: Money,
+    /// Timestamp when the dropped price was received from the catalog.
+    pub dropped_at: DateTime,
+}
+
+/// Amount of money in a currency.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, SimpleObject, InputObject)]
+#[graphql(input_name = "MoneyInput")]
+pub struct Money {
+    /// Amount in the minor unit of the currency, e.g. cents.
+    pub amount: u64,
+    /// ISO 4217 code of the currency, e.g. `EUR`.
+    pub currency: String,
+}
+
+/// Estimated value of the product variants of a wishlist, based on the prices received from the catalog.
+#[derive(Debug, Clone, PartialEq, Eq, SimpleObject)]
+pub struct EstimatedValue {
+    /// Sum of the current prices of all product variants, in the minor unit of the currency.
+    pub amount: u64,
+    /// ISO 4217 code of the currency of all prices.
+    pub currency: String,
+    /// Timestamp when the least recently received price was received, the value reflects no older prices.
+    pub last_price_sync_at: DateTime,
+}
@@ -0,0 +1,15 @@
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+
+use super::{date_time::DateTime, uuid::Uuid};
+
+/// Product variant of a wishlist which was marked as purchased, it stays in the wishlist.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
+pub struct PurchasedItem {
+    /// UUID of the purchased product variant.
+    pub product_variant_id: Uuid,
+    /// Timestamp when the product variant was purchased.
+    pub purchased_at: DateTime,
+    /// UUID of the user who marked the product variant as purchased.
+    pub marked_by_user_id: Option<Uuid>,
+}
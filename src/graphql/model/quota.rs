@@ -0,0 +1,25 @@
+use async_graphql::SimpleObject;
+
+use super::uuid::Uuid;
+
+/// Usage of the per-user wishlist limits.
+#[derive(Debug, SimpleObject)]
+pub struct WishlistQuota {
+    /// Number of wishlists of the user.
+    pub used: u64,
+    /// Maximum number of wishlists per user, `null` if unlimited.
+    pub limit: Option<u64>,
+    /// Usage of the per-wishlist item limit for each wishlist of the user.
+    pub items_used_per_list: Vec<WishlistItemQuota>,
+}
+
+/// Usage of the item limit of a wishlist.
+#[derive(Debug, SimpleObject)]
+pub struct WishlistItemQuota {
+    /// UUID of wishlist.
+    pub wishlist_id: Uuid,
+    /// Number of product variants in wishlist.
+    pub used: u64,
+    /// Maximum number of product variants per wishlist, `null` if unlimited.
+    pub limit: Option<u64>,
+}
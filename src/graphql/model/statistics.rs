@@ -0,0 +1,53 @@
+use async_graphql::{Enum, SimpleObject};
+use serde::Deserialize;
+
+use super::date_time::DateTime;
+
+/// Platform-wide statistics of the wishlist service.
+#[derive(Debug, SimpleObject)]
+pub struct WishlistServiceStatistics {
+    /// Total number of wishlists.
+    pub total_wishlists: u64,
+    /// Total number of users owning at least one wishlist.
+    pub total_users_with_wishlists: u64,
+    /// Average number of product variants per wishlist.
+    pub average_items_per_wishlist: f64,
+    /// Numbers of created wishlists per time bucket, in ascending order of time buckets.
+    pub creation_counts: Vec<WishlistCreationCount>,
+}
+
+/// Number of wishlists created in a time bucket.
+#[derive(Debug, Deserialize, SimpleObject)]
+pub struct WishlistCreationCount {
+    /// Start of time bucket.
+    #[serde(rename = "_id")]
+    pub bucket_start: DateTime,
+    /// Number of wishlists created in time bucket.
+    pub count: u64,
+}
+
+/// Time bucket size of statistics.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Default)]
+pub enum StatisticsTimeBucket {
+    /// Buckets of one hour.
+    Hour,
+    /// Buckets of one day.
+    #[default]
+    Day,
+    /// Buckets of one week.
+    Week,
+    /// Buckets of one month.
+    Month,
+}
+
+impl StatisticsTimeBucket {
+    /// Unit of the MongoDB `$dateTrunc` operator.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatisticsTimeBucket::Hour => "hour",
+            StatisticsTimeBucket::Day => "day",
+            StatisticsTimeBucket::Week => "week",
+            StatisticsTimeBucket::Month => "month",
+        }
+    }
+}
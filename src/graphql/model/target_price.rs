@@ -0,0 +1,17 @@
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+
+use super::{date_time::DateTime, product_variant_metadata::Money, uuid::Uuid};
+
+/// Price below which the user of a wishlist wants to be notified about a product variant.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, SimpleObject)]
+pub struct TargetPrice {
+    /// UUID of the product variant.
+    pub product_variant_id: Uuid,
+    /// Price the catalog price of the product variant must drop below.
+    pub price: Money,
+    /// Timestamp when the user was notified that the price dropped below the target price.
+    ///
+    /// Reset when the price rises to the target price again, so the next drop is notified again.
+    pub notified_at: Option<DateTime>,
+}
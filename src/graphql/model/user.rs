@@ -1,17 +1,24 @@
-use async_graphql::{ComplexObject, Context, Error, Result, SimpleObject};
-use bson::{doc, Document, Uuid};
+use async_graphql::{ComplexObject, Context, Error, InputObject, Result, SimpleObject};
+use bson::{doc, Document};
+use futures::TryStreamExt;
 use mongodb::{options::FindOptions, Collection, Database};
 use mongodb_cursor_pagination::{error::CursorError, FindResult, PaginatedCursor};
 use serde::{Deserialize, Serialize};
 
 use crate::authorization::authorize_user;
+use crate::graphql::pagination::{self, alternative_arguments, page_size, LastUpdatedCursor};
+use crate::settings::Settings;
+use crate::tenant::tenant_id;
 
 use super::{
     connection::{
         base_connection::{BaseConnection, FindResultWrapper},
         wishlist_connection::WishlistConnection,
     },
-    order_types::WishlistOrderInput,
+    filter_types::WishlistFilterInput,
+    order_types::{OrderDirection, WishlistOrderField, WishlistOrderInput},
+    user_preferences::{find_user_preferences, UserPreferences, USER_PREFERENCES_COLLECTION},
+    uuid::Uuid,
     wishlist::Wishlist,
 };
 
@@ -23,43 +30,226 @@ pub struct User {
     pub _id: Uuid,
 }
 
+/// Reference to a user in federation keys of entities owned by the user, e.g. `user { id } name` of wishlists.
+#[derive(Debug, Clone, Copy, InputObject)]
+pub struct UserKeyInput {
+    /// UUID of the user.
+    pub id: Uuid,
+}
+
 #[ComplexObject]
 impl User {
     /// Retrieves wishlists of user.
+    #[allow(clippy::too_many_arguments)]
     async fn wishlists<'a>(
         &self,
         ctx: &Context<'a>,
-        #[graphql(desc = "Describes that the `first` N wishlists should be retrieved.")]
+        #[graphql(
+            desc = "Describes that the `first` N wishlists should be retrieved. Defaults to the default page size, must not exceed the maximum page size."
+        )]
         first: Option<u32>,
-        #[graphql(desc = "Describes how many wishlists should be skipped at the beginning.")]
+        #[graphql(
+            desc = "Describes how many wishlists should be skipped at the beginning. Must not exceed the maximum offset."
+        )]
         skip: Option<u64>,
+        #[graphql(
+            desc = "Describes how many wishlists should be skipped at the beginning, alternative to `skip`. Must not exceed the maximum offset."
+        )]
+        offset: Option<u64>,
+        #[graphql(
+            desc = "Describes how many wishlists should be retrieved, alternative to `first`. Defaults to the default page size, must not exceed the maximum page size."
+        )]
+        limit: Option<u32>,
         #[graphql(desc = "Specifies the order in which wishlists are retrieved.")] order_by: Option<
             WishlistOrderInput,
         >,
+        #[graphql(desc = "Specifies which wishlists are retrieved.")] filter: Option<
+            WishlistFilterInput,
+        >,
+        #[graphql(
+            desc = "Retrieves the wishlists following this cursor in descending order of last update, see `endCursor`. Can not be combined with `orderBy`, `skip` or `offset`."
+        )]
+        updated_before: Option<String>,
+        #[graphql(
+            desc = "Describes that the `last` N wishlists in descending order of last update should be retrieved, or the N wishlists preceding `before`. Can not be combined with `first`, `limit`, `updatedBefore`, `orderBy`, `skip` or `offset`."
+        )]
+        last: Option<u32>,
+        #[graphql(
+            desc = "Retrieves the wishlists preceding this cursor in descending order of last update, see `startCursor`. Can not be combined with `first`, `limit`, `updatedBefore`, `orderBy`, `skip` or `offset`."
+        )]
+        before: Option<String>,
     ) -> Result<WishlistConnection> {
-        authorize_user(&ctx, Some(self._id))?;
+        authorize_user(ctx, Some(self._id))?;
+        let settings = ctx.data::<Settings>()?;
         let db_client = ctx.data::<Database>()?;
         let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        let wishlist_order = order_by.unwrap_or_default();
-        let sorting_doc = doc! {wishlist_order.field.unwrap_or_default().as_str(): i32::from(wishlist_order.direction.unwrap_or_default())};
+        let mut filter_doc = tenant_id(ctx)?.scope(doc! {"user._id": self._id});
+        filter_doc.extend(filter.unwrap_or_default().to_document());
+        if last.is_some() || before.is_some() {
+            if first.is_some()
+                || limit.is_some()
+                || updated_before.is_some()
+                || order_by.is_some()
+                || skip.is_some()
+                || offset.is_some()
+            {
+                return Err(Error::new(
+                    "Arguments `last` and `before` can not be combined with `first`, `limit`, `updatedBefore`, `orderBy`, `skip` or `offset`.",
+                ));
+            }
+            let definitely_last = page_size(settings, last.map(u64::from))?;
+            let cursor = match before {
+                Some(before) => Some(LastUpdatedCursor::parse(&before)?),
+                None => None,
+            };
+            return find_wishlists_before_cursor(&collection, filter_doc, cursor, definitely_last)
+                .await;
+        }
+        let definitely_first = page_size(
+            settings,
+            alternative_arguments("first", first, "limit", limit)?.map(u64::from),
+        )?;
+        let definitely_skip = pagination::offset(
+            settings,
+            alternative_arguments("skip", skip, "offset", offset)?,
+        )?;
+        if let Some(updated_before) = updated_before {
+            if order_by.is_some() || definitely_skip > 0 {
+                return Err(Error::new(
+                    "Argument `updatedBefore` can not be combined with `orderBy`, `skip` or `offset`.",
+                ));
+            }
+            let cursor = LastUpdatedCursor::parse(&updated_before)?;
+            return find_wishlists_after_cursor(&collection, filter_doc, cursor, definitely_first)
+                .await;
+        }
+        let wishlist_order = match order_by {
+            Some(order_by) => order_by,
+            None => {
+                let preferences_collection: Collection<UserPreferences> =
+                    db_client.collection::<UserPreferences>(USER_PREFERENCES_COLLECTION);
+                find_user_preferences(&preferences_collection, tenant_id(ctx)?, self._id)
+                    .await?
+                    .default_wishlist_order
+                    .unwrap_or_default()
+            }
+        };
+        let order_field = wishlist_order.field.unwrap_or_default();
+        let order_direction = wishlist_order.direction.unwrap_or_default();
+        let keyset_order = order_field == WishlistOrderField::LastUpdatedAt
+            && order_direction == OrderDirection::Desc;
+        let sorting_doc = match keyset_order {
+            true => LastUpdatedCursor::sorting_document(),
+            false => doc! {order_field.as_str(): i32::from(order_direction)},
+        };
         let find_options = FindOptions::builder()
-            .skip(skip)
-            .limit(first.map(|definitely_first| i64::from(definitely_first)))
+            .skip(definitely_skip)
+            .limit(definitely_first as i64)
             .sort(sorting_doc)
             .build();
         let document_collection = collection.clone_with_type::<Document>();
-        let filter = doc! {"user._id": self._id};
         let maybe_find_results: Result<FindResult<Wishlist>, CursorError> =
             PaginatedCursor::new(Some(find_options.clone()), None, None)
-                .find(&document_collection, Some(&filter))
+                .find(&document_collection, Some(&filter_doc))
                 .await;
         match maybe_find_results {
             Ok(find_results) => {
                 let find_result_wrapper = FindResultWrapper(find_results);
                 let connection = Into::<BaseConnection<Wishlist>>::into(find_result_wrapper);
-                Ok(Into::<WishlistConnection>::into(connection))
+                match keyset_order {
+                    true => Ok(WishlistConnection::from_keyset_page(connection)),
+                    false => Ok(Into::<WishlistConnection>::into(connection)),
+                }
             }
-            Err(_) => return Err(Error::new("Retrieving wishlists failed in MongoDB.")),
+            Err(_) => Err(Error::new("Retrieving wishlists failed in MongoDB.")),
         }
     }
 }
+
+/// Retrieves the wishlists following a keyset pagination cursor.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `filter_doc` - Filter of the wishlists of the connection, without cursor.
+/// * `cursor` - Keyset pagination cursor.
+/// * `first` - Number of wishlists to retrieve.
+async fn find_wishlists_after_cursor(
+    collection: &Collection<Wishlist>,
+    filter_doc: Document,
+    cursor: LastUpdatedCursor,
+    first: u64,
+) -> Result<WishlistConnection> {
+    let message = "Retrieving wishlists failed in MongoDB.";
+    let total_count = collection
+        .count_documents(filter_doc.clone(), None)
+        .await
+        .map_err(|_| Error::new(message))?;
+    let mut cursor_filter_doc = filter_doc;
+    cursor_filter_doc.extend(cursor.to_document());
+    let find_options = FindOptions::builder()
+        .limit((first + 1) as i64)
+        .sort(LastUpdatedCursor::sorting_document())
+        .build();
+    let mut nodes: Vec<Wishlist> = match collection.find(cursor_filter_doc, find_options).await {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let has_next_page = nodes.len() as u64 > first;
+    nodes.truncate(first as usize);
+    let connection = BaseConnection {
+        nodes,
+        has_next_page,
+        has_previous_page: true,
+        total_count,
+    };
+    Ok(WishlistConnection::from_keyset_page(connection))
+}
+
+/// Retrieves the wishlists preceding a keyset pagination cursor, or the last wishlists without cursor.
+///
+/// The wishlists are queried in reverse order, starting at the cursor, and returned in descending order of last update.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `filter_doc` - Filter of the wishlists of the connection, without cursor.
+/// * `cursor` - Keyset pagination cursor, the end of the wishlists if `None`.
+/// * `last` - Number of wishlists to retrieve.
+async fn find_wishlists_before_cursor(
+    collection: &Collection<Wishlist>,
+    filter_doc: Document,
+    cursor: Option<LastUpdatedCursor>,
+    last: u64,
+) -> Result<WishlistConnection> {
+    let message = "Retrieving wishlists failed in MongoDB.";
+    let total_count = collection
+        .count_documents(filter_doc.clone(), None)
+        .await
+        .map_err(|_| Error::new(message))?;
+    let has_next_page = cursor.is_some();
+    let mut cursor_filter_doc = filter_doc;
+    if let Some(cursor) = cursor {
+        cursor_filter_doc.extend(cursor.to_reverse_document());
+    }
+    let find_options = FindOptions::builder()
+        .limit((last + 1) as i64)
+        .sort(LastUpdatedCursor::reverse_sorting_document())
+        .build();
+    let mut nodes: Vec<Wishlist> = match collection.find(cursor_filter_doc, find_options).await {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let has_previous_page = nodes.len() as u64 > last;
+    nodes.truncate(last as usize);
+    nodes.reverse();
+    let connection = BaseConnection {
+        nodes,
+        has_next_page,
+        has_previous_page,
+        total_count,
+    };
+    Ok(WishlistConnection::from_keyset_page(connection))
+}
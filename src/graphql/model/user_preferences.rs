@@ -0,0 +1,64 @@
+use async_graphql::{ComplexObject, Context, Error, Result, SimpleObject};
+use bson::doc;
+use mongodb::{Collection, Database};
+use serde::{Deserialize, Serialize};
+
+use crate::jobs::wishlist_digest::{
+    is_digest_enabled, DigestSubscription, DIGEST_SUBSCRIPTION_COLLECTION,
+};
+use crate::tenant::TenantId;
+
+use super::{order_types::WishlistOrderInput, uuid::Uuid};
+
+/// Name of the MongoDB collection of user preferences.
+pub const USER_PREFERENCES_COLLECTION: &str = "user_preferences";
+
+/// Preferences of a user, used as defaults by wishlist queries.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct UserPreferences {
+    /// Identifier of the tenant and UUID of the user, separated by `:`.
+    #[graphql(skip)]
+    pub _id: String,
+    /// Identifier of the tenant of the user.
+    #[graphql(skip)]
+    pub tenant_id: String,
+    /// UUID of the user.
+    pub user_id: Uuid,
+    /// Order of wishlists if no order is requested.
+    pub default_wishlist_order: Option<WishlistOrderInput>,
+}
+
+#[ComplexObject]
+impl UserPreferences {
+    /// Whether the periodic digest of the wishlist activity is enabled.
+    async fn digest_enabled<'a>(&self, ctx: &Context<'a>) -> Result<bool> {
+        let collection: Collection<DigestSubscription> = ctx
+            .data::<Database>()?
+            .collection::<DigestSubscription>(DIGEST_SUBSCRIPTION_COLLECTION);
+        is_digest_enabled(&collection, &TenantId(self.tenant_id.clone()), self.user_id).await
+    }
+}
+
+/// Retrieves the preferences of a user, or the default preferences if the user has not set any.
+///
+/// * `collection` - MongoDB collection of user preferences.
+/// * `tenant_id` - Tenant of the user.
+/// * `user_id` - UUID of the user.
+pub async fn find_user_preferences(
+    collection: &Collection<UserPreferences>,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+) -> Result<UserPreferences> {
+    let id = format!("{}:{}", tenant_id.0, user_id);
+    match collection.find_one(doc! {"_id": &id}, None).await {
+        Ok(Some(preferences)) => Ok(preferences),
+        Ok(None) => Ok(UserPreferences {
+            _id: id,
+            tenant_id: tenant_id.0.clone(),
+            user_id,
+            default_wishlist_order: None,
+        }),
+        Err(_) => Err(Error::new("Retrieving user preferences failed in MongoDB.")),
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use bson::Bson;
+use serde::{Deserialize, Serialize};
+
+/// UUID, represented as string in GraphQL and as BSON binary in MongoDB.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(transparent)]
+pub struct Uuid(pub bson::Uuid);
+
+impl Uuid {
+    /// Generates a new random UUID.
+    pub fn new() -> Self {
+        Self(bson::Uuid::new())
+    }
+
+    /// Parses a UUID from its hyphenated string representation.
+    ///
+    /// * `s` - String to parse.
+    pub fn parse_str(s: &str) -> Result<Self, bson::uuid::Error> {
+        bson::Uuid::parse_str(s).map(Self)
+    }
+}
+
+/// A UUID string, e.g. `123e4567-e89b-12d3-a456-426614174000`.
+///
+/// Malformed UUIDs are rejected during validation, naming the argument they were passed to.
+#[Scalar(
+    name = "UUID",
+    specified_by_url = "https://datatracker.ietf.org/doc/html/rfc4122"
+)]
+impl ScalarType for Uuid {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::String(s) => Uuid::parse_str(s)
+                .map_err(|_| InputValueError::custom(format!("`{}` is not a valid UUID.", s))),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn is_valid(value: &Value) -> bool {
+        matches!(value, Value::String(s) if Uuid::parse_str(s).is_ok())
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<bson::Uuid> for Uuid {
+    fn from(value: bson::Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Uuid> for Bson {
+    fn from(value: Uuid) -> Self {
+        Bson::from(value.0)
+    }
+}
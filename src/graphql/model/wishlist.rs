@@ -1,14 +1,29 @@
 use std::{cmp::Ordering, collections::HashSet};
 
-use async_graphql::{ComplexObject, Result, SimpleObject};
-use bson::datetime::DateTime;
-use bson::Uuid;
+use super::uuid::Uuid;
+use async_graphql::{dataloader::DataLoader, ComplexObject, Context, Result, SimpleObject};
+use bson::{doc, Document};
+use mongodb::Database;
 use serde::{Deserialize, Serialize};
 
+use crate::audit::{find_wishlist_activity, AuditEntry};
+use crate::graphql::data_loaders::ObjectLoader;
+use crate::graphql::pagination::{page_size, ActivityCursor};
+use crate::settings::Settings;
+use crate::tenant::TenantId;
+
 use super::{
-    connection::product_variant_connection::ProductVariantConnection,
+    connection::{
+        product_variant_connection::ProductVariantConnection,
+        wishlist_activity_connection::WishlistActivityConnection,
+    },
+    date_time::DateTime,
+    filter_types::ProductVariantFilterInput,
     foreign_types::ProductVariant,
-    order_types::{CommonOrderInput, OrderDirection},
+    order_types::{CommonOrderField, CommonOrderInput, OrderDirection},
+    product_variant_metadata::{EstimatedValue, ProductVariantMetadata},
+    purchased_item::PurchasedItem,
+    target_price::TargetPrice,
     user::User,
 };
 
@@ -22,20 +37,61 @@ pub struct Wishlist {
     pub user: User,
     /// Name of wishlist.
     pub name: String,
+    /// Description of wishlist.
+    pub description: Option<String>,
+    /// Date of the occasion the wishlist is intended for, e.g. a birthday.
+    pub occasion_date: Option<DateTime>,
+    /// URL of the cover image of wishlist.
+    pub cover_image_url: Option<String>,
+    /// Icon of wishlist, a single emoji.
+    pub icon: Option<String>,
+    /// Theme color of wishlist, a lowercase hex color of the form `#rgb` or `#rrggbb`.
+    pub color: Option<String>,
+    /// Timestamp after which wishlist is archived.
+    pub expires_at: Option<DateTime>,
+    /// Timestamp when wishlist was archived after its expiration.
+    pub archived_at: Option<DateTime>,
     /// Timestamp when wishlist was created.
     pub created_at: DateTime,
     /// Timestamp when wishlist was last updated.
     pub last_updated_at: DateTime,
+    /// Number of product variants in wishlist.
+    #[serde(default)]
+    pub item_count: u64,
+    /// Whether the name or description of wishlist was flagged for review by the content filters.
+    #[serde(default)]
+    pub content_flagged: bool,
+    /// Whether wishlist is suspended, as its user is disabled. Suspended wishlists can only be modified by admins.
+    #[serde(default)]
+    pub suspended: bool,
     #[graphql(skip)]
     pub internal_product_variants: HashSet<ProductVariant>,
+    /// Product variants of wishlist which were marked as purchased.
+    #[serde(default)]
+    pub purchased_items: Vec<PurchasedItem>,
+    /// Target prices of product variants of wishlist, the user is notified when a price drops below its target price.
+    #[serde(default)]
+    pub target_prices: Vec<TargetPrice>,
+    /// Identifier of the tenant owning wishlist.
+    #[graphql(skip)]
+    #[serde(default)]
+    pub tenant_id: String,
 }
 
 #[ComplexObject]
 impl Wishlist {
+    /// UUID of the user owning wishlist, as part of the `Ownable` interface.
+    pub async fn owner_id(&self) -> Uuid {
+        self.user._id
+    }
+
     /// Retrieves product variants.
     async fn product_variants(
         &self,
-        #[graphql(desc = "Describes that the `first` N product variants should be retrieved.")]
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "Describes that the `first` N product variants should be retrieved. Defaults to the default page size, must not exceed the maximum page size."
+        )]
         first: Option<usize>,
         #[graphql(
             desc = "Describes how many product variants should be skipped at the beginning."
@@ -44,13 +100,31 @@ impl Wishlist {
         #[graphql(desc = "Specifies the order in which product variants are retrieved.")] order_by: Option<
             CommonOrderInput,
         >,
+        #[graphql(desc = "Specifies which product variants are retrieved.")] filter: Option<
+            ProductVariantFilterInput,
+        >,
     ) -> Result<ProductVariantConnection> {
         let mut product_variants: Vec<ProductVariant> =
             self.internal_product_variants.clone().into_iter().collect();
-        sort_product_variants(&mut product_variants, order_by);
+        let filter = filter.unwrap_or_default();
+        if filter.only_available == Some(true) {
+            retain_available_product_variants(ctx, &mut product_variants).await?;
+        }
+        if let Some(purchased) = filter.purchased {
+            product_variants
+                .retain(|product_variant| self.is_purchased(product_variant._id) == purchased);
+        }
+        let order_by = order_by.unwrap_or_default();
+        let order_field = order_by.field.unwrap_or_default();
+        let order_direction = order_by.direction.unwrap_or_default();
+        sort_product_variants(&mut product_variants, Some(order_by));
+        if order_field == CommonOrderField::CurrentPrice {
+            sort_product_variants_by_price(ctx, &mut product_variants, order_direction).await?;
+        }
         let total_count = product_variants.len();
         let definitely_skip = skip.unwrap_or(0);
-        let definitely_first = first.unwrap_or(usize::MAX);
+        let definitely_first =
+            page_size(ctx.data::<Settings>()?, first.map(|first| first as u64))? as usize;
         let product_variants_part: Vec<ProductVariant> = product_variants
             .into_iter()
             .skip(definitely_skip)
@@ -60,9 +134,119 @@ impl Wishlist {
         Ok(ProductVariantConnection {
             nodes: product_variants_part,
             has_next_page,
+            has_previous_page: definitely_skip > 0,
             total_count: total_count as u64,
         })
     }
+
+    /// Estimated value of the product variants of wishlist, the sum of their current catalog prices.
+    ///
+    /// `null` if the price of a product variant is unknown or the prices have different currencies, and for empty wishlists, as their value has no currency.
+    async fn estimated_total_value(&self, ctx: &Context<'_>) -> Result<Option<EstimatedValue>> {
+        let ids: Vec<Uuid> = self
+            .internal_product_variants
+            .iter()
+            .map(|product_variant| product_variant._id)
+            .collect();
+        let metadata = ctx
+            .data::<DataLoader<ObjectLoader<ProductVariantMetadata>>>()?
+            .load_many(ids.iter().copied())
+            .await?;
+        let mut estimated_value: Option<EstimatedValue> = None;
+        for id in &ids {
+            let (current_price, price_updated_at) = match metadata.get(id) {
+                Some(ProductVariantMetadata {
+                    current_price: Some(current_price),
+                    price_updated_at: Some(price_updated_at),
+                    ..
+                }) => (current_price, *price_updated_at),
+                _ => return Ok(None),
+            };
+            estimated_value = match estimated_value {
+                None => Some(EstimatedValue {
+                    amount: current_price.amount,
+                    currency: current_price.currency.clone(),
+                    last_price_sync_at: price_updated_at,
+                }),
+                Some(estimated_value) if estimated_value.currency == current_price.currency => {
+                    Some(EstimatedValue {
+                        amount: estimated_value.amount + current_price.amount,
+                        last_price_sync_at: estimated_value
+                            .last_price_sync_at
+                            .min(price_updated_at),
+                        ..estimated_value
+                    })
+                }
+                Some(_) => return Ok(None),
+            };
+        }
+        Ok(estimated_value)
+    }
+
+    /// Recent changes of wishlist derived from its audit history, most recent first, e.g. for showing what changed since the last visit.
+    async fn activity(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "Describes that the `first` N changes should be retrieved. Defaults to the default page size, must not exceed the maximum page size."
+        )]
+        first: Option<u64>,
+        #[graphql(desc = "Cursor of the change after which changes are retrieved.")] after: Option<
+            String,
+        >,
+    ) -> Result<WishlistActivityConnection> {
+        let first = page_size(ctx.data::<Settings>()?, first)? as usize;
+        let after = after.as_deref().map(ActivityCursor::parse).transpose()?;
+        let has_previous_page = after.is_some();
+        let collection = ctx
+            .data::<Database>()?
+            .collection::<AuditEntry>("audit_entries");
+        let (nodes, has_next_page) = find_wishlist_activity(
+            &collection,
+            &TenantId(self.tenant_id.clone()),
+            self._id,
+            first,
+            after,
+        )
+        .await?;
+        Ok(WishlistActivityConnection {
+            end_cursor: nodes.last().map(|node| node.cursor.clone()),
+            nodes,
+            has_next_page,
+            has_previous_page,
+        })
+    }
+}
+
+impl Wishlist {
+    /// Returns whether a product variant of wishlist was marked as purchased.
+    ///
+    /// * `product_variant_id` - UUID of product variant.
+    pub fn is_purchased(&self, product_variant_id: Uuid) -> bool {
+        self.purchased_items
+            .iter()
+            .any(|purchased_item| purchased_item.product_variant_id == product_variant_id)
+    }
+
+    /// Returns the pipeline stage deriving the fields depending on the product variants of a wishlist.
+    ///
+    /// Sets the item count and removes the purchased items and target prices of product variants the wishlist no longer contains.
+    /// Must follow every pipeline update which removes product variants.
+    pub fn product_variant_dependent_fields_stage() -> Document {
+        let contained =
+            doc! {"$in": ["$$this.product_variant_id", "$internal_product_variants._id"]};
+        doc! {"$set": {
+            "item_count": {"$size": "$internal_product_variants"},
+            "purchased_items": {"$filter": {
+                "input": {"$ifNull": ["$purchased_items", []]},
+                "cond": &contained,
+            }},
+            "target_prices": {"$filter": {
+                "input": {"$ifNull": ["$target_prices", []]},
+                "cond": &contained,
+            }},
+        }}
+    }
 }
 
 /// Sorts product variants according to base order.
@@ -70,7 +254,7 @@ impl Wishlist {
 /// * `product_variants` - Product variants to sort.
 /// * `order_by` - Specifies order of sorted result.
 fn sort_product_variants(
-    product_variants: &mut Vec<ProductVariant>,
+    product_variants: &mut [ProductVariant],
     order_by: Option<CommonOrderInput>,
 ) {
     let comparator: fn(&ProductVariant, &ProductVariant) -> bool =
@@ -92,6 +276,75 @@ fn sort_product_variants(
     });
 }
 
+/// Removes product variants which are unavailable according to the inventory, product variants without known availability are kept.
+///
+/// * `ctx` - GraphQL context containing the data loader of product variant metadata.
+/// * `product_variants` - Product variants to filter.
+async fn retain_available_product_variants(
+    ctx: &Context<'_>,
+    product_variants: &mut Vec<ProductVariant>,
+) -> Result<()> {
+    let metadata = ctx
+        .data::<DataLoader<ObjectLoader<ProductVariantMetadata>>>()?
+        .load_many(
+            product_variants
+                .iter()
+                .map(|product_variant| product_variant._id),
+        )
+        .await?;
+    product_variants.retain(|product_variant| {
+        metadata
+            .get(&product_variant._id)
+            .and_then(|metadata| metadata.available)
+            != Some(false)
+    });
+    Ok(())
+}
+
+/// Sorts product variants by their current catalog price, looked up in the product variant projection.
+///
+/// Product variants without known price come last, product variants of equal price keep their order.
+/// Prices are compared by amount, regardless of their currency.
+///
+/// * `ctx` - GraphQL context containing the data loader of product variant metadata.
+/// * `product_variants` - Product variants to sort.
+/// * `direction` - Order direction of the prices.
+async fn sort_product_variants_by_price(
+    ctx: &Context<'_>,
+    product_variants: &mut [ProductVariant],
+    direction: OrderDirection,
+) -> Result<()> {
+    let metadata = ctx
+        .data::<DataLoader<ObjectLoader<ProductVariantMetadata>>>()?
+        .load_many(
+            product_variants
+                .iter()
+                .map(|product_variant| product_variant._id),
+        )
+        .await?;
+    let price_of = |product_variant: &ProductVariant| {
+        metadata
+            .get(&product_variant._id)
+            .and_then(|metadata| metadata.current_price.as_ref())
+            .map(|current_price| current_price.amount)
+    };
+    product_variants.sort_by(|first_product_variant, second_product_variant| {
+        match (
+            price_of(first_product_variant),
+            price_of(second_product_variant),
+        ) {
+            (Some(first_price), Some(second_price)) => match direction {
+                OrderDirection::Asc => first_price.cmp(&second_price),
+                OrderDirection::Desc => second_price.cmp(&first_price),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+    Ok(())
+}
+
 impl From<Wishlist> for Uuid {
     fn from(value: Wishlist) -> Self {
         value._id
@@ -0,0 +1,126 @@
+use async_graphql::{Enum, SimpleObject};
+
+use crate::audit::{AuditAction, AuditEntry};
+use crate::graphql::pagination::ActivityCursor;
+
+use super::{date_time::DateTime, uuid::Uuid};
+
+/// Kind of a change of a wishlist in its activity feed.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum WishlistActivityKind {
+    /// Wishlist was created.
+    Created,
+    /// A product variant was added to the wishlist.
+    ItemAdded,
+    /// A product variant was removed from the wishlist.
+    ItemRemoved,
+    /// Wishlist was renamed.
+    Renamed,
+    /// A product variant of the wishlist was claimed by marking it as purchased.
+    Claimed,
+    /// Ownership of the wishlist was moved to another user.
+    Reassigned,
+}
+
+/// Change of a wishlist, derived from its audit history.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WishlistActivity {
+    /// Cursor of the change, which can be passed as `after` to retrieve the following changes.
+    pub cursor: String,
+    /// Kind of the change.
+    pub kind: WishlistActivityKind,
+    /// Timestamp of the change.
+    pub created_at: DateTime,
+    /// UUID of the user who performed the change, if performed by a user.
+    pub actor_user_id: Option<Uuid>,
+    /// UUID of the added, removed or claimed product variant.
+    pub product_variant_id: Option<Uuid>,
+    /// Name of the wishlist before it was renamed.
+    pub previous_name: Option<String>,
+    /// Name of the wishlist after it was created or renamed.
+    pub name: Option<String>,
+    /// UUID of the user the wishlist was reassigned to.
+    pub reassigned_to_user_id: Option<Uuid>,
+}
+
+impl WishlistActivity {
+    /// Derives the changes of an audit entry, in the order they are listed in the activity feed.
+    ///
+    /// Changes of entries with snapshot are the differences to the previous snapshot,
+    /// an entry with snapshot but without previous snapshot is the creation of the wishlist.
+    ///
+    /// * `audit_entry` - Audit entry to derive the changes of.
+    /// * `previous_audit_entry` - Previous audit entry with snapshot of the same wishlist.
+    pub fn from_audit_entry(
+        audit_entry: &AuditEntry,
+        previous_audit_entry: Option<&AuditEntry>,
+    ) -> Vec<Self> {
+        let created_at = DateTime(audit_entry.created_at);
+        let activity = |kind| Self {
+            cursor: String::new(),
+            kind,
+            created_at,
+            actor_user_id: audit_entry.actor_user_id,
+            product_variant_id: None,
+            previous_name: None,
+            name: None,
+            reassigned_to_user_id: None,
+        };
+        let mut activities = vec![];
+        match &audit_entry.action {
+            AuditAction::ItemPurchased { product_variant_id } => activities.push(Self {
+                product_variant_id: Some(*product_variant_id),
+                ..activity(WishlistActivityKind::Claimed)
+            }),
+            AuditAction::Reassigned { to_user_id, .. } => activities.push(Self {
+                reassigned_to_user_id: Some(*to_user_id),
+                ..activity(WishlistActivityKind::Reassigned)
+            }),
+            _ => {}
+        }
+        if let Some(snapshot) = &audit_entry.snapshot {
+            match previous_audit_entry
+                .and_then(|previous_audit_entry| previous_audit_entry.snapshot.as_ref())
+            {
+                Some(previous_snapshot) => {
+                    if previous_snapshot.name != snapshot.name {
+                        activities.push(Self {
+                            previous_name: Some(previous_snapshot.name.clone()),
+                            name: Some(snapshot.name.clone()),
+                            ..activity(WishlistActivityKind::Renamed)
+                        });
+                    }
+                    for id in &snapshot.product_variant_ids {
+                        if !previous_snapshot.product_variant_ids.contains(id) {
+                            activities.push(Self {
+                                product_variant_id: Some(*id),
+                                ..activity(WishlistActivityKind::ItemAdded)
+                            });
+                        }
+                    }
+                    for id in &previous_snapshot.product_variant_ids {
+                        if !snapshot.product_variant_ids.contains(id) {
+                            activities.push(Self {
+                                product_variant_id: Some(*id),
+                                ..activity(WishlistActivityKind::ItemRemoved)
+                            });
+                        }
+                    }
+                }
+                None => activities.push(Self {
+                    name: Some(snapshot.name.clone()),
+                    ..activity(WishlistActivityKind::Created)
+                }),
+            }
+        }
+        for (index, activity) in activities.iter_mut().enumerate() {
+            activity.cursor = ActivityCursor {
+                created_at,
+                audit_entry_id: audit_entry._id,
+                index: index as u64,
+            }
+            .to_string();
+        }
+        activities
+    }
+}
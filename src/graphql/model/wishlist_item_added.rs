@@ -0,0 +1,14 @@
+use async_graphql::SimpleObject;
+
+use super::uuid::Uuid;
+
+/// Addition of a product variant to a wishlist.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WishlistItemAdded {
+    /// UUID of the user owning the wishlist.
+    pub user_id: Uuid,
+    /// UUID of the wishlist the product variant was added to.
+    pub wishlist_id: Uuid,
+    /// UUID of the added product variant.
+    pub product_variant_id: Uuid,
+}
@@ -0,0 +1,12 @@
+use async_graphql::SimpleObject;
+
+use super::wishlist::Wishlist;
+
+/// Membership of a product variant in the wishlists of a user.
+#[derive(Debug, SimpleObject)]
+pub struct WishlistMembership {
+    /// Whether the product variant is in at least one wishlist of the user.
+    pub is_wishlisted: bool,
+    /// Wishlists of the user containing the product variant.
+    pub wishlists: Vec<Wishlist>,
+}
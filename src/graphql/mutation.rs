@@ -1,22 +1,92 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+};
 
-use async_graphql::{Context, Error, Object, Result};
-use bson::Bson;
-use bson::Uuid;
-use futures::TryStreamExt;
+use crate::graphql::model::uuid::Uuid;
+use async_graphql::{Context, Error, ErrorExtensions, MaybeUndefined, Object, Result, UploadValue};
+use bson::{Bson, Document};
+use futures::{future::join_all, TryStreamExt};
+use log::warn;
 use mongodb::{
-    bson::{doc, DateTime},
-    Collection, Database,
+    bson::doc,
+    error::{TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT},
+    options::{FindOneOptions, ReadPreference, ReplaceOptions, SelectionCriteria, UpdateOptions},
+    Client, ClientSession, Collection, Database,
 };
 
-use crate::authorization::authorize_user;
+use crate::api_key::{generate_api_key, hash_api_key, ApiKey, ApiKeyScope};
+use crate::audit::{
+    find_wishlist_version, record_audit_entries, record_audit_entry, record_wishlist_snapshots,
+    AuditAction, AuditEntry, WishlistSnapshot,
+};
+use crate::authorization::{
+    authorize_admin, authorize_user, authorized_user_id, is_admin, AuthorizedUserHeader,
+};
+use crate::cache::{catalog_product_variant_key, wishlist_key, StateCache};
+use crate::calendar::{CalendarToken, CALENDAR_TOKEN_COLLECTION};
+use crate::content_filter::ContentPolicy;
+use crate::dapr_client::DaprClient;
+use crate::event::failed_events::reprocess_failed_event;
+use crate::event::http_event_service::HttpEventServiceState;
+use crate::event::outgoing_events::{
+    AddWishlistToCartEventData, ShoppingCartItemEventData, WishlistBulkUpdatedEventData,
+    WishlistItemPurchasedEventData, WishlistMilestoneReachedEventData, ADD_WISHLIST_TO_CART_TOPIC,
+    WISHLIST_BULK_UPDATED_TOPIC, WISHLIST_ITEM_PURCHASED_TOPIC, WISHLIST_MILESTONE_REACHED_TOPIC,
+};
+use crate::jobs::projection_reconciliation::{fetch_foreign_ids, reconcile_foreign_projections};
+use crate::jobs::wishlist_digest::{
+    set_digest_enabled, DigestSubscription, DIGEST_SUBSCRIPTION_COLLECTION,
+};
+use crate::service_invocation::product_variant_exists_in_catalog;
+use crate::settings::{Settings, ValidationStrictness};
+use crate::tenant::{tenant_id, DatabaseRouter, TenantId};
 
+use super::creation_throttle::{throttle_creation, ThrottledCreation};
+use super::csv_import::parse_wishlist_csv;
+use super::field_validation::{
+    validate_color, validate_cover_image_url, validate_expires_at, validate_icon,
+};
+use super::idempotency::{is_duplicate_key_error, with_idempotency};
+use super::item_upload::{parse_product_variant_id_lines, UploadLine};
+use super::model::date_time::DateTime;
+use super::model::filter_types::BulkWishlistFilterInput;
 use super::model::foreign_types::ProductVariant;
+use super::model::product_variant_metadata::Money;
+use super::model::purchased_item::PurchasedItem;
+use super::model::target_price::TargetPrice;
 use super::model::user::User;
+use super::model::user_preferences::{
+    find_user_preferences, UserPreferences, USER_PREFERENCES_COLLECTION,
+};
 use super::model::wishlist::Wishlist;
-use super::mutation_input_structs::CreateWishlistInput;
-use super::mutation_input_structs::UpdateWishlistInput;
-use super::query::query_object;
+use super::mutation_input_structs::{
+    AddItemsFromUploadInput, CreateWishlistInput, ImportWishlistFromCsvInput,
+};
+use super::mutation_input_structs::{
+    BulkWishlistPatchInput, ItemOperationType, UpdateUserPreferencesInput, UpdateWishlistInput,
+    WishlistVersionSelector,
+};
+use super::mutation_payload_structs::{
+    AddItemsFromUploadPayload, BulkUpdateWishlistsPayload, CleanupOrphanedProductVariantsPayload,
+    CsvRowError, DeleteWishlistPayload, ImportWishlistFromCsvPayload, ReassignWishlistsPayload,
+    ReconcileForeignProjectionsPayload, ReprocessFailedEventsPayload, SplitWishlistPayload,
+    UploadLineError,
+};
+use super::mutation_validation::{MutationValidators, WishlistMutation};
+use super::query::{query_object, query_object_from_primary};
+
+/// Number of lines of an uploaded file of product variants which are validated at once.
+const UPLOAD_VALIDATION_BATCH_SIZE: usize = 100;
+
+/// MongoDB collection of the per-user documents serializing wishlist creations, see `is_within_wishlist_quota`.
+const WISHLIST_QUOTA_LOCK_COLLECTION: &str = "wishlist_quota_locks";
+
+/// Number of attempts of a wishlist creation or split whose transaction conflicts with a concurrent write.
+const MAX_QUOTA_TRANSACTION_ATTEMPTS: usize = 3;
+
+/// Number of wishlists patched per batch of a bulk update.
+const BULK_UPDATE_BATCH_SIZE: usize = 500;
 
 /// Describes GraphQL wishlist mutations.
 pub struct Mutation;
@@ -25,128 +95,1908 @@ pub struct Mutation;
 impl Mutation {
     /// Adds a wishlist with a user_id, a list of product_variant_ids and a name.
     ///
+    /// If `deduplicate` is set, returns an existing wishlist of the user with the same name and identical product variants instead.
+    /// Creations of non-admin users are throttled per sliding window, see `throttle_creation`.
     /// Formats UUIDs as hyphenated lowercase strings.
     async fn create_wishlist<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(desc = "CreateWishlistInput")] input: CreateWishlistInput,
     ) -> Result<Wishlist> {
-        authorize_user(&ctx, Some(input.user_id))?;
+        with_idempotency(ctx, "createWishlist", async {
+            insert_wishlist(ctx, input).await
+        })
+        .await
+    }
+
+    /// Imports a wishlist of a user from a CSV file, uploaded according to the GraphQL multipart request specification.
+    ///
+    /// Rows have the form `product_variant_id[,quantity,note]`, an optional header row is skipped.
+    /// Quantities and notes are validated for compatibility with legacy exports, but not imported, as wishlists do not store them.
+    /// The wishlist is only created if all rows are valid, otherwise the errors of all invalid rows are reported.
+    /// Files exceeding the maximum upload size or the maximum number of product variants are rejected before any validation,
+    /// rows are validated in batches.
+    async fn import_wishlist_from_csv<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "ImportWishlistFromCsvInput")] input: ImportWishlistFromCsvInput,
+    ) -> Result<ImportWishlistFromCsvPayload> {
+        with_idempotency(ctx, "importWishlistFromCsv", async {
+            authorize_user(ctx, Some(input.user_id))?;
+            let db_client = ctx.data::<Database>()?;
+            let settings = ctx.data::<Settings>()?;
+            let dapr_client = ctx.data::<DaprClient>()?;
+            let state_cache = ctx.data::<StateCache>()?;
+            let upload = input
+                .file
+                .value(ctx)
+                .map_err(|_| Error::new("Reading uploaded CSV file failed."))?;
+            validate_upload_size(settings, &upload)?;
+            let (rows, mut row_errors) =
+                parse_wishlist_csv(upload.into_read(), settings.max_items_per_wishlist)
+                    .map_err(quota_exceeded_error)?;
+            let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+            validate_wishlist_quota(&collection, settings, tenant_id(ctx)?, input.user_id).await?;
+            let product_variant_collection: Collection<ProductVariant> =
+                db_client.collection::<ProductVariant>("product_variants");
+            for batch in rows.chunks(UPLOAD_VALIDATION_BATCH_SIZE) {
+                let product_variant_ids: Vec<Uuid> =
+                    batch.iter().map(|row| row.product_variant_id).collect();
+                let errors = validate_product_variant_batch(
+                    &product_variant_collection,
+                    settings,
+                    dapr_client,
+                    state_cache,
+                    &product_variant_ids,
+                )
+                .await?;
+                row_errors.extend(batch.iter().filter_map(|row| {
+                    errors
+                        .get(&row.product_variant_id)
+                        .map(|error| CsvRowError {
+                            row: row.row,
+                            message: error.message.clone(),
+                        })
+                }));
+            }
+            if !row_errors.is_empty() {
+                row_errors.sort_by_key(|row_error| row_error.row);
+                return Ok(ImportWishlistFromCsvPayload {
+                    wishlist: None,
+                    row_errors,
+                });
+            }
+            let create_wishlist_input = CreateWishlistInput {
+                user_id: input.user_id,
+                product_variant_ids: rows.iter().map(|row| row.product_variant_id).collect(),
+                name: input.name,
+                description: None,
+                occasion_date: None,
+                cover_image_url: None,
+                icon: None,
+                color: None,
+                expires_at: None,
+                deduplicate: None,
+            };
+            Ok(ImportWishlistFromCsvPayload {
+                wishlist: Some(insert_wishlist(ctx, create_wishlist_input).await?),
+                row_errors,
+            })
+        })
+        .await
+    }
+
+    /// Adds the product variants of a file to a wishlist, uploaded according to the GraphQL multipart request specification.
+    ///
+    /// The file lists one product variant UUID per line. Lines are validated in batches,
+    /// the product variants of all valid lines are added and the errors of all invalid lines are reported.
+    /// Fails without adding any product variant if the file exceeds the maximum upload size
+    /// or the wishlist would exceed the maximum number of product variants, which is checked before any validation
+    /// and enforced again by the conditional update adding the product variants.
+    async fn add_items_from_upload<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "AddItemsFromUploadInput")] input: AddItemsFromUploadInput,
+    ) -> Result<AddItemsFromUploadPayload> {
+        with_idempotency(ctx, "addItemsFromUpload", async {
+            let db_client = ctx.data::<Database>()?;
+            let settings = ctx.data::<Settings>()?;
+            let dapr_client = ctx.data::<DaprClient>()?;
+            let state_cache = ctx.data::<StateCache>()?;
+            let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+            let wishlist = tenant_id(ctx)?
+                .check_wishlist(query_object_from_primary(&collection, input.wishlist_id).await?)?;
+            authorize_user(ctx, Some(wishlist.user._id))?;
+            check_not_suspended(ctx, &wishlist)?;
+            let upload = input
+                .file
+                .value(ctx)
+                .map_err(|_| Error::new("Reading uploaded file failed."))?;
+            validate_upload_size(settings, &upload)?;
+            let (lines, mut line_errors) = parse_product_variant_id_lines(upload.into_read());
+            let product_variant_ids = product_variant_ids_of(&wishlist);
+            let new_lines: Vec<&UploadLine> = lines
+                .iter()
+                .filter(|line| !product_variant_ids.contains(&line.product_variant_id))
+                .collect();
+            validate_item_quota(settings, product_variant_ids.len() + new_lines.len())?;
+            let product_variant_collection: Collection<ProductVariant> =
+                db_client.collection::<ProductVariant>("product_variants");
+            let mut added_product_variant_ids = HashSet::new();
+            for batch in new_lines.chunks(UPLOAD_VALIDATION_BATCH_SIZE) {
+                let batch_product_variant_ids: Vec<Uuid> =
+                    batch.iter().map(|line| line.product_variant_id).collect();
+                let errors = validate_product_variant_batch(
+                    &product_variant_collection,
+                    settings,
+                    dapr_client,
+                    state_cache,
+                    &batch_product_variant_ids,
+                )
+                .await?;
+                for line in batch {
+                    match errors.get(&line.product_variant_id) {
+                        Some(error) => line_errors.push(UploadLineError {
+                            line: line.line,
+                            message: error.message.clone(),
+                        }),
+                        None => {
+                            added_product_variant_ids.insert(line.product_variant_id);
+                        }
+                    }
+                }
+            }
+            line_errors.sort_by_key(|line_error| line_error.line);
+            if added_product_variant_ids.is_empty() {
+                return Ok(AddItemsFromUploadPayload {
+                    wishlist,
+                    added_count: 0,
+                    line_errors,
+                });
+            }
+            write_product_variant_changes(
+                &collection,
+                input.wishlist_id,
+                &added_product_variant_ids,
+                &HashSet::new(),
+                settings.max_items_per_wishlist,
+                &DateTime::now(),
+            )
+            .await?;
+            state_cache
+                .invalidate(&wishlist_key(input.wishlist_id))
+                .await;
+            let updated_wishlist =
+                query_object_from_primary(&collection, input.wishlist_id).await?;
+            let audit_entry = AuditEntry::new(
+                input.wishlist_id,
+                authorized_user_id(ctx).ok(),
+                AuditAction::Updated,
+                wishlist.tenant_id.clone(),
+            )
+            .with_snapshot(WishlistSnapshot::from(&updated_wishlist));
+            record_audit_entry(
+                &db_client.collection::<AuditEntry>("audit_entries"),
+                &audit_entry,
+            )
+            .await;
+            notify_milestones_reached(ctx, wishlist.item_count, &updated_wishlist).await?;
+            Ok(AddItemsFromUploadPayload {
+                wishlist: updated_wishlist,
+                added_count: added_product_variant_ids.len() as u64,
+                line_errors,
+            })
+        })
+        .await
+    }
+
+    /// Updates name and/or product_variant_ids of a specific wishlist referenced with an UUID.
+    ///
+    /// Product variants can be replaced as a whole with `productVariantIds` or changed with `itemOperations`.
+    ///
+    /// Formats UUIDs as hyphenated lowercase strings.
+    async fn update_wishlist<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UpdateWishlistInput")] input: UpdateWishlistInput,
+    ) -> Result<Wishlist> {
+        with_idempotency(ctx, "updateWishlist", async {
+            let db_client = ctx.data::<Database>()?;
+            let settings = ctx.data::<Settings>()?;
+            let dapr_client = ctx.data::<DaprClient>()?;
+            let state_cache = ctx.data::<StateCache>()?;
+            let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+            let wishlist = tenant_id(ctx)?
+                .check_wishlist(query_object_from_primary(&collection, input.id).await?)?;
+            authorize_user(ctx, Some(wishlist.user._id))?;
+            check_not_suspended(ctx, &wishlist)?;
+            if input.product_variant_ids.is_some() && input.item_operations.is_some() {
+                return Err(Error::new(
+                    "Arguments `productVariantIds` and `itemOperations` can not be combined.",
+                ));
+            }
+            ctx.data::<MutationValidators>()?
+                .validate(
+                    ctx,
+                    &WishlistMutation::Update {
+                        wishlist: &wishlist,
+                        input: &input,
+                    },
+                )
+                .await?;
+            let content_flagged = match input.name.is_some() || !input.description.is_undefined() {
+                true => {
+                    let name = input.name.as_ref().unwrap_or(&wishlist.name);
+                    let description = match &input.description {
+                        MaybeUndefined::Value(description) => Some(description.as_str()),
+                        MaybeUndefined::Null => None,
+                        MaybeUndefined::Undefined => wishlist.description.as_deref(),
+                    };
+                    let texts = wishlist_texts(name, description);
+                    Some(ctx.data::<ContentPolicy>()?.check(&texts).await?)
+                }
+                false => None,
+            };
+            let product_variant_collection: Collection<ProductVariant> =
+                db_client.collection::<ProductVariant>("product_variants");
+            let current_timestamp = DateTime::now();
+            update_product_variant_ids(
+                &collection,
+                &product_variant_collection,
+                settings,
+                dapr_client,
+                state_cache,
+                &wishlist,
+                &input,
+                &current_timestamp,
+            )
+            .await?;
+            apply_item_operations(
+                &collection,
+                &product_variant_collection,
+                settings,
+                dapr_client,
+                state_cache,
+                &wishlist,
+                &input,
+                &current_timestamp,
+            )
+            .await?;
+            update_name(&collection, &input, &current_timestamp).await?;
+            update_optional_fields(&collection, settings, &input, &current_timestamp).await?;
+            if let Some(content_flagged) = content_flagged {
+                update_content_flagged(&collection, input.id, content_flagged).await?;
+            }
+            state_cache.invalidate(&wishlist_key(input.id)).await;
+            let updated_wishlist = query_object_from_primary(&collection, input.id).await?;
+            let audit_entry = AuditEntry::new(
+                input.id,
+                authorized_user_id(ctx).ok(),
+                AuditAction::Updated,
+                wishlist.tenant_id.clone(),
+            )
+            .with_snapshot(WishlistSnapshot::from(&updated_wishlist));
+            record_audit_entry(
+                &db_client.collection::<AuditEntry>("audit_entries"),
+                &audit_entry,
+            )
+            .await;
+            notify_milestones_reached(ctx, wishlist.item_count, &updated_wishlist).await?;
+            Ok(updated_wishlist)
+        })
+        .await
+    }
+
+    /// Restores the name and product variants of a wishlist to a previous version of its audit history.
+    ///
+    /// The restored version is validated like an update of the wishlist. The restore is recorded in the audit history as new version.
+    async fn restore_wishlist_version<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to restore.")] wishlist_id: Uuid,
+        #[graphql(desc = "Version to restore, selected by number or timestamp.")]
+        version_or_timestamp: WishlistVersionSelector,
+    ) -> Result<Wishlist> {
         let db_client = ctx.data::<Database>()?;
+        let settings = ctx.data::<Settings>()?;
+        let state_cache = ctx.data::<StateCache>()?;
         let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        validate_input(db_client, &input).await?;
-        let normalized_product_variants: HashSet<ProductVariant> = input
-            .product_variant_ids
+        let audit_collection: Collection<AuditEntry> =
+            db_client.collection::<AuditEntry>("audit_entries");
+        let tenant_id = tenant_id(ctx)?;
+        let wishlist =
+            tenant_id.check_wishlist(query_object_from_primary(&collection, wishlist_id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        check_not_suspended(ctx, &wishlist)?;
+        let (version, snapshot) = find_wishlist_version(
+            &audit_collection,
+            tenant_id,
+            wishlist_id,
+            &version_or_timestamp,
+        )
+        .await?;
+        let input = UpdateWishlistInput {
+            id: wishlist_id,
+            product_variant_ids: Some(snapshot.product_variant_ids.iter().copied().collect()),
+            item_operations: None,
+            name: Some(snapshot.name),
+            description: MaybeUndefined::Undefined,
+            occasion_date: MaybeUndefined::Undefined,
+            cover_image_url: MaybeUndefined::Undefined,
+            icon: MaybeUndefined::Undefined,
+            color: MaybeUndefined::Undefined,
+            expires_at: MaybeUndefined::Undefined,
+        };
+        ctx.data::<MutationValidators>()?
+            .validate(
+                ctx,
+                &WishlistMutation::Update {
+                    wishlist: &wishlist,
+                    input: &input,
+                },
+            )
+            .await?;
+        let texts = wishlist_texts(
+            input.name.as_ref().unwrap_or(&wishlist.name),
+            wishlist.description.as_deref(),
+        );
+        let content_flagged = ctx.data::<ContentPolicy>()?.check(&texts).await?;
+        let current_timestamp = DateTime::now();
+        update_product_variant_ids(
+            &collection,
+            &db_client.collection::<ProductVariant>("product_variants"),
+            settings,
+            ctx.data::<DaprClient>()?,
+            state_cache,
+            &wishlist,
+            &input,
+            &current_timestamp,
+        )
+        .await?;
+        update_name(&collection, &input, &current_timestamp).await?;
+        update_content_flagged(&collection, wishlist_id, content_flagged).await?;
+        state_cache.invalidate(&wishlist_key(wishlist_id)).await;
+        let restored_wishlist = query_object_from_primary(&collection, wishlist_id).await?;
+        let audit_entry = AuditEntry::new(
+            wishlist_id,
+            authorized_user_id(ctx).ok(),
+            AuditAction::Restored { version },
+            tenant_id.0.clone(),
+        )
+        .with_snapshot(WishlistSnapshot::from(&restored_wishlist));
+        record_audit_entry(&audit_collection, &audit_entry).await;
+        notify_milestones_reached(ctx, wishlist.item_count, &restored_wishlist).await?;
+        Ok(restored_wishlist)
+    }
+
+    /// Deletes wishlist of UUID, returns whether it was deleted.
+    ///
+    /// A dry run performs all checks and returns whether the wishlist would be deleted, without deleting it.
+    #[graphql(
+        deprecation = "Use `removeWishlist`, which reports the number of deleted wishlists instead of a bare boolean."
+    )]
+    async fn delete_wishlist<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to delete.")] id: Uuid,
+        #[graphql(desc = "Whether the deletion is only checked, without deleting the wishlist.")]
+        dry_run: Option<bool>,
+    ) -> Result<bool> {
+        self.remove_wishlist(ctx, id, dry_run)
+            .await
+            .map(|payload| payload.success)
+    }
+
+    /// Deletes wishlist of UUID and reports the outcome.
+    ///
+    /// Deleting a wishlist which does not exist is not an error, it reports a `deletedCount` of 0, so repeated deletions are observable.
+    /// A dry run performs all checks and reports the number of wishlists which would be deleted, without deleting them.
+    async fn remove_wishlist<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to delete.")] id: Uuid,
+        #[graphql(desc = "Whether the deletion is only checked, without deleting the wishlist.")]
+        dry_run: Option<bool>,
+    ) -> Result<DeleteWishlistPayload> {
+        let dry_run = dry_run.unwrap_or(false);
+        let deletion = async {
+            let db_client = ctx.data::<Database>()?;
+            let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+            let find_options = FindOneOptions::builder()
+                .selection_criteria(SelectionCriteria::ReadPreference(ReadPreference::Primary))
+                .build();
+            let wishlist = match collection.find_one(doc! {"_id": id}, find_options).await {
+                Ok(Some(wishlist)) if wishlist.tenant_id == tenant_id(ctx)?.0 => wishlist,
+                Ok(_) => return Ok(DeleteWishlistPayload::new(id, 0)),
+                Err(_) => {
+                    let message = format!("Retrieving wishlist of id: `{}` failed in MongoDB.", id);
+                    return Err(Error::new(message));
+                }
+            };
+            authorize_user(ctx, Some(wishlist.user._id))?;
+            check_not_suspended(ctx, &wishlist)?;
+            if dry_run {
+                return Ok(DeleteWishlistPayload::new(id, 1));
+            }
+            let deleted_count = match collection.delete_one(doc! {"_id": id }, None).await {
+                Ok(result) => result.deleted_count,
+                Err(_) => {
+                    let message = format!("Deleting wishlist of id: `{}` failed in MongoDB.", id);
+                    return Err(Error::new(message));
+                }
+            };
+            ctx.data::<StateCache>()?
+                .invalidate(&wishlist_key(id))
+                .await;
+            Ok(DeleteWishlistPayload::new(id, deleted_count))
+        };
+        match dry_run {
+            true => deletion.await,
+            false => with_idempotency(ctx, "removeWishlist", deletion).await,
+        }
+    }
+
+    /// Requests the shopping cart service to add all product variants of a wishlist to the cart of its user.
+    ///
+    /// Publishes a command event and returns its correlation UUID, which can be used to track the request.
+    async fn add_wishlist_to_cart<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to add to the shopping cart.")] wishlist_id: Uuid,
+    ) -> Result<Uuid> {
+        with_idempotency(ctx, "addWishlistToCart", async {
+            let db_client = ctx.data::<Database>()?;
+            let dapr_client = ctx.data::<DaprClient>()?;
+            let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+            let wishlist = tenant_id(ctx)?
+                .check_wishlist(query_object_from_primary(&collection, wishlist_id).await?)?;
+            authorize_user(ctx, Some(wishlist.user._id))?;
+            check_not_suspended(ctx, &wishlist)?;
+            let shopping_cart_items = wishlist
+                .internal_product_variants
+                .iter()
+                .map(|product_variant| ShoppingCartItemEventData {
+                    product_variant_id: product_variant._id,
+                    count: 1,
+                })
+                .collect();
+            let correlation_id = Uuid::new();
+            let event_data = AddWishlistToCartEventData {
+                correlation_id,
+                user_id: wishlist.user._id,
+                wishlist_id,
+                shopping_cart_items,
+            };
+            dapr_client
+                .publish_event(ADD_WISHLIST_TO_CART_TOPIC, &event_data)
+                .await?;
+            Ok(correlation_id)
+        })
+        .await
+    }
+
+    /// Moves product variants of a wishlist into a new wishlist of the same user, e.g. to split a wishlist which grew too large.
+    ///
+    /// Purchase marks of the moved product variants are moved along. Performed in a MongoDB transaction, which is retried on transient conflicts.
+    async fn split_wishlist<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to split.")] id: Uuid,
+        #[graphql(desc = "UUIDs of product variants moved into the new wishlist.")]
+        product_variant_ids: HashSet<Uuid>,
+        #[graphql(desc = "Name of the new wishlist.")] new_name: String,
+    ) -> Result<SplitWishlistPayload> {
+        let db_client = ctx.data::<Database>()?;
+        let settings = ctx.data::<Settings>()?;
+        let tenant_id = tenant_id(ctx)?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let wishlist =
+            tenant_id.check_wishlist(query_object_from_primary(&collection, id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        check_not_suspended(ctx, &wishlist)?;
+        if product_variant_ids.is_empty() {
+            return Err(Error::new(
+                "At least one product variant must be moved into the new wishlist.",
+            ));
+        }
+        let contained_product_variant_ids = product_variant_ids_of(&wishlist);
+        if let Some(missing_id) = product_variant_ids
             .iter()
-            .map(|id| ProductVariant { _id: id.clone() })
-            .collect();
+            .find(|product_variant_id| !contained_product_variant_ids.contains(product_variant_id))
+        {
+            let message = format!(
+                "Product variant of id: `{}` is not contained in wishlist of id: `{}`.",
+                missing_id, id
+            );
+            return Err(Error::new(message));
+        }
+        validate_wishlist_quota(&collection, settings, tenant_id, wishlist.user._id).await?;
+        let create_wishlist_input = CreateWishlistInput {
+            user_id: wishlist.user._id,
+            product_variant_ids: product_variant_ids.clone(),
+            name: new_name,
+            description: None,
+            occasion_date: None,
+            cover_image_url: None,
+            icon: None,
+            color: None,
+            expires_at: None,
+            deduplicate: None,
+        };
+        ctx.data::<MutationValidators>()?
+            .validate(ctx, &WishlistMutation::Create(&create_wishlist_input))
+            .await?;
+        throttle_creation(ctx, ThrottledCreation::Wishlist).await?;
+        let content_flagged = ctx
+            .data::<ContentPolicy>()?
+            .check(&wishlist_texts(&create_wishlist_input.name, None))
+            .await?;
         let current_timestamp = DateTime::now();
-        let wishlist = Wishlist {
+        let new_wishlist = Wishlist {
             _id: Uuid::new(),
-            user: User { _id: input.user_id },
-            internal_product_variants: normalized_product_variants,
-            name: input.name,
+            user: wishlist.user.clone(),
+            item_count: product_variant_ids.len() as u64,
+            content_flagged,
+            suspended: false,
+            internal_product_variants: product_variant_ids
+                .iter()
+                .map(|product_variant_id| ProductVariant {
+                    _id: *product_variant_id,
+                })
+                .collect(),
+            purchased_items: wishlist
+                .purchased_items
+                .iter()
+                .filter(|purchased_item| {
+                    product_variant_ids.contains(&purchased_item.product_variant_id)
+                })
+                .cloned()
+                .collect(),
+            target_prices: wishlist
+                .target_prices
+                .iter()
+                .filter(|target_price| {
+                    product_variant_ids.contains(&target_price.product_variant_id)
+                })
+                .cloned()
+                .collect(),
+            name: create_wishlist_input.name,
+            description: None,
+            occasion_date: None,
+            cover_image_url: None,
+            icon: None,
+            color: None,
+            expires_at: None,
+            archived_at: None,
             created_at: current_timestamp,
             last_updated_at: current_timestamp,
+            tenant_id: tenant_id.0.clone(),
         };
-        match collection.insert_one(wishlist, None).await {
-            Ok(result) => {
-                let id = uuid_from_bson(result.inserted_id)?;
-                query_object(&collection, id).await
+        insert_wishlist_within_quota(ctx, db_client, settings, tenant_id, &new_wishlist, Some(id))
+            .await?;
+        ctx.data::<StateCache>()?
+            .invalidate(&wishlist_key(id))
+            .await;
+        let wishlist = query_object_from_primary(&collection, id).await?;
+        let actor_user_id = authorized_user_id(ctx).ok();
+        let audit_collection: Collection<AuditEntry> =
+            db_client.collection::<AuditEntry>("audit_entries");
+        for (audited_wishlist, action) in [
+            (&wishlist, AuditAction::Updated),
+            (&new_wishlist, AuditAction::Created),
+        ] {
+            let audit_entry = AuditEntry::new(
+                audited_wishlist._id,
+                actor_user_id,
+                action,
+                tenant_id.0.clone(),
+            )
+            .with_snapshot(WishlistSnapshot::from(audited_wishlist));
+            record_audit_entry(&audit_collection, &audit_entry).await;
+        }
+        notify_milestones_reached(ctx, 0, &new_wishlist).await?;
+        Ok(SplitWishlistPayload {
+            wishlist,
+            new_wishlist,
+        })
+    }
+
+    /// Marks a product variant of a wishlist as purchased or removes the mark.
+    ///
+    /// Purchased product variants stay in the wishlist. Only the owner of the wishlist may mark its product variants.
+    /// Publishes an event when a product variant becomes purchased.
+    async fn set_item_purchased<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist containing the product variant.")] wishlist_id: Uuid,
+        #[graphql(desc = "UUID of the product variant.")] product_variant_id: Uuid,
+        #[graphql(desc = "Whether the product variant is purchased.")] purchased: bool,
+        #[graphql(desc = "Timestamp of the purchase, defaults to now.")] purchased_at: Option<
+            DateTime,
+        >,
+    ) -> Result<Wishlist> {
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let wishlist = tenant_id(ctx)?
+            .check_wishlist(query_object_from_primary(&collection, wishlist_id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        let previous_item = wishlist
+            .purchased_items
+            .iter()
+            .find(|purchased_item| purchased_item.product_variant_id == product_variant_id);
+        check_not_suspended(ctx, &wishlist)?;
+        if !wishlist
+            .internal_product_variants
+            .contains(&ProductVariant {
+                _id: product_variant_id,
+            })
+        {
+            let message = format!(
+                "Product variant of id: `{}` is not contained in wishlist of id: `{}`.",
+                product_variant_id, wishlist_id
+            );
+            return Err(Error::new(message));
+        }
+        if !purchased && previous_item.is_none() {
+            return Ok(wishlist);
+        }
+        let other_purchased_items = doc! {"$filter": {
+            "input": {"$ifNull": ["$purchased_items", []]},
+            "cond": {"$ne": ["$$this.product_variant_id", product_variant_id]},
+        }};
+        let purchased_item = PurchasedItem {
+            product_variant_id,
+            purchased_at: purchased_at.unwrap_or(DateTime::now()),
+            marked_by_user_id: ctx
+                .data_opt::<AuthorizedUserHeader>()
+                .map(|authorized_user_header| authorized_user_header.id),
+        };
+        let purchased_items = match purchased {
+            true => doc! {"$concatArrays": [
+                other_purchased_items,
+                [{"$literal": bson::to_bson(&purchased_item)?}],
+            ]},
+            false => other_purchased_items,
+        };
+        let update = vec![doc! {"$set": {
+            "purchased_items": purchased_items,
+            "last_updated_at": DateTime::now(),
+        }}];
+        let filter = match previous_item {
+            Some(previous_item) => doc! {
+                "_id": wishlist_id,
+                "purchased_items": {"$elemMatch": {
+                    "product_variant_id": product_variant_id,
+                    "marked_by_user_id": previous_item.marked_by_user_id,
+                }},
+            },
+            None => doc! {
+                "_id": wishlist_id,
+                "purchased_items.product_variant_id": {"$ne": product_variant_id},
+            },
+        };
+        match collection.update_one(filter, update, None).await {
+            Ok(result) if result.matched_count > 0 => (),
+            Ok(_) => {
+                let message = format!(
+                    "Purchase of product variant of id: `{}` in wishlist of id: `{}` was changed concurrently.",
+                    product_variant_id, wishlist_id
+                );
+                return Err(Error::new(message));
+            }
+            Err(_) => {
+                let message = format!(
+                    "Updating purchased items of wishlist of id: `{}` failed in MongoDB.",
+                    wishlist_id
+                );
+                return Err(Error::new(message));
+            }
+        }
+        ctx.data::<StateCache>()?
+            .invalidate(&wishlist_key(wishlist_id))
+            .await;
+        if purchased && previous_item.is_none() {
+            let audit_entry = AuditEntry::new(
+                wishlist_id,
+                purchased_item.marked_by_user_id,
+                AuditAction::ItemPurchased { product_variant_id },
+                wishlist.tenant_id.clone(),
+            );
+            record_audit_entry(
+                &db_client.collection::<AuditEntry>("audit_entries"),
+                &audit_entry,
+            )
+            .await;
+            let event_data = WishlistItemPurchasedEventData {
+                wishlist_id,
+                user_id: wishlist.user._id,
+                product_variant_id,
+                purchased_at: purchased_item.purchased_at,
+                wishlist_created_at: wishlist.created_at,
+            };
+            if let Err(error) = ctx
+                .data::<DaprClient>()?
+                .publish_event(WISHLIST_ITEM_PURCHASED_TOPIC, &event_data)
+                .await
+            {
+                warn!("{}", error.message);
             }
-            Err(_) => Err(Error::new("Adding wishlist failed in MongoDB.")),
         }
+        query_object_from_primary(&collection, wishlist_id).await
     }
 
-    /// Updates name and/or product_variant_ids of a specific wishlist referenced with an UUID.
+    /// Sets or removes the target price of a product variant of a wishlist.
     ///
-    /// Formats UUIDs as hyphenated lowercase strings.
-    async fn update_wishlist<'a>(
+    /// When a catalog price event drops the price of the product variant below the target price, the user is notified once per price drop.
+    async fn set_item_target_price<'a>(
         &self,
         ctx: &Context<'a>,
-        #[graphql(desc = "UpdateWishlistInput")] input: UpdateWishlistInput,
+        #[graphql(desc = "UUID of wishlist containing the product variant.")] wishlist_id: Uuid,
+        #[graphql(desc = "UUID of the product variant.")] product_variant_id: Uuid,
+        #[graphql(
+            desc = "Target price in the minor unit of the catalog currency, `null` removes the target price."
+        )]
+        target_price: Option<Money>,
     ) -> Result<Wishlist> {
         let db_client = ctx.data::<Database>()?;
         let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        let wishlist = query_object(&collection, input.id).await?;
-        authorize_user(&ctx, Some(wishlist.user._id))?;
+        let wishlist = tenant_id(ctx)?
+            .check_wishlist(query_object_from_primary(&collection, wishlist_id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        check_not_suspended(ctx, &wishlist)?;
+        if !wishlist
+            .internal_product_variants
+            .contains(&ProductVariant {
+                _id: product_variant_id,
+            })
+        {
+            let message = format!(
+                "Product variant of id: `{}` is not contained in wishlist of id: `{}`.",
+                product_variant_id, wishlist_id
+            );
+            return Err(Error::new(message));
+        }
+        if target_price
+            .as_ref()
+            .is_some_and(|target_price| target_price.amount == 0)
+        {
+            return Err(Error::new("Target price must be greater than zero."));
+        }
+        let catalog_currency = &ctx.data::<Settings>()?.catalog_currency;
+        if let Some(target_price) = target_price
+            .as_ref()
+            .filter(|target_price| target_price.currency != *catalog_currency)
+        {
+            let message = format!(
+                "Currency of target price: `{}` does not match the catalog currency: `{}`.",
+                target_price.currency, catalog_currency
+            );
+            return Err(Error::new(message));
+        }
+        let other_target_prices = doc! {"$filter": {
+            "input": {"$ifNull": ["$target_prices", []]},
+            "cond": {"$ne": ["$$this.product_variant_id", product_variant_id]},
+        }};
+        let target_prices = match target_price {
+            Some(price) => {
+                let target_price = TargetPrice {
+                    product_variant_id,
+                    price,
+                    notified_at: None,
+                };
+                doc! {"$concatArrays": [
+                    other_target_prices,
+                    [{"$literal": bson::to_bson(&target_price)?}],
+                ]}
+            }
+            None => other_target_prices,
+        };
+        let update = vec![doc! {"$set": {
+            "target_prices": target_prices,
+            "last_updated_at": DateTime::now(),
+        }}];
+        if collection
+            .update_one(doc! {"_id": wishlist_id}, update, None)
+            .await
+            .is_err()
+        {
+            let message = format!(
+                "Updating target prices of wishlist of id: `{}` failed in MongoDB.",
+                wishlist_id
+            );
+            return Err(Error::new(message));
+        }
+        ctx.data::<StateCache>()?
+            .invalidate(&wishlist_key(wishlist_id))
+            .await;
+        query_object_from_primary(&collection, wishlist_id).await
+    }
+
+    /// Creates an API key for a machine client and returns the plaintext key, which can not be retrieved again.
+    ///
+    /// The machine client acts in the tenant of the request creating the key.
+    /// Requires role: `admin`.
+    async fn create_api_key<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Unique name of the machine client.")] name: String,
+        #[graphql(desc = "Scopes granted to the machine client.")] scopes: Vec<ApiKeyScope>,
+    ) -> Result<String> {
+        authorize_admin(ctx)?;
+        let db_client = ctx.data::<DatabaseRouter>()?.default_database();
+        let collection: Collection<ApiKey> = db_client.collection::<ApiKey>("api_keys");
+        let key = generate_api_key();
+        let api_key = ApiKey {
+            _id: hash_api_key(&key),
+            name,
+            scopes,
+            tenant_id: Some(tenant_id(ctx)?.0.clone()),
+            created_at: DateTime::now(),
+        };
+        match collection.insert_one(&api_key, None).await {
+            Ok(_) => Ok(key),
+            Err(error) if is_duplicate_key_error(&error) => {
+                let message = format!("API key with name: `{}` already exists.", api_key.name);
+                Err(Error::new(message))
+            }
+            Err(_) => Err(Error::new("Adding API key failed in MongoDB.")),
+        }
+    }
+
+    /// Creates a calendar token for the calling user and returns the path of the calendar of the occasions of their wishlists.
+    ///
+    /// The path contains the token, which can not be retrieved again. Replaces the previous calendar token of the user.
+    async fn create_calendar_token<'a>(&self, ctx: &Context<'a>) -> Result<String> {
+        let user_id = authorized_user_id(ctx)?;
+        throttle_creation(ctx, ThrottledCreation::CalendarToken).await?;
+        let tenant_id = tenant_id(ctx)?;
+        let settings = ctx.data::<Settings>()?;
+        let db_client = ctx.data::<DatabaseRouter>()?.default_database();
+        let collection: Collection<CalendarToken> =
+            db_client.collection::<CalendarToken>(CALENDAR_TOKEN_COLLECTION);
+        let message = "Adding calendar token failed in MongoDB.";
+        collection
+            .delete_many(doc! {"tenant_id": &tenant_id.0, "user_id": user_id}, None)
+            .await
+            .map_err(|_| Error::new(message))?;
+        let token = generate_api_key();
+        let calendar_token = CalendarToken {
+            _id: hash_api_key(&token),
+            user_id,
+            tenant_id: tenant_id.0.clone(),
+            created_at: DateTime::now(),
+        };
+        match collection.insert_one(&calendar_token, None).await {
+            Ok(_) => Ok(format!(
+                "{}/calendar/{}.ics?token={}",
+                settings.public_path_prefix.0, user_id, token
+            )),
+            Err(_) => Err(Error::new(message)),
+        }
+    }
+
+    /// Enables or disables the periodic digest of the wishlist activity of the calling user.
+    ///
+    /// The first digest is published one digest period after it was enabled. Returns whether the digest is enabled.
+    async fn set_wishlist_digest_enabled<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Whether the digest is enabled.")] enabled: bool,
+    ) -> Result<bool> {
+        let user_id = authorized_user_id(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<DigestSubscription> =
+            db_client.collection::<DigestSubscription>(DIGEST_SUBSCRIPTION_COLLECTION);
+        set_digest_enabled(&collection, tenant_id(ctx)?, user_id, enabled).await?;
+        Ok(enabled)
+    }
+
+    /// Updates the preferences of the calling user, unspecified preferences are kept.
+    async fn update_my_preferences<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UpdateUserPreferencesInput")] input: UpdateUserPreferencesInput,
+    ) -> Result<UserPreferences> {
+        let user_id = authorized_user_id(ctx)?;
+        let tenant_id = tenant_id(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        if let Some(digest_enabled) = input.digest_enabled {
+            let collection: Collection<DigestSubscription> =
+                db_client.collection::<DigestSubscription>(DIGEST_SUBSCRIPTION_COLLECTION);
+            set_digest_enabled(&collection, tenant_id, user_id, digest_enabled).await?;
+        }
+        let collection: Collection<UserPreferences> =
+            db_client.collection::<UserPreferences>(USER_PREFERENCES_COLLECTION);
+        let mut preferences = find_user_preferences(&collection, tenant_id, user_id).await?;
+        if !input.default_wishlist_order.is_undefined() {
+            preferences.default_wishlist_order = input.default_wishlist_order.take();
+            let replace_options = ReplaceOptions::builder().upsert(true).build();
+            if collection
+                .replace_one(
+                    doc! {"_id": &preferences._id},
+                    &preferences,
+                    replace_options,
+                )
+                .await
+                .is_err()
+            {
+                return Err(Error::new("Updating user preferences failed in MongoDB."));
+            }
+        }
+        Ok(preferences)
+    }
+
+    /// Handles failed events again, events handled successfully are removed from the failed events. Requires role: `admin`.
+    async fn reprocess_failed_events<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Identifiers of the failed events to reprocess.")] ids: Vec<String>,
+    ) -> Result<ReprocessFailedEventsPayload> {
+        authorize_admin(ctx)?;
+        let event_service_state = ctx.data::<HttpEventServiceState>()?;
+        let collection = &event_service_state.failed_event_collection;
+        let mut reprocessed_event_ids = vec![];
+        let mut failed_event_ids = vec![];
+        for id in ids {
+            let failed_event = match collection.find_one(doc! {"_id": &id}, None).await {
+                Ok(Some(failed_event)) => failed_event,
+                Ok(None) => {
+                    failed_event_ids.push(id);
+                    continue;
+                }
+                Err(_) => return Err(Error::new("Retrieving failed events failed in MongoDB.")),
+            };
+            match reprocess_failed_event(event_service_state, failed_event).await {
+                Ok(()) => reprocessed_event_ids.push(id),
+                Err(_) => failed_event_ids.push(id),
+            }
+        }
+        Ok(ReprocessFailedEventsPayload {
+            reprocessed_event_ids,
+            failed_event_ids,
+        })
+    }
+
+    /// Adds the users and product variants missing in the projections of the tenant from the user and catalog service.
+    ///
+    /// Useful after data loss or missed events. Requires role: `admin`.
+    async fn reconcile_foreign_projections<'a>(
+        &self,
+        ctx: &Context<'a>,
+    ) -> Result<ReconcileForeignProjectionsPayload> {
+        authorize_admin(ctx)?;
+        let settings = ctx.data::<Settings>()?;
+        let dapr_client = ctx.data::<DaprClient>()?;
+        let db_client = ctx.data::<Database>()?;
+        let foreign_ids = fetch_foreign_ids(dapr_client, settings).await?;
+        reconcile_foreign_projections(db_client, &foreign_ids).await
+    }
+
+    /// Revokes the API key of a machine client. Requires role: `admin`.
+    async fn revoke_api_key<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Name of the machine client.")] name: String,
+    ) -> Result<bool> {
+        authorize_admin(ctx)?;
+        let db_client = ctx.data::<DatabaseRouter>()?.default_database();
+        let collection: Collection<ApiKey> = db_client.collection::<ApiKey>("api_keys");
+        match collection.delete_one(doc! {"name": &name}, None).await {
+            Ok(result) if result.deleted_count == 1 => Ok(true),
+            Ok(_) => {
+                let message = format!("API key with name: `{}` not found.", name);
+                Err(Error::new(message))
+            }
+            Err(_) => Err(Error::new("Revoking API key failed in MongoDB.")),
+        }
+    }
+
+    /// Removes references to product variants which are no longer present in the system from all wishlists of the tenant.
+    ///
+    /// Useful after missed deletion events. A dry run reports the references which would be removed, without removing them.
+    /// Requires role: `admin`.
+    async fn cleanup_orphaned_product_variants<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Whether the orphaned references are only reported, without removing them."
+        )]
+        dry_run: Option<bool>,
+    ) -> Result<CleanupOrphanedProductVariantsPayload> {
+        authorize_admin(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let tenant_id = tenant_id(ctx)?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
         let product_variant_collection: Collection<ProductVariant> =
             db_client.collection::<ProductVariant>("product_variants");
-        let current_timestamp = DateTime::now();
-        update_product_variant_ids(
+        let orphaned_product_variant_ids =
+            find_orphaned_product_variant_ids(&collection, &product_variant_collection, tenant_id)
+                .await?;
+        if orphaned_product_variant_ids.is_empty() {
+            return Ok(CleanupOrphanedProductVariantsPayload {
+                removed_product_variant_ids: vec![],
+                affected_wishlist_ids: vec![],
+            });
+        }
+        let filter = tenant_id
+            .scope(doc! {"internal_product_variants._id": {"$in": &orphaned_product_variant_ids}});
+        let affected_wishlist_ids = match collection.distinct("_id", filter.clone(), None).await {
+            Ok(ids) => ids
+                .into_iter()
+                .map(uuid_from_bson)
+                .collect::<Result<Vec<Uuid>>>()?,
+            Err(_) => return Err(Error::new("Retrieving wishlists failed in MongoDB.")),
+        };
+        if dry_run.unwrap_or(false) {
+            return Ok(CleanupOrphanedProductVariantsPayload {
+                removed_product_variant_ids: orphaned_product_variant_ids,
+                affected_wishlist_ids,
+            });
+        }
+        let update = vec![
+            doc! {"$set": {
+                "internal_product_variants": {"$filter": {
+                    "input": "$internal_product_variants",
+                    "cond": {"$not": [{"$in": ["$$this._id", &orphaned_product_variant_ids]}]},
+                }},
+                "last_updated_at": DateTime::now(),
+            }},
+            Wishlist::product_variant_dependent_fields_stage(),
+        ];
+        if collection.update_many(filter, update, None).await.is_err() {
+            return Err(Error::new(
+                "Removing orphaned product variants from wishlists failed in MongoDB.",
+            ));
+        }
+        invalidate_wishlists(ctx.data::<StateCache>()?, &affected_wishlist_ids).await;
+        record_wishlist_snapshots(
             &collection,
-            &product_variant_collection,
-            &input,
-            &current_timestamp,
+            &db_client.collection::<AuditEntry>("audit_entries"),
+            &affected_wishlist_ids,
+            authorized_user_id(ctx).ok(),
         )
+        .await;
+        Ok(CleanupOrphanedProductVariantsPayload {
+            removed_product_variant_ids: orphaned_product_variant_ids,
+            affected_wishlist_ids,
+        })
+    }
+
+    /// Applies a patch to all wishlists of the tenant matching a filter.
+    ///
+    /// Changed wishlists are streamed and patched in batches, each field is only written to the wishlists it changes.
+    /// Publishes an event and records an audit entry for each changed wishlist. Requires role: `admin`.
+    async fn bulk_update_wishlists<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Filter of the wishlists to patch.")] filter: BulkWishlistFilterInput,
+        #[graphql(desc = "Patch applied to the matching wishlists.")] patch: BulkWishlistPatchInput,
+    ) -> Result<BulkUpdateWishlistsPayload> {
+        authorize_admin(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let remove_product_variant_ids: Vec<Uuid> = patch
+            .remove_product_variant_ids
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let mut change_filters = vec![];
+        if !remove_product_variant_ids.is_empty() {
+            change_filters
+                .push(doc! {"internal_product_variants._id": {"$in": &remove_product_variant_ids}});
+        }
+        match patch.archived {
+            Some(true) => change_filters.push(doc! {"archived_at": null}),
+            Some(false) => change_filters.push(doc! {"archived_at": {"$ne": null}}),
+            None => (),
+        }
+        if change_filters.is_empty() {
+            return Err(Error::new(
+                "Patch of bulk update does not change wishlists.",
+            ));
+        }
+        let filter_doc = tenant_id(ctx)?.scope(filter.to_document());
+        let mut change_filter_doc = filter_doc.clone();
+        change_filter_doc.insert("$or", change_filters);
+        let message = "Bulk updating wishlists failed in MongoDB.";
+        let matched_count = collection
+            .count_documents(filter_doc, None)
+            .await
+            .map_err(|_| Error::new(message))?;
+        let mut cursor = collection
+            .find(change_filter_doc, None)
+            .await
+            .map_err(|_| Error::new(message))?;
+        let mut modified_count = 0;
+        let mut batch = vec![];
+        while let Some(wishlist) = cursor.try_next().await.map_err(|_| Error::new(message))? {
+            batch.push(wishlist);
+            if batch.len() == BULK_UPDATE_BATCH_SIZE {
+                modified_count += patch_wishlist_batch(
+                    ctx,
+                    &collection,
+                    &std::mem::take(&mut batch),
+                    &remove_product_variant_ids,
+                    patch.archived,
+                )
+                .await?;
+            }
+        }
+        if !batch.is_empty() {
+            modified_count += patch_wishlist_batch(
+                ctx,
+                &collection,
+                &batch,
+                &remove_product_variant_ids,
+                patch.archived,
+            )
+            .await?;
+        }
+        Ok(BulkUpdateWishlistsPayload {
+            matched_count,
+            modified_count,
+        })
+    }
+
+    /// Moves the ownership of all wishlists of a user to another user, e.g. when merging user accounts.
+    ///
+    /// Wishlists whose names collide with wishlists of the receiving user are renamed with a numeric suffix.
+    /// Performed in a MongoDB transaction and recorded in the audit history.
+    /// A dry run reports the wishlists which would be reassigned and renamed, without reassigning them. Requires role: `admin`.
+    async fn reassign_wishlists<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of user whose wishlists are reassigned.")] from_user_id: Uuid,
+        #[graphql(desc = "UUID of user receiving the wishlists.")] to_user_id: Uuid,
+        #[graphql(
+            desc = "Whether the reassignment is only reported, without reassigning the wishlists."
+        )]
+        dry_run: Option<bool>,
+    ) -> Result<ReassignWishlistsPayload> {
+        authorize_admin(ctx)?;
+        if from_user_id == to_user_id {
+            return Err(Error::new(
+                "Wishlists can not be reassigned to the user owning them.",
+            ));
+        }
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let audit_collection: Collection<AuditEntry> =
+            db_client.collection::<AuditEntry>("audit_entries");
+        let user_collection: Collection<User> = db_client.collection::<User>("users");
+        validate_user(&user_collection, to_user_id).await?;
+        let tenant_id = tenant_id(ctx)?;
+        let actor_user_id = ctx
+            .data_opt::<AuthorizedUserHeader>()
+            .map(|authorized_user_header| authorized_user_header.id);
+        let wishlists = find_wishlists_of_user(&collection, tenant_id, from_user_id).await?;
+        let mut taken_names: HashSet<String> =
+            find_wishlists_of_user(&collection, tenant_id, to_user_id)
+                .await?
+                .into_iter()
+                .map(|wishlist| wishlist.name)
+                .collect();
+        let mut renamed_wishlist_ids = vec![];
+        let mut reassignments = vec![];
+        let mut audit_entries = vec![];
+        for wishlist in wishlists {
+            let name = resolve_name_collision(&wishlist.name, &mut taken_names);
+            if name != wishlist.name {
+                renamed_wishlist_ids.push(wishlist._id);
+            }
+            reassignments.push((wishlist._id, name.clone()));
+            let snapshot = WishlistSnapshot {
+                name: name.clone(),
+                ..WishlistSnapshot::from(&wishlist)
+            };
+            let action = AuditAction::Reassigned {
+                from_user_id,
+                to_user_id,
+                previous_name: wishlist.name,
+                name,
+            };
+            audit_entries.push(
+                AuditEntry::new(wishlist._id, actor_user_id, action, tenant_id.0.clone())
+                    .with_snapshot(snapshot),
+            );
+        }
+        if dry_run.unwrap_or(false) {
+            return Ok(ReassignWishlistsPayload {
+                reassigned_wishlist_ids: reassignments.into_iter().map(|(id, _)| id).collect(),
+                renamed_wishlist_ids,
+            });
+        }
+        let mut session = match ctx.data::<Client>()?.start_session(None).await {
+            Ok(session) => session,
+            Err(_) => return Err(Error::new("Starting MongoDB session failed.")),
+        };
+        if session.start_transaction(None).await.is_err() {
+            return Err(Error::new("Starting MongoDB transaction failed."));
+        }
+        let result = write_reassignments(
+            &collection,
+            &audit_collection,
+            to_user_id,
+            &reassignments,
+            &audit_entries,
+            &mut session,
+        )
+        .await;
+        let transaction_result = match result {
+            Ok(()) => session.commit_transaction().await,
+            Err(error) => {
+                let _ = session.abort_transaction().await;
+                Err(error)
+            }
+        };
+        if transaction_result.is_err() {
+            let message = format!(
+                "Reassigning wishlists of user of UUID: `{}` failed in MongoDB.",
+                from_user_id
+            );
+            return Err(Error::new(message));
+        }
+        let reassigned_wishlist_ids: Vec<Uuid> =
+            reassignments.into_iter().map(|(id, _)| id).collect();
+        invalidate_wishlists(ctx.data::<StateCache>()?, &reassigned_wishlist_ids).await;
+        Ok(ReassignWishlistsPayload {
+            reassigned_wishlist_ids,
+            renamed_wishlist_ids,
+        })
+    }
+}
+
+/// Creates a wishlist after validating its input, see `Mutation::create_wishlist`.
+///
+/// * `ctx` - GraphQL context containing the database client, settings and the caller.
+/// * `input` - Wishlist to create.
+async fn insert_wishlist(ctx: &Context<'_>, input: CreateWishlistInput) -> Result<Wishlist> {
+    authorize_user(ctx, Some(input.user_id))?;
+    let db_client = ctx.data::<Database>()?;
+    let settings = ctx.data::<Settings>()?;
+    let dapr_client = ctx.data::<DaprClient>()?;
+    let state_cache = ctx.data::<StateCache>()?;
+    let tenant_id = tenant_id(ctx)?;
+    let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+    if input.deduplicate.unwrap_or(false) {
+        if let Some(wishlist) = find_duplicate_wishlist(&collection, tenant_id, &input).await? {
+            return Ok(wishlist);
+        }
+    }
+    validate_wishlist_quota(&collection, settings, tenant_id, input.user_id).await?;
+    validate_item_quota(settings, input.product_variant_ids.len())?;
+    if let Some(cover_image_url) = &input.cover_image_url {
+        validate_cover_image_url(settings, cover_image_url)?;
+    }
+    if let Some(icon) = &input.icon {
+        validate_icon(settings, icon)?;
+    }
+    if let Some(color) = &input.color {
+        validate_color(color)?;
+    }
+    if let Some(expires_at) = &input.expires_at {
+        validate_expires_at(expires_at)?;
+    }
+    validate_input(db_client, settings, dapr_client, state_cache, &input).await?;
+    let normalized_product_variants: HashSet<ProductVariant> = input
+        .product_variant_ids
+        .iter()
+        .map(|id| ProductVariant { _id: *id })
+        .collect();
+    ctx.data::<MutationValidators>()?
+        .validate(ctx, &WishlistMutation::Create(&input))
         .await?;
-        update_name(&collection, &input, &current_timestamp).await?;
-        query_object(&collection, input.id).await
+    throttle_creation(ctx, ThrottledCreation::Wishlist).await?;
+    let content_flagged = ctx
+        .data::<ContentPolicy>()?
+        .check(&wishlist_texts(&input.name, input.description.as_deref()))
+        .await?;
+    let current_timestamp = DateTime::now();
+    let wishlist = Wishlist {
+        _id: Uuid::new(),
+        user: User { _id: input.user_id },
+        item_count: normalized_product_variants.len() as u64,
+        content_flagged,
+        suspended: false,
+        internal_product_variants: normalized_product_variants,
+        purchased_items: vec![],
+        target_prices: vec![],
+        name: input.name,
+        description: input.description,
+        occasion_date: input.occasion_date,
+        cover_image_url: input.cover_image_url,
+        icon: input.icon,
+        color: input.color.map(|color| color.to_lowercase()),
+        expires_at: input.expires_at,
+        archived_at: None,
+        created_at: current_timestamp,
+        last_updated_at: current_timestamp,
+        tenant_id: tenant_id.0.clone(),
+    };
+    insert_wishlist_within_quota(ctx, db_client, settings, tenant_id, &wishlist, None).await?;
+    let audit_entry = AuditEntry::new(
+        wishlist._id,
+        authorized_user_id(ctx).ok(),
+        AuditAction::Created,
+        wishlist.tenant_id.clone(),
+    )
+    .with_snapshot(WishlistSnapshot::from(&wishlist));
+    record_audit_entry(
+        &db_client.collection::<AuditEntry>("audit_entries"),
+        &audit_entry,
+    )
+    .await;
+    notify_milestones_reached(ctx, 0, &wishlist).await?;
+    Ok(wishlist)
+}
+
+/// Publishes an event for each milestone size crossed by a growing wishlist, if milestone events are enabled.
+///
+/// Failed publications are logged, as the mutation already succeeded.
+///
+/// * `ctx` - GraphQL context containing the service settings and the Dapr client.
+/// * `previous_item_count` - Number of product variants of the wishlist before the mutation, 0 for created wishlists.
+/// * `wishlist` - Wishlist after the mutation.
+async fn notify_milestones_reached(
+    ctx: &Context<'_>,
+    previous_item_count: u64,
+    wishlist: &Wishlist,
+) -> Result<()> {
+    let settings = ctx.data::<Settings>()?;
+    if !settings.wishlist_milestone_events_enabled {
+        return Ok(());
+    }
+    let dapr_client = ctx.data::<DaprClient>()?;
+    for milestone in settings
+        .wishlist_milestone_sizes
+        .crossed(previous_item_count, wishlist.item_count)
+    {
+        let event_data = WishlistMilestoneReachedEventData {
+            wishlist_id: wishlist._id,
+            user_id: wishlist.user._id,
+            milestone,
+            item_count: wishlist.item_count,
+            reached_at: wishlist.last_updated_at,
+        };
+        if let Err(error) = dapr_client
+            .publish_event(WISHLIST_MILESTONE_REACHED_TOPIC, &event_data)
+            .await
+        {
+            warn!("{}", error.message);
+        }
+    }
+    Ok(())
+}
+
+/// Removes cached wishlists after they were modified.
+///
+/// * `state_cache` - Cache of wishlists.
+/// * `ids` - UUIDs of modified wishlists.
+async fn invalidate_wishlists(state_cache: &StateCache, ids: &[Uuid]) {
+    for id in ids {
+        state_cache.invalidate(&wishlist_key(*id)).await;
+    }
+}
+
+/// Extracts UUID from BSON.
+///
+/// Adding a wishlist returns a UUID in a BSON document. This function helps to extract the UUID.
+///
+/// * `bson` - BSON document to extract UUID from.
+fn uuid_from_bson(bson: Bson) -> Result<Uuid> {
+    match bson {
+        Bson::Binary(id) => Ok(Uuid(id.to_uuid()?)),
+        _ => {
+            let message = format!(
+                "Returned id: `{}` needs to be a Binary in order to be parsed as a Uuid",
+                bson
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Finds UUIDs of product variants which are referenced in wishlists, but no longer present in the system.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `product_variant_collection` - MongoDB collection of product variants present in the system.
+/// * `tenant_id` - Tenant owning the wishlists.
+async fn find_orphaned_product_variant_ids(
+    collection: &Collection<Wishlist>,
+    product_variant_collection: &Collection<ProductVariant>,
+    tenant_id: &TenantId,
+) -> Result<Vec<Uuid>> {
+    let referenced_product_variant_ids: Vec<Uuid> = match collection
+        .distinct(
+            "internal_product_variants._id",
+            tenant_id.scope(doc! {}),
+            None,
+        )
+        .await
+    {
+        Ok(ids) => ids.into_iter().map(uuid_from_bson).collect::<Result<_>>()?,
+        Err(_) => return Err(Error::new("Retrieving wishlists failed in MongoDB.")),
+    };
+    let present_product_variant_ids: Vec<Uuid> = match product_variant_collection
+        .distinct(
+            "_id",
+            doc! {"_id": {"$in": &referenced_product_variant_ids}},
+            None,
+        )
+        .await
+    {
+        Ok(ids) => ids.into_iter().map(uuid_from_bson).collect::<Result<_>>()?,
+        Err(_) => return Err(Error::new("Retrieving product variants failed in MongoDB.")),
+    };
+    let orphaned_product_variant_ids = referenced_product_variant_ids
+        .into_iter()
+        .filter(|id| !present_product_variant_ids.contains(id))
+        .collect();
+    Ok(orphaned_product_variant_ids)
+}
+
+/// Patches a batch of wishlists of a bulk update and returns the number of modified wishlists.
+///
+/// Only wishlists containing the product variants to remove or not having the requested archival are matched,
+/// so the modified count and `last_updated_at` only cover wishlists actually changed by the patch.
+///
+/// * `ctx` - GraphQL context containing the state cache, the Dapr client and the `Authorized-User` header of the admin.
+/// * `collection` - MongoDB collection of wishlists.
+/// * `wishlists` - Wishlists to patch, as they were before the patch.
+/// * `remove_product_variant_ids` - UUIDs of product variants to remove from the wishlists.
+/// * `archived` - Whether the wishlists are archived or restored, `None` if the archival is not changed.
+async fn patch_wishlist_batch(
+    ctx: &Context<'_>,
+    collection: &Collection<Wishlist>,
+    wishlists: &[Wishlist],
+    remove_product_variant_ids: &[Uuid],
+    archived: Option<bool>,
+) -> Result<u64> {
+    let current_timestamp = DateTime::now();
+    let ids: Vec<Uuid> = wishlists.iter().map(|wishlist| wishlist._id).collect();
+    let mut changed_filters = vec![];
+    let mut set = doc! {"last_updated_at": current_timestamp};
+    if !remove_product_variant_ids.is_empty() {
+        changed_filters
+            .push(doc! {"internal_product_variants._id": {"$in": remove_product_variant_ids}});
+        set.insert(
+            "internal_product_variants",
+            doc! {"$filter": {
+                "input": "$internal_product_variants",
+                "cond": {"$not": [{"$in": ["$$this._id", remove_product_variant_ids]}]},
+            }},
+        );
+    }
+    if let Some(archived) = archived {
+        match archived {
+            true => {
+                changed_filters.push(doc! {"archived_at": null});
+                set.insert(
+                    "archived_at",
+                    doc! {"$ifNull": ["$archived_at", current_timestamp]},
+                );
+            }
+            false => {
+                changed_filters.push(doc! {"archived_at": {"$ne": null}});
+                set.insert("archived_at", doc! {"$literal": null});
+            }
+        }
+    }
+    if changed_filters.is_empty() {
+        return Ok(0);
+    }
+    let mut update = vec![doc! {"$set": set}];
+    if !remove_product_variant_ids.is_empty() {
+        update.push(Wishlist::product_variant_dependent_fields_stage());
+    }
+    let modified_count = collection
+        .update_many(
+            doc! {"_id": {"$in": &ids}, "$or": changed_filters},
+            update,
+            None,
+        )
+        .await
+        .map_err(|_| Error::new("Bulk updating wishlists failed in MongoDB."))?
+        .modified_count;
+    invalidate_wishlists(ctx.data::<StateCache>()?, &ids).await;
+    let actor_user_id = authorized_user_id(ctx).ok();
+    let mut events = vec![];
+    let mut audit_entries = vec![];
+    for wishlist in wishlists {
+        let removed_product_variant_ids: Vec<Uuid> = remove_product_variant_ids
+            .iter()
+            .filter(|id| {
+                wishlist
+                    .internal_product_variants
+                    .contains(&ProductVariant { _id: **id })
+            })
+            .copied()
+            .collect();
+        let archived = archived.filter(|archived| *archived != wishlist.archived_at.is_some());
+        let mut snapshot = WishlistSnapshot::from(wishlist);
+        snapshot
+            .product_variant_ids
+            .retain(|id| !removed_product_variant_ids.contains(id));
+        let action = AuditAction::BulkUpdated {
+            removed_product_variant_ids: removed_product_variant_ids.clone(),
+            archived,
+        };
+        audit_entries.push(
+            AuditEntry::new(
+                wishlist._id,
+                actor_user_id,
+                action,
+                wishlist.tenant_id.clone(),
+            )
+            .with_snapshot(snapshot),
+        );
+        events.push(WishlistBulkUpdatedEventData {
+            id: wishlist._id,
+            user_id: wishlist.user._id,
+            removed_product_variant_ids,
+            archived,
+        });
     }
-
-    /// Deletes wishlist of UUID.
-    async fn delete_wishlist<'a>(
-        &self,
-        ctx: &Context<'a>,
-        #[graphql(desc = "UUID of wishlist to delete.")] id: Uuid,
-    ) -> Result<bool> {
-        let db_client = ctx.data::<Database>()?;
-        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        let wishlist = query_object(&collection, id).await?;
-        authorize_user(&ctx, Some(wishlist.user._id))?;
-        if let Err(_) = collection.delete_one(doc! {"_id": id }, None).await {
-            let message = format!("Deleting wishlist of id: `{}` failed in MongoDB.", id);
-            return Err(Error::new(message));
+    record_audit_entries(
+        &ctx.data::<Database>()?
+            .collection::<AuditEntry>("audit_entries"),
+        &audit_entries,
+    )
+    .await;
+    let dapr_client = ctx.data::<DaprClient>()?;
+    for events in events.chunks(ctx.data::<Settings>()?.event_batch_max_size) {
+        if let Err(error) = dapr_client
+            .publish_events(WISHLIST_BULK_UPDATED_TOPIC, events)
+            .await
+        {
+            warn!("{}", error.message);
         }
-        Ok(true)
     }
+    Ok(modified_count)
 }
 
-/// Extracts UUID from BSON.
+/// Finds a wishlist of the user with the same name and identical product variants as a wishlist to create.
 ///
-/// Adding a wishlist returns a UUID in a BSON document. This function helps to extract the UUID.
+/// The number of product variants is always compared, so wishlists containing additional product variants are not identical.
 ///
-/// * `bson` - BSON document to extract UUID from.
-fn uuid_from_bson(bson: Bson) -> Result<Uuid> {
-    match bson {
-        Bson::Binary(id) => Ok(id.to_uuid()?),
-        _ => {
+/// * `collection` - MongoDB collection of wishlists.
+/// * `tenant_id` - Tenant the wishlist is created in.
+/// * `input` - Input of the wishlist to create.
+async fn find_duplicate_wishlist(
+    collection: &Collection<Wishlist>,
+    tenant_id: &TenantId,
+    input: &CreateWishlistInput,
+) -> Result<Option<Wishlist>> {
+    let product_variant_ids: Vec<Uuid> = input.product_variant_ids.iter().copied().collect();
+    let mut filter = doc! {
+        "user._id": input.user_id,
+        "name": &input.name,
+        "item_count": product_variant_ids.len() as i64,
+    };
+    if !product_variant_ids.is_empty() {
+        filter.insert(
+            "internal_product_variants._id",
+            doc! {"$all": &product_variant_ids},
+        );
+    }
+    let filter = tenant_id.scope(filter);
+    collection.find_one(filter, None).await.map_err(|_| {
+        let message = format!(
+            "Retrieving wishlists of user of UUID: `{}` failed in MongoDB.",
+            input.user_id
+        );
+        Error::new(message)
+    })
+}
+
+/// Retrieves all wishlists of a user in a tenant.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `tenant_id` - Tenant owning the wishlists.
+/// * `user_id` - UUID of user owning the wishlists.
+async fn find_wishlists_of_user(
+    collection: &Collection<Wishlist>,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+) -> Result<Vec<Wishlist>> {
+    match collection
+        .find(tenant_id.scope(doc! {"user._id": user_id}), None)
+        .await
+    {
+        Ok(cursor) => Ok(cursor.try_collect().await?),
+        Err(_) => {
             let message = format!(
-                "Returned id: `{}` needs to be a Binary in order to be parsed as a Uuid",
-                bson
+                "Retrieving wishlists of user of UUID: `{}` failed in MongoDB.",
+                user_id
             );
             Err(Error::new(message))
         }
     }
 }
 
+/// Returns a wishlist name which is not taken yet and marks it as taken.
+///
+/// Taken names are suffixed with the lowest free number, starting with ` (2)`.
+///
+/// * `name` - Preferred wishlist name.
+/// * `taken_names` - Wishlist names which are already taken.
+fn resolve_name_collision(name: &str, taken_names: &mut HashSet<String>) -> String {
+    let mut resolved_name = name.to_string();
+    let mut suffix = 2;
+    while taken_names.contains(&resolved_name) {
+        resolved_name = format!("{} ({})", name, suffix);
+        suffix += 1;
+    }
+    taken_names.insert(resolved_name.clone());
+    resolved_name
+}
+
+/// Writes reassignments of wishlists and their audit entries.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `audit_collection` - MongoDB collection of audit entries.
+/// * `to_user_id` - UUID of user receiving the wishlists.
+/// * `reassignments` - UUIDs of reassigned wishlists with their resolved names.
+/// * `audit_entries` - Audit entries of reassignments.
+/// * `session` - MongoDB session with active transaction.
+async fn write_reassignments(
+    collection: &Collection<Wishlist>,
+    audit_collection: &Collection<AuditEntry>,
+    to_user_id: Uuid,
+    reassignments: &[(Uuid, String)],
+    audit_entries: &[AuditEntry],
+    session: &mut ClientSession,
+) -> mongodb::error::Result<()> {
+    let current_timestamp = DateTime::now();
+    for (id, name) in reassignments {
+        collection
+            .update_one_with_session(
+                doc! {"_id": id},
+                doc! {"$set": {"user._id": to_user_id, "name": name, "last_updated_at": current_timestamp}},
+                None,
+                session,
+            )
+            .await?;
+    }
+    if !audit_entries.is_empty() {
+        audit_collection
+            .insert_many_with_session(audit_entries, None, session)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Removes the product variants of a new wishlist from the split wishlist and inserts the new wishlist.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `id` - UUID of the split wishlist.
+/// * `new_wishlist` - New wishlist containing the moved product variants.
+/// * `session` - MongoDB session of the transaction.
+async fn write_split(
+    collection: &Collection<Wishlist>,
+    id: Uuid,
+    new_wishlist: &Wishlist,
+    session: &mut ClientSession,
+) -> mongodb::error::Result<()> {
+    let moved_ids: Vec<Uuid> = product_variant_ids_of(new_wishlist).into_iter().collect();
+    let update = vec![
+        doc! {"$set": {
+            "internal_product_variants": {"$filter": {
+                "input": "$internal_product_variants",
+                "cond": {"$not": [{"$in": ["$$this._id", &moved_ids]}]},
+            }},
+            "last_updated_at": new_wishlist.created_at,
+        }},
+        Wishlist::product_variant_dependent_fields_stage(),
+    ];
+    collection
+        .update_one_with_session(doc! {"_id": id}, update, None, session)
+        .await?;
+    collection
+        .insert_one_with_session(new_wishlist, None, session)
+        .await?;
+    Ok(())
+}
+
 /// Updates product variant ids of a wishlist.
 ///
+/// Adds and removes the difference to the current product variants, so concurrent additions are not dropped.
+///
 /// * `collection` - MongoDB collection to update.
 /// * `product_variant_collection` - MongoDB product variant collection used for product variant validation.
+/// * `settings` - Service settings defining the product variant validation.
+/// * `dapr_client` - Dapr client used for product variant validation against the catalog service.
+/// * `state_cache` - Cache of product variant lookups in the catalog service.
+/// * `wishlist` - Wishlist before the update.
 /// * `input` - Update wishlist input containing product variant ids.
 /// * `current_timestamp` - Timestamp of product variant ids update.
+#[allow(clippy::too_many_arguments)]
 async fn update_product_variant_ids(
     collection: &Collection<Wishlist>,
     product_variant_collection: &Collection<ProductVariant>,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
+    wishlist: &Wishlist,
     input: &UpdateWishlistInput,
     current_timestamp: &DateTime,
 ) -> Result<()> {
     if let Some(definitely_product_variant_ids) = &input.product_variant_ids {
-        validate_product_variant_ids(&product_variant_collection, definitely_product_variant_ids)
-            .await?;
-        let normalized_product_variants: Vec<ProductVariant> = definitely_product_variant_ids
-            .iter()
-            .map(|id| ProductVariant { _id: id.clone() })
+        validate_item_quota(settings, definitely_product_variant_ids.len())?;
+        let validation = validate_product_variant_ids(
+            product_variant_collection,
+            settings,
+            dapr_client,
+            state_cache,
+            definitely_product_variant_ids,
+        );
+        validate_with_strictness(settings.validation_strictness, validation).await?;
+        let current_product_variant_ids = product_variant_ids_of(wishlist);
+        let added_product_variant_ids = definitely_product_variant_ids
+            .difference(&current_product_variant_ids)
+            .copied()
             .collect();
-        if let Err(_) = collection.update_one(doc!{"_id": input.id }, doc!{"$set": {"internal_product_variants": normalized_product_variants, "last_updated_at": current_timestamp}}, None).await {
-            let message = format!("Updating product_variant_ids of wishlist of id: `{}` failed in MongoDB.", input.id);
-            return Err(Error::new(message))
-        }
+        let removed_product_variant_ids = current_product_variant_ids
+            .difference(definitely_product_variant_ids)
+            .copied()
+            .collect();
+        write_product_variant_changes(
+            collection,
+            input.id,
+            &added_product_variant_ids,
+            &removed_product_variant_ids,
+            settings.max_items_per_wishlist,
+            current_timestamp,
+        )
+        .await?;
     }
     Ok(())
 }
 
+/// Applies the item operations of the update wishlist input.
+///
+/// Only the last operation per product variant takes effect. Added product variants are validated.
+///
+/// * `collection` - MongoDB collection to update.
+/// * `product_variant_collection` - MongoDB product variant collection used for product variant validation.
+/// * `settings` - Service settings defining the product variant validation and the maximum number of product variants.
+/// * `dapr_client` - Dapr client used for product variant validation against the catalog service.
+/// * `state_cache` - Cache of product variant lookups in the catalog service.
+/// * `wishlist` - Wishlist before the update.
+/// * `input` - Update wishlist input containing item operations.
+/// * `current_timestamp` - Timestamp of item operations.
+#[allow(clippy::too_many_arguments)]
+async fn apply_item_operations(
+    collection: &Collection<Wishlist>,
+    product_variant_collection: &Collection<ProductVariant>,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
+    wishlist: &Wishlist,
+    input: &UpdateWishlistInput,
+    current_timestamp: &DateTime,
+) -> Result<()> {
+    let item_operations = match &input.item_operations {
+        Some(item_operations) if !item_operations.is_empty() => item_operations,
+        _ => return Ok(()),
+    };
+    let mut added_product_variant_ids = HashSet::new();
+    let mut removed_product_variant_ids = HashSet::new();
+    for item_operation in item_operations {
+        let id = item_operation.product_variant_id;
+        match item_operation.op {
+            ItemOperationType::Add => {
+                removed_product_variant_ids.remove(&id);
+                added_product_variant_ids.insert(id);
+            }
+            ItemOperationType::Remove => {
+                added_product_variant_ids.remove(&id);
+                removed_product_variant_ids.insert(id);
+            }
+        }
+    }
+    let mut resulting_product_variant_ids = product_variant_ids_of(wishlist);
+    resulting_product_variant_ids.extend(&added_product_variant_ids);
+    resulting_product_variant_ids.retain(|id| !removed_product_variant_ids.contains(id));
+    validate_item_quota(settings, resulting_product_variant_ids.len())?;
+    if !added_product_variant_ids.is_empty() {
+        let validation = validate_product_variant_ids(
+            product_variant_collection,
+            settings,
+            dapr_client,
+            state_cache,
+            &added_product_variant_ids,
+        );
+        validate_with_strictness(settings.validation_strictness, validation).await?;
+    }
+    write_product_variant_changes(
+        collection,
+        input.id,
+        &added_product_variant_ids,
+        &removed_product_variant_ids,
+        settings.max_items_per_wishlist,
+        current_timestamp,
+    )
+    .await
+}
+
+/// Returns the UUIDs of the product variants of a wishlist.
+///
+/// * `wishlist` - Wishlist containing the product variants.
+fn product_variant_ids_of(wishlist: &Wishlist) -> HashSet<Uuid> {
+    wishlist
+        .internal_product_variants
+        .iter()
+        .map(|product_variant| product_variant._id)
+        .collect()
+}
+
+/// Adds and removes product variants of a wishlist in a single pipeline update, which also derives its fields depending on the product variants.
+///
+/// Never replaces the whole array of product variants, so concurrent changes of other product variants are preserved.
+/// If a maximum number of product variants is set, the update only matches if the resulting wishlist does not exceed it,
+/// so concurrent additions can not exceed the quota together.
+///
+/// * `collection` - MongoDB collection to update.
+/// * `id` - UUID of wishlist to update.
+/// * `added_product_variant_ids` - UUIDs of product variants to add.
+/// * `removed_product_variant_ids` - UUIDs of product variants to remove.
+/// * `max_items_per_wishlist` - Maximum number of product variants per wishlist, unlimited if `None`.
+/// * `current_timestamp` - Timestamp of the update.
+async fn write_product_variant_changes(
+    collection: &Collection<Wishlist>,
+    id: Uuid,
+    added_product_variant_ids: &HashSet<Uuid>,
+    removed_product_variant_ids: &HashSet<Uuid>,
+    max_items_per_wishlist: Option<u64>,
+    current_timestamp: &DateTime,
+) -> Result<()> {
+    let added_product_variants: Vec<ProductVariant> = added_product_variant_ids
+        .iter()
+        .map(|id| ProductVariant { _id: *id })
+        .collect();
+    let removed_product_variant_ids: Vec<Uuid> =
+        removed_product_variant_ids.iter().copied().collect();
+    let resulting_product_variants = doc! {"$setUnion": [
+        {"$filter": {
+            "input": "$internal_product_variants",
+            "cond": {"$not": [{"$in": ["$$this._id", &removed_product_variant_ids]}]},
+        }},
+        added_product_variants,
+    ]};
+    let mut filter = doc! {"_id": id};
+    if let Some(max_items_per_wishlist) = max_items_per_wishlist {
+        filter.insert(
+            "$expr",
+            doc! {"$lte": [{"$size": &resulting_product_variants}, max_items_per_wishlist as i64]},
+        );
+    }
+    let update = vec![
+        doc! {"$set": {
+            "internal_product_variants": resulting_product_variants,
+            "last_updated_at": current_timestamp,
+        }},
+        Wishlist::product_variant_dependent_fields_stage(),
+    ];
+    match collection.update_one(filter, update, None).await {
+        Ok(result) if result.matched_count > 0 => Ok(()),
+        Ok(_) => match max_items_per_wishlist {
+            Some(max_items_per_wishlist) => Err(item_quota_exceeded_error(max_items_per_wishlist)),
+            None => {
+                let message = format!("Wishlist with the UUID: `{}` not found.", id);
+                Err(Error::new(message))
+            }
+        },
+        Err(_) => {
+            let message = format!(
+                "Updating product_variant_ids of wishlist of id: `{}` failed in MongoDB.",
+                id
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
 /// Updates name of a wishlist.
 ///
 /// * `collection` - MongoDB collection to update.
@@ -165,7 +2015,7 @@ async fn update_name(
                 None,
             )
             .await;
-        if let Err(_) = result {
+        if result.is_err() {
             let message = format!(
                 "Updating name of wishlist of id: `{}` failed in MongoDB.",
                 input.id
@@ -176,52 +2026,608 @@ async fn update_name(
     Ok(())
 }
 
+/// Updates whether the user-provided text of a wishlist is flagged for review.
+///
+/// * `collection` - MongoDB collection to update.
+/// * `id` - UUID of wishlist to update.
+/// * `content_flagged` - Whether the text of the wishlist is flagged.
+async fn update_content_flagged(
+    collection: &Collection<Wishlist>,
+    id: Uuid,
+    content_flagged: bool,
+) -> Result<()> {
+    match collection
+        .update_one(
+            doc! {"_id": id},
+            doc! {"$set": {"content_flagged": content_flagged}},
+            None,
+        )
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            let message = format!(
+                "Updating content flag of wishlist of id: `{}` failed in MongoDB.",
+                id
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Collects the user-provided texts of a wishlist checked by the content filters, referenced by field name.
+///
+/// * `name` - Name of wishlist.
+/// * `description` - Description of wishlist.
+fn wishlist_texts<'a>(name: &'a str, description: Option<&'a str>) -> Vec<(&'static str, &'a str)> {
+    let mut texts = vec![("name", name)];
+    if let Some(description) = description {
+        texts.push(("description", description));
+    }
+    texts
+}
+
+/// Updates or removes optional fields of a wishlist.
+///
+/// Fields which are `null` in the input are removed, undefined fields are left untouched.
+///
+/// * `collection` - MongoDB collection to update.
+/// * `settings` - Service settings defining the validation of optional fields.
+/// * `input` - Update wishlist input containing optional fields.
+/// * `current_timestamp` - Timestamp of update.
+async fn update_optional_fields(
+    collection: &Collection<Wishlist>,
+    settings: &Settings,
+    input: &UpdateWishlistInput,
+    current_timestamp: &DateTime,
+) -> Result<()> {
+    if let MaybeUndefined::Value(cover_image_url) = &input.cover_image_url {
+        validate_cover_image_url(settings, cover_image_url)?;
+    }
+    if let MaybeUndefined::Value(icon) = &input.icon {
+        validate_icon(settings, icon)?;
+    }
+    if let MaybeUndefined::Value(color) = &input.color {
+        validate_color(color)?;
+    }
+    if let MaybeUndefined::Value(expires_at) = &input.expires_at {
+        validate_expires_at(expires_at)?;
+    }
+    let mut set_doc = Document::new();
+    let mut unset_doc = Document::new();
+    add_optional_field_update(
+        &mut set_doc,
+        &mut unset_doc,
+        "description",
+        &input.description,
+    );
+    add_optional_field_update(
+        &mut set_doc,
+        &mut unset_doc,
+        "occasion_date",
+        &input.occasion_date,
+    );
+    add_optional_field_update(
+        &mut set_doc,
+        &mut unset_doc,
+        "cover_image_url",
+        &input.cover_image_url,
+    );
+    add_optional_field_update(&mut set_doc, &mut unset_doc, "icon", &input.icon);
+    let color = match &input.color {
+        MaybeUndefined::Value(color) => MaybeUndefined::Value(color.to_lowercase()),
+        MaybeUndefined::Null => MaybeUndefined::Null,
+        MaybeUndefined::Undefined => MaybeUndefined::Undefined,
+    };
+    add_optional_field_update(&mut set_doc, &mut unset_doc, "color", &color);
+    add_optional_field_update(
+        &mut set_doc,
+        &mut unset_doc,
+        "expires_at",
+        &input.expires_at,
+    );
+    if set_doc.is_empty() && unset_doc.is_empty() {
+        return Ok(());
+    }
+    set_doc.insert("last_updated_at", current_timestamp);
+    let mut update = doc! {"$set": set_doc};
+    if !unset_doc.is_empty() {
+        update.insert("$unset", unset_doc);
+    }
+    let result = collection
+        .update_one(doc! {"_id": input.id }, update, None)
+        .await;
+    if result.is_err() {
+        let message = format!(
+            "Updating optional fields of wishlist of id: `{}` failed in MongoDB.",
+            input.id
+        );
+        return Err(Error::new(message));
+    }
+    Ok(())
+}
+
+/// Adds the update of an optional field to the `$set` or `$unset` document of an update.
+///
+/// * `set_doc` - Document of the `$set` operator.
+/// * `unset_doc` - Document of the `$unset` operator.
+/// * `field` - Name of field in MongoDB.
+/// * `value` - Value of field in update input.
+fn add_optional_field_update<T: Clone + Into<Bson>>(
+    set_doc: &mut Document,
+    unset_doc: &mut Document,
+    field: &str,
+    value: &MaybeUndefined<T>,
+) {
+    match value {
+        MaybeUndefined::Value(value) => {
+            set_doc.insert(field, value.clone());
+        }
+        MaybeUndefined::Null => {
+            unset_doc.insert(field, "");
+        }
+        MaybeUndefined::Undefined => {}
+    }
+}
+
 /// Checks if product variants and user in create wishlist input are in the system (MongoDB database populated with events).
 ///
+/// Failing checks are handled according to the validation strictness of the service settings.
+///
 /// * `db_client` - MongoDB database client.
+/// * `settings` - Service settings defining the product variant validation.
+/// * `dapr_client` - Dapr client used for product variant validation against the catalog service.
+/// * `state_cache` - Cache of product variant lookups in the catalog service.
 /// * `input` - Create wishlist input containing product variants.
-async fn validate_input(db_client: &Database, input: &CreateWishlistInput) -> Result<()> {
+async fn validate_input(
+    db_client: &Database,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
+    input: &CreateWishlistInput,
+) -> Result<()> {
     let product_variant_collection: Collection<ProductVariant> =
         db_client.collection::<ProductVariant>("product_variants");
     let user_collection: Collection<User> = db_client.collection::<User>("users");
-    validate_product_variant_ids(&product_variant_collection, &input.product_variant_ids).await?;
-    validate_user(&user_collection, input.user_id).await?;
-    Ok(())
+    let validation = async {
+        validate_product_variant_ids(
+            &product_variant_collection,
+            settings,
+            dapr_client,
+            state_cache,
+            &input.product_variant_ids,
+        )
+        .await?;
+        validate_user(&user_collection, input.user_id).await
+    };
+    validate_with_strictness(settings.validation_strictness, validation).await
+}
+
+/// Checks if a user can create another wishlist without exceeding the maximum number of wishlists per user.
+///
+/// Early check before the input is validated, the quota is enforced atomically by `insert_wishlist_within_quota`.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `settings` - Service settings defining the maximum number of wishlists per user.
+/// * `tenant_id` - Tenant the wishlist is created in.
+/// * `user_id` - UUID of user creating the wishlist.
+async fn validate_wishlist_quota(
+    collection: &Collection<Wishlist>,
+    settings: &Settings,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+) -> Result<()> {
+    let max_wishlists_per_user = match settings.max_wishlists_per_user {
+        Some(max_wishlists_per_user) => max_wishlists_per_user,
+        None => return Ok(()),
+    };
+    let wishlist_count = collection
+        .count_documents(tenant_id.scope(doc! {"user._id": user_id}), None)
+        .await
+        .map_err(|_| Error::new("Counting wishlists of user failed in MongoDB."))?;
+    match wishlist_count < max_wishlists_per_user {
+        true => Ok(()),
+        false => Err(wishlist_quota_exceeded_error(
+            user_id,
+            max_wishlists_per_user,
+        )),
+    }
+}
+
+/// Checks within a transaction creating a wishlist whether the user can create another wishlist.
+///
+/// Writes the quota lock document of the user first, so concurrent transactions creating wishlists of the same user
+/// conflict on it instead of both passing the count.
+///
+/// * `db_client` - MongoDB database of the tenant.
+/// * `tenant_id` - Tenant the wishlist is created in.
+/// * `user_id` - UUID of user creating the wishlist.
+/// * `max_wishlists_per_user` - Maximum number of wishlists per user.
+/// * `session` - MongoDB session with active transaction.
+async fn is_within_wishlist_quota(
+    db_client: &Database,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+    max_wishlists_per_user: u64,
+    session: &mut ClientSession,
+) -> mongodb::error::Result<bool> {
+    let options = UpdateOptions::builder().upsert(true).build();
+    db_client
+        .collection::<Document>(WISHLIST_QUOTA_LOCK_COLLECTION)
+        .update_one_with_session(
+            doc! {"_id": user_id},
+            doc! {"$inc": {"creations": 1}},
+            options,
+            session,
+        )
+        .await?;
+    let wishlist_count = db_client
+        .collection::<Wishlist>("wishlists")
+        .count_documents_with_session(tenant_id.scope(doc! {"user._id": user_id}), None, session)
+        .await?;
+    Ok(wishlist_count < max_wishlists_per_user)
+}
+
+/// Inserts a new wishlist, enforcing the maximum number of wishlists per user atomically if it is set.
+///
+/// The count and the insertion run in a MongoDB transaction, see `is_within_wishlist_quota`,
+/// which is retried if it conflicts with a concurrent write, e.g. a creation of the same user.
+/// A new wishlist split from another wishlist is always written in a transaction together with the split, see `write_split`.
+///
+/// * `ctx` - GraphQL context containing the MongoDB client.
+/// * `db_client` - MongoDB database of the tenant.
+/// * `settings` - Service settings defining the maximum number of wishlists per user.
+/// * `tenant_id` - Tenant the wishlist is created in.
+/// * `wishlist` - Wishlist to insert.
+/// * `split_from` - UUID of the wishlist whose product variants are moved into the new wishlist, `None` if not split.
+async fn insert_wishlist_within_quota(
+    ctx: &Context<'_>,
+    db_client: &Database,
+    settings: &Settings,
+    tenant_id: &TenantId,
+    wishlist: &Wishlist,
+    split_from: Option<Uuid>,
+) -> Result<()> {
+    let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+    let message = match split_from {
+        Some(split_from) => format!(
+            "Splitting wishlist of id: `{}` failed in MongoDB.",
+            split_from
+        ),
+        None => "Adding wishlist failed in MongoDB.".to_string(),
+    };
+    if settings.max_wishlists_per_user.is_none() && split_from.is_none() {
+        return match collection.insert_one(wishlist, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Error::new(message)),
+        };
+    }
+    let mut session = match ctx.data::<Client>()?.start_session(None).await {
+        Ok(session) => session,
+        Err(_) => return Err(Error::new("Starting MongoDB session failed.")),
+    };
+    for _ in 0..MAX_QUOTA_TRANSACTION_ATTEMPTS {
+        if session.start_transaction(None).await.is_err() {
+            return Err(Error::new("Starting MongoDB transaction failed."));
+        }
+        let within_wishlist_quota = match settings.max_wishlists_per_user {
+            Some(max_wishlists_per_user) => {
+                is_within_wishlist_quota(
+                    db_client,
+                    tenant_id,
+                    wishlist.user._id,
+                    max_wishlists_per_user,
+                    &mut session,
+                )
+                .await
+            }
+            None => Ok(true),
+        };
+        let result = match (within_wishlist_quota, split_from) {
+            (Ok(true), Some(split_from)) => {
+                write_split(&collection, split_from, wishlist, &mut session)
+                    .await
+                    .map(|_| true)
+            }
+            (Ok(true), None) => collection
+                .insert_one_with_session(wishlist, None, &mut session)
+                .await
+                .map(|_| true),
+            (result, _) => result,
+        };
+        let transaction_result = match result {
+            Ok(true) => commit_transaction(&mut session).await.map(|_| true),
+            Ok(false) => session.abort_transaction().await.map(|_| false),
+            Err(error) => {
+                let _ = session.abort_transaction().await;
+                Err(error)
+            }
+        };
+        match (transaction_result, settings.max_wishlists_per_user) {
+            (Ok(true), _) => return Ok(()),
+            (Ok(false), Some(max_wishlists_per_user)) => {
+                return Err(wishlist_quota_exceeded_error(
+                    wishlist.user._id,
+                    max_wishlists_per_user,
+                ))
+            }
+            (Err(error), _) if error.contains_label(TRANSIENT_TRANSACTION_ERROR) => continue,
+            _ => break,
+        }
+    }
+    Err(Error::new(message))
+}
+
+/// Commits the transaction of a session, retrying the commit while its result is unknown.
+///
+/// * `session` - MongoDB session of the transaction.
+async fn commit_transaction(session: &mut ClientSession) -> mongodb::error::Result<()> {
+    let mut result = session.commit_transaction().await;
+    for _ in 1..MAX_QUOTA_TRANSACTION_ATTEMPTS {
+        match &result {
+            Err(error) if error.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) => {
+                result = session.commit_transaction().await;
+            }
+            _ => break,
+        }
+    }
+    result
+}
+
+/// Checks if a number of product variants does not exceed the maximum number of product variants per wishlist.
+///
+/// * `settings` - Service settings defining the maximum number of product variants per wishlist.
+/// * `item_count` - Number of product variants of the wishlist.
+fn validate_item_quota(settings: &Settings, item_count: usize) -> Result<()> {
+    match settings.max_items_per_wishlist {
+        Some(max_items_per_wishlist) if item_count as u64 > max_items_per_wishlist => {
+            Err(item_quota_exceeded_error(max_items_per_wishlist))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Constructs the error of a user who reached the maximum number of wishlists per user.
+///
+/// * `user_id` - UUID of the user.
+/// * `max_wishlists_per_user` - Maximum number of wishlists per user.
+fn wishlist_quota_exceeded_error(user_id: Uuid, max_wishlists_per_user: u64) -> Error {
+    let message = format!(
+        "User of UUID: `{}` reached the maximum number of wishlists: `{}`.",
+        user_id, max_wishlists_per_user
+    );
+    quota_exceeded_error(message)
+}
+
+/// Constructs the error of a wishlist which would exceed the maximum number of product variants per wishlist.
+///
+/// * `max_items_per_wishlist` - Maximum number of product variants per wishlist.
+fn item_quota_exceeded_error(max_items_per_wishlist: u64) -> Error {
+    let message = format!(
+        "Wishlist exceeds the maximum number of product variants: `{}`.",
+        max_items_per_wishlist
+    );
+    quota_exceeded_error(message)
+}
+
+/// Checks that a wishlist is not suspended, as its user is disabled. Admins can modify suspended wishlists.
+///
+/// Fails with the error code `WISHLIST_SUSPENDED`.
+///
+/// * `ctx` - GraphQL context containing the caller.
+/// * `wishlist` - Wishlist to modify.
+fn check_not_suspended(ctx: &Context<'_>, wishlist: &Wishlist) -> Result<()> {
+    match wishlist.suspended && !is_admin(ctx) {
+        true => {
+            let message = format!(
+                "Wishlist of UUID: `{}` is suspended, as its user is disabled.",
+                wishlist._id
+            );
+            Err(Error::new(message)
+                .extend_with(|_, extensions| extensions.set("code", "WISHLIST_SUSPENDED")))
+        }
+        false => Ok(()),
+    }
+}
+
+/// Builds an error with code `QUOTA_EXCEEDED`.
+///
+/// * `message` - Error message.
+fn quota_exceeded_error(message: String) -> Error {
+    Error::new(message).extend_with(|_, extensions| extensions.set("code", "QUOTA_EXCEEDED"))
+}
+
+/// Checks that an uploaded file does not exceed the maximum upload size.
+///
+/// * `settings` - Service settings defining the maximum upload size.
+/// * `upload` - Uploaded file.
+fn validate_upload_size(settings: &Settings, upload: &UploadValue) -> Result<()> {
+    let size = upload
+        .size()
+        .map_err(|_| Error::new("Reading uploaded file failed."))?;
+    match size <= settings.max_upload_bytes {
+        true => Ok(()),
+        false => {
+            let message = format!(
+                "Uploaded file exceeds the maximum size of `{}` bytes.",
+                settings.max_upload_bytes
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Validates a batch of product variants at once, returning the validation error of each invalid product variant.
+///
+/// The batch is looked up in a single MongoDB query, product variants missing in the MongoDB database
+/// are looked up in the catalog service concurrently, see `validate_product_variant_in_catalog`.
+/// Failed validations are handled according to the validation strictness, see `validate_with_strictness`.
+///
+/// * `collection` - MongoDB collection to validate against.
+/// * `settings` - Service settings defining the product variant validation.
+/// * `dapr_client` - Dapr client used for product variant validation against the catalog service.
+/// * `state_cache` - Cache of product variant lookups in the catalog service.
+/// * `product_variant_ids` - Product variant UUIDs to validate.
+async fn validate_product_variant_batch(
+    collection: &Collection<ProductVariant>,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
+    product_variant_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Error>> {
+    if settings.validation_strictness == ValidationStrictness::Off {
+        return Ok(HashMap::new());
+    }
+    let message = "Retrieving product variants failed in MongoDB.";
+    let present_product_variants: Vec<ProductVariant> = match collection
+        .find(doc! {"_id": {"$in": product_variant_ids}}, None)
+        .await
+    {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let present_product_variant_ids: HashSet<Uuid> = present_product_variants
+        .into_iter()
+        .map(|product_variant| product_variant._id)
+        .collect();
+    let catalog_validations = product_variant_ids
+        .iter()
+        .filter(|id| !present_product_variant_ids.contains(id))
+        .map(|id| async move {
+            let validation = validate_product_variant_in_catalog(
+                collection,
+                settings,
+                dapr_client,
+                state_cache,
+                *id,
+            )
+            .await;
+            (*id, validation)
+        });
+    let mut errors = HashMap::new();
+    for (id, validation) in join_all(catalog_validations).await {
+        if let Err(error) = validation {
+            match settings.validation_strictness {
+                ValidationStrictness::Strict => {
+                    errors.insert(id, error);
+                }
+                _ => warn!("Validation failed, continuing mutation: {}", error.message),
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Runs a validation according to a validation strictness.
+///
+/// `ValidationStrictness::Warn` logs failed validations instead of returning an error.
+/// `ValidationStrictness::Off` skips the validation.
+///
+/// * `strictness` - Validation strictness to apply.
+/// * `validation` - Validation to run.
+async fn validate_with_strictness(
+    strictness: ValidationStrictness,
+    validation: impl Future<Output = Result<()>>,
+) -> Result<()> {
+    match strictness {
+        ValidationStrictness::Strict => validation.await,
+        ValidationStrictness::Warn => {
+            if let Err(error) = validation.await {
+                warn!("Validation failed, continuing mutation: {}", error.message);
+            }
+            Ok(())
+        }
+        ValidationStrictness::Off => Ok(()),
+    }
 }
 
 /// Checks if product variants are in the system (MongoDB database populated with events).
 ///
 /// Used before adding or modifying product variants / wishlists.
+/// Product variants missing in the MongoDB database are looked up in the catalog service if catalog fallback validation is enabled.
 ///
 /// * `collection` - MongoDB collection to validate against.
+/// * `settings` - Service settings defining the product variant validation.
+/// * `dapr_client` - Dapr client used for product variant validation against the catalog service.
 /// * `product_variant_ids` - Product variant UUIDs to validate.
 async fn validate_product_variant_ids(
     collection: &Collection<ProductVariant>,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
     product_variant_ids: &HashSet<Uuid>,
 ) -> Result<()> {
     let product_variant_ids_vec: Vec<Uuid> = product_variant_ids.clone().into_iter().collect();
-    match collection
+    let product_variants: Vec<ProductVariant> = match collection
         .find(doc! {"_id": { "$in": &product_variant_ids_vec } }, None)
         .await
     {
-        Ok(cursor) => {
-            let product_variants: Vec<ProductVariant> = cursor.try_collect().await?;
-            product_variant_ids_vec.iter().fold(Ok(()), |_, p| {
-                match product_variants.contains(&ProductVariant { _id: *p }) {
-                    true => Ok(()),
-                    false => {
-                        let message = format!(
-                            "Product variant with the UUID: `{}` is not present in the system.",
-                            p
-                        );
-                        Err(Error::new(message))
-                    }
-                }
-            })
+        Ok(cursor) => cursor.try_collect().await?,
+        Err(_) => {
+            return Err(Error::new(
+                "Product variants with the specified UUIDs are not present in the system.",
+            ))
+        }
+    };
+    for id in product_variant_ids_vec {
+        if !product_variants.contains(&ProductVariant { _id: id }) {
+            validate_product_variant_in_catalog(collection, settings, dapr_client, state_cache, id)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks if a product variant, which is missing in the MongoDB database, is present in the catalog service.
+///
+/// Only performed if catalog fallback validation is enabled.
+/// A product variant found in the catalog service is cached in the MongoDB database.
+///
+/// * `collection` - MongoDB collection to cache product variant in.
+/// * `settings` - Service settings defining the product variant validation.
+/// * `dapr_client` - Dapr client used for the service invocation of the catalog service.
+/// * `state_cache` - Cache of product variant lookups in the catalog service.
+/// * `id` - Product variant UUID to validate.
+async fn validate_product_variant_in_catalog(
+    collection: &Collection<ProductVariant>,
+    settings: &Settings,
+    dapr_client: &DaprClient,
+    state_cache: &StateCache,
+    id: Uuid,
+) -> Result<()> {
+    let message = format!(
+        "Product variant with the UUID: `{}` is not present in the system.",
+        id
+    );
+    if !settings.catalog_fallback_validation {
+        return Err(Error::new(message));
+    }
+    let lookup = product_variant_exists_in_catalog(dapr_client, &settings.catalog_app_id, id);
+    match state_cache
+        .get_or_load(&catalog_product_variant_key(id), lookup)
+        .await
+    {
+        Ok(true) => {
+            if collection
+                .insert_one(ProductVariant { _id: id }, None)
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Caching product variant of UUID: `{}` failed in MongoDB.",
+                    id
+                );
+            }
+            Ok(())
+        }
+        Ok(false) => Err(Error::new(message)),
+        Err(error) => {
+            warn!("{}", error.message);
+            Err(Error::new(message))
         }
-        Err(_) => Err(Error::new(
-            "Product variants with the specified UUIDs are not present in the system.",
-        )),
     }
 }
 
@@ -232,5 +2638,5 @@ async fn validate_product_variant_ids(
 /// * `collection` - MongoDB collection to validate against.
 /// * `id` - User UUID to validate.
 async fn validate_user(collection: &Collection<User>, id: Uuid) -> Result<()> {
-    query_object(&collection, id).await.map(|_| ())
+    query_object(collection, id).await.map(|_| ())
 }
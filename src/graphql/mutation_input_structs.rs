@@ -1,7 +1,9 @@
-use async_graphql::{InputObject, SimpleObject};
-use bson::Uuid;
+use crate::graphql::model::uuid::Uuid;
+use async_graphql::{Enum, InputObject, MaybeUndefined, OneofObject, SimpleObject, Upload};
 use std::collections::HashSet;
 
+use super::model::{date_time::DateTime, order_types::WishlistOrderInput};
+
 #[derive(SimpleObject, InputObject)]
 pub struct CreateWishlistInput {
     /// UUID of user owning the wishlist.
@@ -10,14 +12,107 @@ pub struct CreateWishlistInput {
     pub product_variant_ids: HashSet<Uuid>,
     /// Wishlist name.
     pub name: String,
+    /// Wishlist description.
+    pub description: Option<String>,
+    /// Date of the occasion the wishlist is intended for.
+    pub occasion_date: Option<DateTime>,
+    /// URL of the cover image of the wishlist.
+    pub cover_image_url: Option<String>,
+    /// Icon of the wishlist, a single emoji.
+    pub icon: Option<String>,
+    /// Theme color of the wishlist, a hex color of the form `#RGB` or `#RRGGBB`.
+    pub color: Option<String>,
+    /// Timestamp after which the wishlist is archived.
+    pub expires_at: Option<DateTime>,
+    /// Whether an existing wishlist of the user with the same name and identical product variants is returned instead of creating a new one.
+    pub deduplicate: Option<bool>,
 }
 
-#[derive(SimpleObject, InputObject)]
+#[derive(InputObject)]
 pub struct UpdateWishlistInput {
     /// UUID of wishlist to update.
     pub id: Uuid,
     /// product variant UUIDs of wishlist to update
     pub product_variant_ids: Option<HashSet<Uuid>>,
+    /// Operations adding or removing single product variants, applied in order. Can not be combined with `productVariantIds`.
+    pub item_operations: Option<Vec<ItemOperationInput>>,
     /// Wishlist name to update
     pub name: Option<String>,
+    /// Wishlist description to update, `null` removes the description.
+    pub description: MaybeUndefined<String>,
+    /// Occasion date to update, `null` removes the occasion date.
+    pub occasion_date: MaybeUndefined<DateTime>,
+    /// Cover image URL to update, `null` removes the cover image.
+    pub cover_image_url: MaybeUndefined<String>,
+    /// Icon to update, `null` removes the icon.
+    pub icon: MaybeUndefined<String>,
+    /// Theme color to update, `null` removes the theme color.
+    pub color: MaybeUndefined<String>,
+    /// Expiration timestamp to update, `null` removes the expiration.
+    pub expires_at: MaybeUndefined<DateTime>,
+}
+
+/// Wishlist to import from a CSV file.
+#[derive(InputObject)]
+pub struct ImportWishlistFromCsvInput {
+    /// UUID of user owning the wishlist.
+    pub user_id: Uuid,
+    /// Wishlist name.
+    pub name: String,
+    /// CSV file with rows of the form `product_variant_id[,quantity,note]`.
+    pub file: Upload,
+}
+
+/// File of product variants to add to a wishlist.
+#[derive(InputObject)]
+pub struct AddItemsFromUploadInput {
+    /// UUID of wishlist to add the product variants to.
+    pub wishlist_id: Uuid,
+    /// File with one product variant UUID per line.
+    pub file: Upload,
+}
+
+/// Operation adding or removing a single product variant of a wishlist.
+#[derive(InputObject)]
+pub struct ItemOperationInput {
+    /// Type of the operation.
+    pub op: ItemOperationType,
+    /// UUID of the product variant to add or remove.
+    pub product_variant_id: Uuid,
+}
+
+/// Type of an operation on the product variants of a wishlist.
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ItemOperationType {
+    /// Adds the product variant, if not already contained.
+    Add,
+    /// Removes the product variant, if contained.
+    Remove,
+}
+
+/// Version of a wishlist in its audit history, selected by number or by timestamp.
+#[derive(OneofObject)]
+pub enum WishlistVersionSelector {
+    /// Number of the version, the creation of the wishlist is version 1.
+    Version(u64),
+    /// Selects the last version created at or before this timestamp.
+    Timestamp(DateTime),
+}
+
+/// Patch applied to all wishlists matching the filter of a bulk update.
+#[derive(InputObject)]
+pub struct BulkWishlistPatchInput {
+    /// UUIDs of product variants to remove from the wishlists, e.g. of a recalled product variant.
+    pub remove_product_variant_ids: Option<HashSet<Uuid>>,
+    /// Archives the wishlists if `true`, restores archived wishlists if `false`.
+    pub archived: Option<bool>,
+}
+
+/// Preferences of the calling user to update.
+#[derive(InputObject)]
+pub struct UpdateUserPreferencesInput {
+    /// Order of wishlists if no order is requested, `null` removes the default order.
+    pub default_wishlist_order: MaybeUndefined<WishlistOrderInput>,
+    /// Whether the periodic digest of the wishlist activity is enabled.
+    pub digest_enabled: Option<bool>,
 }
@@ -0,0 +1,120 @@
+use crate::graphql::model::{uuid::Uuid, wishlist::Wishlist};
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+
+/// Report of removing references to product variants which are no longer present in the system.
+#[derive(SimpleObject)]
+pub struct CleanupOrphanedProductVariantsPayload {
+    /// UUIDs of removed product variants, or of product variants which would be removed by a dry run.
+    pub removed_product_variant_ids: Vec<Uuid>,
+    /// UUIDs of wishlists which referenced removed product variants.
+    pub affected_wishlist_ids: Vec<Uuid>,
+}
+
+/// Result of reprocessing failed events.
+#[derive(SimpleObject)]
+pub struct ReprocessFailedEventsPayload {
+    /// Identifiers of events which were handled successfully and removed from the failed events.
+    pub reprocessed_event_ids: Vec<String>,
+    /// Identifiers of events which failed again or are not among the failed events.
+    pub failed_event_ids: Vec<String>,
+}
+
+/// Result of adding the users and product variants missing in the local projections.
+#[derive(SimpleObject)]
+pub struct ReconcileForeignProjectionsPayload {
+    /// Number of added users.
+    pub added_user_count: u64,
+    /// Number of added product variants.
+    pub added_product_variant_count: u64,
+}
+
+/// Result of deleting a wishlist.
+#[derive(SimpleObject, Serialize, Deserialize)]
+pub struct DeleteWishlistPayload {
+    /// Whether the wishlist was deleted, or would be deleted by a dry run. `false` if the wishlist did not exist.
+    pub success: bool,
+    /// Number of deleted wishlists, 0 if the wishlist did not exist, or the number which would be deleted by a dry run.
+    pub deleted_count: u64,
+    /// UUID of the wishlist to delete.
+    pub id: Uuid,
+}
+
+impl DeleteWishlistPayload {
+    /// Constructs the result of a deletion, deriving its success from the number of deleted wishlists.
+    ///
+    /// * `id` - UUID of the wishlist to delete.
+    /// * `deleted_count` - Number of deleted wishlists.
+    pub fn new(id: Uuid, deleted_count: u64) -> Self {
+        Self {
+            success: deleted_count > 0,
+            deleted_count,
+            id,
+        }
+    }
+}
+
+/// Result of patching all wishlists matching a filter.
+#[derive(SimpleObject)]
+pub struct BulkUpdateWishlistsPayload {
+    /// Number of wishlists matching the filter.
+    pub matched_count: u64,
+    /// Number of wishlists changed by the patch.
+    pub modified_count: u64,
+}
+
+/// Result of moving product variants of a wishlist into a new wishlist.
+#[derive(SimpleObject)]
+pub struct SplitWishlistPayload {
+    /// Split wishlist without the moved product variants.
+    pub wishlist: Wishlist,
+    /// New wishlist containing the moved product variants.
+    pub new_wishlist: Wishlist,
+}
+
+/// Result of moving the ownership of all wishlists of a user to another user.
+#[derive(SimpleObject)]
+pub struct ReassignWishlistsPayload {
+    /// UUIDs of reassigned wishlists.
+    pub reassigned_wishlist_ids: Vec<Uuid>,
+    /// UUIDs of reassigned wishlists which were renamed to resolve name collisions.
+    pub renamed_wishlist_ids: Vec<Uuid>,
+}
+
+/// Result of importing a wishlist from a CSV file.
+#[derive(SimpleObject, Serialize, Deserialize)]
+pub struct ImportWishlistFromCsvPayload {
+    /// Imported wishlist, `null` if any row is invalid.
+    pub wishlist: Option<Wishlist>,
+    /// Errors of the invalid rows of the CSV file.
+    pub row_errors: Vec<CsvRowError>,
+}
+
+/// Error of an invalid row of a CSV file.
+#[derive(SimpleObject, Serialize, Deserialize)]
+pub struct CsvRowError {
+    /// Number of the row in the file, starting at 1.
+    pub row: u64,
+    /// Description of the problem of the row.
+    pub message: String,
+}
+
+/// Result of adding the product variants of an uploaded file to a wishlist.
+#[derive(SimpleObject, Serialize, Deserialize)]
+pub struct AddItemsFromUploadPayload {
+    /// Wishlist after adding the product variants of the valid lines.
+    pub wishlist: Wishlist,
+    /// Number of product variants added, excluding product variants already contained in the wishlist.
+    pub added_count: u64,
+    /// Errors of the invalid lines of the file.
+    pub line_errors: Vec<UploadLineError>,
+}
+
+/// Error of an invalid line of an uploaded file.
+#[derive(SimpleObject, Serialize, Deserialize)]
+pub struct UploadLineError {
+    /// Number of the line in the file, starting at 1.
+    pub line: u64,
+    /// Description of the problem of the line.
+    pub message: String,
+}
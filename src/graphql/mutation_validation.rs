@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use async_graphql::{Context, Error, ErrorExtensions, Result};
+
+use super::model::wishlist::Wishlist;
+use super::mutation_input_structs::{CreateWishlistInput, UpdateWishlistInput};
+
+/// Wishlist mutation checked by mutation validators.
+pub enum WishlistMutation<'a> {
+    /// Creation of a wishlist, including imports.
+    Create(&'a CreateWishlistInput),
+    /// Update of an existing wishlist.
+    Update {
+        /// Wishlist before the update.
+        wishlist: &'a Wishlist,
+        /// Requested changes.
+        input: &'a UpdateWishlistInput,
+    },
+}
+
+/// Custom rule checked before a wishlist mutation is stored, e.g. a length rule, a content policy or a business rule.
+///
+/// Deployments implement validators and register them on schema construction, see `MutationValidators`.
+#[async_trait::async_trait]
+pub trait MutationValidator: Send + Sync {
+    /// Checks a mutation, an error rejects the mutation before anything is stored.
+    ///
+    /// * `ctx` - GraphQL context of the mutation, containing the caller and the service data.
+    /// * `mutation` - Mutation to check.
+    async fn validate(&self, ctx: &Context<'_>, mutation: &WishlistMutation<'_>) -> Result<()>;
+}
+
+/// Mutation validators registered on schema construction, applied in the order of registration.
+#[derive(Clone, Default)]
+pub struct MutationValidators(Vec<Arc<dyn MutationValidator>>);
+
+impl MutationValidators {
+    /// Registers a validator, which is applied after the previously registered validators.
+    ///
+    /// * `validator` - Validator to register.
+    pub fn with(mut self, validator: impl MutationValidator + 'static) -> Self {
+        self.0.push(Arc::new(validator));
+        self
+    }
+
+    /// Applies all validators to a mutation, failing with the error of the first failed validator.
+    ///
+    /// * `ctx` - GraphQL context of the mutation.
+    /// * `mutation` - Mutation to check.
+    pub async fn validate(&self, ctx: &Context<'_>, mutation: &WishlistMutation<'_>) -> Result<()> {
+        for validator in &self.0 {
+            validator.validate(ctx, mutation).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Validator limiting the number of characters of the name and description of wishlists.
+///
+/// Unchanged texts of updated wishlists are not checked, so lowering a limit does not block updates of other fields.
+pub struct MaxLengthValidator {
+    /// Maximum number of characters of wishlist names, unlimited if `None`.
+    pub max_name_length: Option<usize>,
+    /// Maximum number of characters of wishlist descriptions, unlimited if `None`.
+    pub max_description_length: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl MutationValidator for MaxLengthValidator {
+    async fn validate(&self, _ctx: &Context<'_>, mutation: &WishlistMutation<'_>) -> Result<()> {
+        let (name, description) = match mutation {
+            WishlistMutation::Create(input) => {
+                (Some(input.name.as_str()), input.description.as_deref())
+            }
+            WishlistMutation::Update { wishlist, input } => (
+                input.name.as_deref().filter(|name| *name != wishlist.name),
+                input
+                    .description
+                    .as_opt_deref()
+                    .flatten()
+                    .filter(|description| Some(*description) != wishlist.description.as_deref()),
+            ),
+        };
+        check_length("name", name, self.max_name_length)?;
+        check_length("description", description, self.max_description_length)
+    }
+}
+
+/// Checks that a text does not exceed a maximum number of characters.
+///
+/// * `field` - Name of the field of the text.
+/// * `text` - Text to check, not checked if `None`.
+/// * `max_length` - Maximum number of characters, unlimited if `None`.
+fn check_length(field: &str, text: Option<&str>, max_length: Option<usize>) -> Result<()> {
+    match (text, max_length) {
+        (Some(text), Some(max_length)) if text.chars().count() > max_length => {
+            let message = format!(
+                "Field `{}` exceeds the maximum length of {} characters.",
+                field, max_length
+            );
+            Err(Error::new(message).extend_with(|_, extensions| {
+                extensions.set("code", "VALIDATION_FAILED");
+                extensions.set("field", field);
+            }))
+        }
+        _ => Ok(()),
+    }
+}
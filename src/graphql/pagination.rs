@@ -0,0 +1,233 @@
+use std::fmt;
+
+use async_graphql::{Error, ErrorExtensions, Result};
+use bson::{doc, Document};
+
+use crate::graphql::model::{date_time::DateTime, uuid::Uuid};
+use crate::settings::Settings;
+
+/// Error code of requests exceeding the maximum page size.
+const PAGE_SIZE_EXCEEDED: &str = "PAGE_SIZE_EXCEEDED";
+
+/// Error code of requests exceeding the maximum offset.
+const OFFSET_EXCEEDED: &str = "OFFSET_EXCEEDED";
+
+/// Determines the number of entities retrieved by a connection query.
+///
+/// Falls back to the default page size if no page size is requested.
+/// Returns an error with code `PAGE_SIZE_EXCEEDED` if the requested page size exceeds the maximum page size.
+///
+/// * `settings` - Service settings defining the default and maximum page size.
+/// * `first` - Requested page size.
+pub fn page_size(settings: &Settings, first: Option<u64>) -> Result<u64> {
+    match first {
+        Some(first) if first > settings.max_page_size => {
+            let message = format!(
+                "Requested page size: `{}` exceeds the maximum page size: `{}`.",
+                first, settings.max_page_size
+            );
+            Err(Error::new(message).extend_with(|_, extensions| {
+                extensions.set("code", PAGE_SIZE_EXCEEDED);
+                extensions.set("maxPageSize", settings.max_page_size);
+            }))
+        }
+        Some(first) => Ok(first),
+        None => Ok(settings.default_page_size),
+    }
+}
+
+/// Determines the number of entities skipped by a connection query.
+///
+/// Returns an error with code `OFFSET_EXCEEDED` if the requested offset exceeds the maximum offset,
+/// as MongoDB scans all skipped documents.
+///
+/// * `settings` - Service settings defining the maximum offset.
+/// * `offset` - Requested offset.
+pub fn offset(settings: &Settings, offset: Option<u64>) -> Result<u64> {
+    match offset {
+        Some(offset) if offset > settings.max_offset => {
+            let message = format!(
+                "Requested offset: `{}` exceeds the maximum offset: `{}`.",
+                offset, settings.max_offset
+            );
+            Err(Error::new(message).extend_with(|_, extensions| {
+                extensions.set("code", OFFSET_EXCEEDED);
+                extensions.set("maxOffset", settings.max_offset);
+            }))
+        }
+        Some(offset) => Ok(offset),
+        None => Ok(0),
+    }
+}
+
+/// Cursor of keyset pagination on the last update timestamp and UUID of wishlists.
+///
+/// Entities following the cursor in descending order of `last_updated_at` and `_id` are retrieved,
+/// which avoids scanning skipped documents.
+pub struct LastUpdatedCursor {
+    /// Timestamp of last update of the entity at the cursor.
+    pub last_updated_at: DateTime,
+    /// UUID of the entity at the cursor.
+    pub id: Uuid,
+}
+
+impl LastUpdatedCursor {
+    /// Parses a cursor of the form `<milliseconds since epoch>_<UUID>`.
+    ///
+    /// * `cursor` - Cursor to parse.
+    pub fn parse(cursor: &str) -> Result<Self> {
+        let message = format!("Cursor: `{}` is invalid.", cursor);
+        let (millis, id) = cursor.split_once('_').ok_or_else(|| Error::new(&message))?;
+        let millis: i64 = millis.parse().map_err(|_| Error::new(&message))?;
+        let id = Uuid::parse_str(id).map_err(|_| Error::new(&message))?;
+        Ok(Self {
+            last_updated_at: DateTime::from_millis(millis),
+            id,
+        })
+    }
+
+    /// Builds MongoDB filter document matching the entities following the cursor.
+    pub fn to_document(&self) -> Document {
+        doc! {"$or": [
+            {"last_updated_at": {"$lt": self.last_updated_at}},
+            {"last_updated_at": self.last_updated_at, "_id": {"$lt": self.id}},
+        ]}
+    }
+
+    /// Builds MongoDB filter document matching the entities preceding the cursor.
+    pub fn to_reverse_document(&self) -> Document {
+        doc! {"$or": [
+            {"last_updated_at": {"$gt": self.last_updated_at}},
+            {"last_updated_at": self.last_updated_at, "_id": {"$gt": self.id}},
+        ]}
+    }
+
+    /// Builds MongoDB sorting document of keyset pagination.
+    pub fn sorting_document() -> Document {
+        doc! {"last_updated_at": -1, "_id": -1}
+    }
+
+    /// Builds MongoDB sorting document of reverse traversal of keyset pagination, the entities closest to the cursor come first.
+    pub fn reverse_sorting_document() -> Document {
+        doc! {"last_updated_at": 1, "_id": 1}
+    }
+}
+
+impl fmt::Display for LastUpdatedCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.last_updated_at.timestamp_millis(), self.id)
+    }
+}
+
+/// Cursor of a change in the activity feed of a wishlist.
+///
+/// Changes are derived from audit entries, an audit entry can result in multiple changes, which are distinguished by their index.
+pub struct ActivityCursor {
+    /// Timestamp of the audit entry of the change.
+    pub created_at: DateTime,
+    /// UUID of the audit entry of the change.
+    pub audit_entry_id: Uuid,
+    /// Index of the change among the changes of its audit entry.
+    pub index: u64,
+}
+
+impl ActivityCursor {
+    /// Parses a cursor of the form `<milliseconds since epoch>_<UUID>_<index>`.
+    ///
+    /// * `cursor` - Cursor to parse.
+    pub fn parse(cursor: &str) -> Result<Self> {
+        let message = format!("Cursor: `{}` is invalid.", cursor);
+        let (millis, rest) = cursor.split_once('_').ok_or_else(|| Error::new(&message))?;
+        let (id, index) = rest.split_once('_').ok_or_else(|| Error::new(&message))?;
+        let millis: i64 = millis.parse().map_err(|_| Error::new(&message))?;
+        let audit_entry_id = Uuid::parse_str(id).map_err(|_| Error::new(&message))?;
+        let index: u64 = index.parse().map_err(|_| Error::new(&message))?;
+        Ok(Self {
+            created_at: DateTime::from_millis(millis),
+            audit_entry_id,
+            index,
+        })
+    }
+
+    /// Builds MongoDB filter document matching the audit entry of the cursor and the audit entries following it.
+    pub fn to_document(&self) -> Document {
+        doc! {"$or": [
+            {"created_at": {"$lt": self.created_at}},
+            {"created_at": self.created_at, "_id": {"$lte": self.audit_entry_id}},
+        ]}
+    }
+
+    /// Builds MongoDB sorting document of the activity feed, the most recent changes come first.
+    pub fn sorting_document() -> Document {
+        doc! {"created_at": -1, "_id": -1}
+    }
+}
+
+impl fmt::Display for ActivityCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}_{}_{}",
+            self.created_at.timestamp_millis(),
+            self.audit_entry_id,
+            self.index
+        )
+    }
+}
+
+/// Merges two alternative arguments of a connection query, of which at most one may be specified.
+///
+/// * `name` - Name of argument.
+/// * `value` - Value of argument.
+/// * `alternative_name` - Name of alternative argument.
+/// * `alternative_value` - Value of alternative argument.
+pub fn alternative_arguments<T>(
+    name: &str,
+    value: Option<T>,
+    alternative_name: &str,
+    alternative_value: Option<T>,
+) -> Result<Option<T>> {
+    match (value, alternative_value) {
+        (Some(_), Some(_)) => {
+            let message = format!(
+                "Arguments `{}` and `{}` can not be specified together.",
+                name, alternative_name
+            );
+            Err(Error::new(message))
+        }
+        (value, alternative_value) => Ok(value.or(alternative_value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_cursor_parse_reverses_display() {
+        let cursor = "1700000000000_123e4567-e89b-12d3-a456-426614174000_3";
+        let parsed = ActivityCursor::parse(cursor).unwrap();
+        assert_eq!(parsed.created_at.timestamp_millis(), 1700000000000);
+        assert_eq!(
+            parsed.audit_entry_id.to_string(),
+            "123e4567-e89b-12d3-a456-426614174000"
+        );
+        assert_eq!(parsed.index, 3);
+        assert_eq!(parsed.to_string(), cursor);
+    }
+
+    #[test]
+    fn activity_cursor_parse_rejects_malformed_cursors() {
+        for cursor in [
+            "",
+            "1700000000000",
+            "1700000000000_123e4567-e89b-12d3-a456-426614174000",
+            "now_123e4567-e89b-12d3-a456-426614174000_0",
+            "1700000000000_not-a-uuid_0",
+            "1700000000000_123e4567-e89b-12d3-a456-426614174000_-1",
+        ] {
+            let error = ActivityCursor::parse(cursor).err().unwrap();
+            assert_eq!(error.message, format!("Cursor: `{}` is invalid.", cursor));
+        }
+    }
+}
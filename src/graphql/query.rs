@@ -1,13 +1,39 @@
 use std::any::type_name;
 
-use async_graphql::{Context, Error, Object, Result};
+use async_graphql::{dataloader::DataLoader, Context, Error, Object, Result};
 
-use bson::Uuid;
-use mongodb::{bson::doc, Collection, Database};
+use bson::Document;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{CountOptions, FindOneOptions, FindOptions, ReadPreference, SelectionCriteria},
+    Collection, Database,
+};
 use serde::Deserialize;
 
-use super::model::{user::User, wishlist::Wishlist};
-use crate::authorization::authorize_user;
+use super::data_loaders::{ObjectLoader, StoredObject};
+use super::model::{
+    build_info::BuildInfo,
+    connection::failed_event_connection::FailedEventConnection,
+    date_time::DateTime,
+    failed_event::FailedEvent,
+    foreign_types::ProductVariant,
+    quota::{WishlistItemQuota, WishlistQuota},
+    statistics::{StatisticsTimeBucket, WishlistCreationCount, WishlistServiceStatistics},
+    user::{User, UserKeyInput},
+    user_preferences::{find_user_preferences, UserPreferences, USER_PREFERENCES_COLLECTION},
+    uuid::Uuid,
+    wishlist::Wishlist,
+};
+use super::pagination;
+use crate::authorization::{authorize_admin, authorize_user, authorized_user_id};
+use crate::cache::{wishlist_key, StateCache};
+use crate::event::failed_events::FAILED_EVENT_COLLECTION;
+use crate::settings::Settings;
+use crate::tenant::{tenant_id, DatabaseRouter};
+
+/// Default duration in milliseconds covered by the wishlist creation counts of the statistics: 30 days.
+const DEFAULT_STATISTICS_DURATION_MILLIS: i64 = 30 * 24 * 60 * 60 * 1000;
 
 /// Describes GraphQL wishlist queries.
 pub struct Query;
@@ -15,15 +41,29 @@ pub struct Query;
 #[Object]
 impl Query {
     /// Entity resolver for user of specific UUID.
+    ///
+    /// Users referenced in the same request are loaded in a single batch.
     #[graphql(entity)]
     async fn user_entity_resolver<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(desc = "UUID of user to retrieve.")] id: Uuid,
     ) -> Result<User> {
-        let db_client = ctx.data::<Database>()?;
-        let collection: Collection<User> = db_client.collection::<User>("users");
-        query_object(&collection, id).await
+        load_object(ctx, id).await
+    }
+
+    /// Entity resolver for product variant of specific UUID.
+    #[graphql(entity)]
+    async fn product_variant_entity_resolver<'a>(
+        &self,
+        #[graphql(key, desc = "UUID of product variant to retrieve.")] id: Uuid,
+    ) -> ProductVariant {
+        ProductVariant { _id: id }
+    }
+
+    /// Retrieves version and git metadata of the deployed build of the wishlist service.
+    async fn build_info(&self) -> BuildInfo {
+        BuildInfo::current()
     }
 
     /// Retrieves wishlist of specific UUID.
@@ -34,23 +74,319 @@ impl Query {
     ) -> Result<Wishlist> {
         let db_client = ctx.data::<Database>()?;
         let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        let wishlist = query_object(&collection, id).await?;
-        authorize_user(&ctx, Some(wishlist.user._id))?;
+        let wishlist = ctx
+            .data::<StateCache>()?
+            .get_or_load(&wishlist_key(id), query_object(&collection, id))
+            .await?;
+        let wishlist = tenant_id(ctx)?.check_wishlist(wishlist)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
         Ok(wishlist)
     }
 
+    /// Checks whether wishlist of specific UUID exists, without retrieving it.
+    ///
+    /// Only the owner of the wishlist is read, the caller must own the wishlist or have a permissive role.
+    async fn wishlist_exists<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to check.")] id: Uuid,
+    ) -> Result<bool> {
+        let collection: Collection<WishlistOwner> = ctx
+            .data::<Database>()?
+            .collection::<WishlistOwner>("wishlists");
+        let find_options = FindOneOptions::builder()
+            .projection(doc! {"_id": 0, "user._id": 1})
+            .build();
+        match collection
+            .find_one(tenant_id(ctx)?.scope(doc! {"_id": id}), find_options)
+            .await
+        {
+            Ok(Some(wishlist_owner)) => {
+                authorize_user(ctx, Some(wishlist_owner.user._id))?;
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(_) => Err(Error::new("Retrieving wishlist failed in MongoDB.")),
+        }
+    }
+
+    /// Checks whether user of specific UUID has at least one wishlist, without retrieving the wishlists.
+    async fn user_has_wishlists<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of user to check.")] user_id: Uuid,
+    ) -> Result<bool> {
+        authorize_user(ctx, Some(user_id))?;
+        let collection: Collection<Document> =
+            ctx.data::<Database>()?.collection::<Document>("wishlists");
+        let count_options = CountOptions::builder().limit(1).build();
+        match collection
+            .count_documents(
+                tenant_id(ctx)?.scope(doc! {"user._id": user_id}),
+                count_options,
+            )
+            .await
+        {
+            Ok(count) => Ok(count > 0),
+            Err(_) => Err(Error::new(
+                "Retrieving wishlists of user failed in MongoDB.",
+            )),
+        }
+    }
+
     /// Entity resolver for wishlist of specific UUID.
+    ///
+    /// Wishlists referenced in the same request are loaded in a single batch.
     #[graphql(entity)]
     async fn wishlist_entity_resolver<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(key, desc = "UUID of wishlist to retrieve.")] id: Uuid,
     ) -> Result<Wishlist> {
+        let tenant_id = tenant_id(ctx)?;
+        let wishlist = tenant_id.check_wishlist(load_object(ctx, id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        Ok(wishlist)
+    }
+
+    /// Entity resolver for wishlist of a user by its name, for subgraphs referencing wishlists without knowing their UUID.
+    ///
+    /// Resolves the federation key `user { id } name`. If the user has multiple wishlists of the name, the earliest created wishlist is resolved.
+    #[graphql(entity)]
+    async fn wishlist_by_user_and_name_entity_resolver<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(key, desc = "User owning the wishlist to retrieve.")] user: UserKeyInput,
+        #[graphql(key, desc = "Name of wishlist to retrieve.")] name: String,
+    ) -> Result<Wishlist> {
+        authorize_user(ctx, Some(user.id))?;
+        let collection: Collection<Wishlist> =
+            ctx.data::<Database>()?.collection::<Wishlist>("wishlists");
+        let find_options = FindOneOptions::builder()
+            .sort(doc! {"created_at": 1, "_id": 1})
+            .build();
+        match collection
+            .find_one(
+                tenant_id(ctx)?.scope(doc! {"user._id": user.id, "name": &name}),
+                find_options,
+            )
+            .await
+        {
+            Ok(Some(wishlist)) => Ok(wishlist),
+            Ok(None) => {
+                let message = format!(
+                    "Wishlist with name: `{}` of user of UUID: `{}` not found.",
+                    name, user.id
+                );
+                Err(Error::new(message))
+            }
+            Err(_) => Err(Error::new("Retrieving wishlist failed in MongoDB.")),
+        }
+    }
+
+    /// Retrieves the usage of the per-user wishlist limits of the calling user.
+    async fn my_wishlist_quota<'a>(&self, ctx: &Context<'a>) -> Result<WishlistQuota> {
+        let user_id = authorized_user_id(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let settings = ctx.data::<Settings>()?;
+        let collection: Collection<WishlistItemCount> =
+            db_client.collection::<WishlistItemCount>("wishlists");
+        let find_options = FindOptions::builder()
+            .projection(doc! {"_id": 1, "item_count": 1})
+            .sort(doc! {"_id": 1})
+            .build();
+        let message = "Retrieving wishlists of user failed in MongoDB.";
+        let item_counts: Vec<WishlistItemCount> = match collection
+            .find(
+                tenant_id(ctx)?.scope(doc! {"user._id": user_id}),
+                find_options,
+            )
+            .await
+        {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| Error::new(message))?,
+            Err(_) => return Err(Error::new(message)),
+        };
+        let items_used_per_list = item_counts
+            .into_iter()
+            .map(|item_count| WishlistItemQuota {
+                wishlist_id: item_count._id,
+                used: item_count.item_count,
+                limit: settings.max_items_per_wishlist,
+            })
+            .collect::<Vec<WishlistItemQuota>>();
+        Ok(WishlistQuota {
+            used: items_used_per_list.len() as u64,
+            limit: settings.max_wishlists_per_user,
+            items_used_per_list,
+        })
+    }
+
+    /// Retrieves the preferences of the calling user.
+    async fn my_preferences<'a>(&self, ctx: &Context<'a>) -> Result<UserPreferences> {
+        let user_id = authorized_user_id(ctx)?;
+        let collection: Collection<UserPreferences> = ctx
+            .data::<Database>()?
+            .collection::<UserPreferences>(USER_PREFERENCES_COLLECTION);
+        find_user_preferences(&collection, tenant_id(ctx)?, user_id).await
+    }
+
+    /// Retrieves statistics of the wishlist service for the tenant of the request. Requires role: `admin`.
+    async fn wishlist_service_statistics<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Size of the time buckets of the wishlist creation counts.")]
+        time_bucket: Option<StatisticsTimeBucket>,
+        #[graphql(
+            desc = "Only wishlists created after this timestamp are part of the wishlist creation counts. Defaults to 30 days ago."
+        )]
+        created_after: Option<DateTime>,
+    ) -> Result<WishlistServiceStatistics> {
+        authorize_admin(ctx)?;
         let db_client = ctx.data::<Database>()?;
         let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
-        let wishlist = query_object(&collection, id).await?;
-        authorize_user(&ctx, Some(wishlist.user._id))?;
-        Ok(wishlist)
+        let definitely_created_after = created_after.unwrap_or(DateTime::from_millis(
+            DateTime::now().timestamp_millis() - DEFAULT_STATISTICS_DURATION_MILLIS,
+        ));
+        let pipeline = vec![
+            doc! {"$match": tenant_id(ctx)?.scope(doc! {})},
+            doc! {"$facet": {
+                "totals": [{"$group": {
+                    "_id": null,
+                    "total_wishlists": {"$sum": 1},
+                    "average_items_per_wishlist": {"$avg": {"$size": "$internal_product_variants"}},
+                }}],
+                "users": [{"$group": {"_id": "$user._id"}}, {"$count": "count"}],
+                "creation_counts": [
+                    {"$match": {"created_at": {"$gt": definitely_created_after}}},
+                    {"$group": {
+                        "_id": {"$dateTrunc": {"date": "$created_at", "unit": time_bucket.unwrap_or_default().as_str()}},
+                        "count": {"$sum": 1},
+                    }},
+                    {"$sort": {"_id": 1}},
+                ],
+            }},
+        ];
+        let message = "Aggregating wishlist statistics failed in MongoDB.";
+        let facets: Document = match collection.aggregate(pipeline, None).await {
+            Ok(cursor) => cursor
+                .try_collect::<Vec<Document>>()
+                .await?
+                .pop()
+                .ok_or(Error::new(message))?,
+            Err(_) => return Err(Error::new(message)),
+        };
+        let statistics_facets: StatisticsFacets = bson::from_document(facets)?;
+        let totals = statistics_facets.totals.first();
+        Ok(WishlistServiceStatistics {
+            total_wishlists: totals.map_or(0, |totals| totals.total_wishlists),
+            total_users_with_wishlists: statistics_facets
+                .users
+                .first()
+                .map_or(0, |users| users.count),
+            average_items_per_wishlist: totals
+                .map_or(0.0, |totals| totals.average_items_per_wishlist),
+            creation_counts: statistics_facets.creation_counts,
+        })
+    }
+
+    /// Retrieves events whose handling failed, most recently failed first. Requires role: `admin`.
+    ///
+    /// Failed events are shared by all tenants, as events are handled for all tenants.
+    async fn failed_events<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Describes that the `first` N failed events should be retrieved.")]
+        first: Option<u32>,
+        #[graphql(desc = "Describes how many failed events should be skipped at the beginning.")]
+        skip: Option<u64>,
+    ) -> Result<FailedEventConnection> {
+        authorize_admin(ctx)?;
+        let settings = ctx.data::<Settings>()?;
+        let collection: Collection<FailedEvent> =
+            ctx.data::<DatabaseRouter>()?
+                .default_database()
+                .collection::<FailedEvent>(FAILED_EVENT_COLLECTION);
+        let definitely_first = pagination::page_size(settings, first.map(u64::from))?;
+        let definitely_skip = pagination::offset(settings, skip)?;
+        let find_options = FindOptions::builder()
+            .sort(doc! {"last_failed_at": -1, "_id": 1})
+            .skip(definitely_skip)
+            .limit(definitely_first as i64)
+            .build();
+        let message = "Retrieving failed events failed in MongoDB.";
+        let nodes: Vec<FailedEvent> = match collection.find(None, find_options).await {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| Error::new(message))?,
+            Err(_) => return Err(Error::new(message)),
+        };
+        let total_count = collection
+            .count_documents(None, None)
+            .await
+            .map_err(|_| Error::new(message))?;
+        Ok(FailedEventConnection {
+            has_next_page: definitely_skip + (nodes.len() as u64) < total_count,
+            has_previous_page: definitely_skip > 0,
+            nodes,
+            total_count,
+        })
+    }
+}
+
+/// Result of the wishlist statistics aggregation.
+#[derive(Deserialize)]
+struct StatisticsFacets {
+    totals: Vec<StatisticsTotals>,
+    users: Vec<StatisticsCount>,
+    creation_counts: Vec<WishlistCreationCount>,
+}
+
+/// Totals of the wishlist statistics aggregation.
+#[derive(Deserialize)]
+struct StatisticsTotals {
+    total_wishlists: u64,
+    average_items_per_wishlist: f64,
+}
+
+/// Count of the wishlist statistics aggregation.
+#[derive(Deserialize)]
+struct StatisticsCount {
+    count: u64,
+}
+
+/// Projection of a wishlist to its item count.
+#[derive(Deserialize)]
+struct WishlistItemCount {
+    _id: Uuid,
+    #[serde(default)]
+    item_count: u64,
+}
+
+/// Projection of a wishlist to its owner.
+#[derive(Deserialize)]
+struct WishlistOwner {
+    user: User,
+}
+
+/// Loads an object: `T` with the data loader of the request, which batches the loads of a request.
+///
+/// * `ctx` - GraphQL context containing the data loader.
+/// * `id` - UUID of object.
+async fn load_object<T: StoredObject>(ctx: &Context<'_>, id: Uuid) -> Result<T> {
+    match ctx
+        .data::<DataLoader<ObjectLoader<T>>>()?
+        .load_one(id)
+        .await?
+    {
+        Some(object) => Ok(object),
+        None => {
+            let message = format!("{} with UUID: `{}` not found.", type_name::<T>(), id);
+            Err(Error::new(message))
+        }
     }
 }
 
@@ -62,7 +398,36 @@ pub async fn query_object<T: for<'a> Deserialize<'a> + Unpin + Send + Sync>(
     collection: &Collection<T>,
     id: Uuid,
 ) -> Result<T> {
-    match collection.find_one(doc! {"_id": id }, None).await {
+    find_object(collection, id, None).await
+}
+
+/// Queries an object: `T` from the primary of the MongoDB replica set, which reflects all acknowledged writes.
+///
+/// Used by mutations to read their own writes, regardless of the read preference of the connection.
+///
+/// * `connection` - MongoDB database connection.
+/// * `id` - UUID of object.
+pub async fn query_object_from_primary<T: for<'a> Deserialize<'a> + Unpin + Send + Sync>(
+    collection: &Collection<T>,
+    id: Uuid,
+) -> Result<T> {
+    let find_options = FindOneOptions::builder()
+        .selection_criteria(SelectionCriteria::ReadPreference(ReadPreference::Primary))
+        .build();
+    find_object(collection, id, find_options).await
+}
+
+/// Finds an object: `T` of UUID with options.
+///
+/// * `connection` - MongoDB database connection.
+/// * `id` - UUID of object.
+/// * `find_options` - Options of the query, e.g. the read preference.
+async fn find_object<T: for<'a> Deserialize<'a> + Unpin + Send + Sync>(
+    collection: &Collection<T>,
+    id: Uuid,
+    find_options: impl Into<Option<FindOneOptions>>,
+) -> Result<T> {
+    match collection.find_one(doc! {"_id": id }, find_options).await {
         Ok(maybe_object) => match maybe_object {
             Some(object) => Ok(object),
             None => {
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use async_graphql::{Context, Error, Result, Subscription as SubscriptionObject};
+use futures::{future, stream, Stream, StreamExt};
+use mongodb::{
+    bson::doc,
+    change_stream::event::{ChangeStreamEvent, OperationType},
+    options::{ChangeStreamOptions, FullDocumentBeforeChangeType, FullDocumentType},
+    Collection, Database,
+};
+
+use crate::authorization::{authorize_admin, authorize_user};
+use crate::tenant::tenant_id;
+
+use super::model::{
+    foreign_types::ProductVariant, uuid::Uuid, wishlist::Wishlist,
+    wishlist_item_added::WishlistItemAdded,
+};
+use super::query::query_object;
+
+/// Describes GraphQL wishlist subscriptions.
+pub struct Subscription;
+
+#[SubscriptionObject]
+impl Subscription {
+    /// Pushes the wishlist of UUID whenever it is modified, by mutations as well as by events.
+    ///
+    /// The subscriber is authorized as owner of the wishlist when subscribing. Ends when the wishlist is deleted.
+    async fn wishlist_updated<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "UUID of wishlist to watch.")] id: Uuid,
+    ) -> Result<impl Stream<Item = Wishlist>> {
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let wishlist = tenant_id(ctx)?.check_wishlist(query_object(&collection, id).await?)?;
+        authorize_user(ctx, Some(wishlist.user._id))?;
+        watch_wishlist(&collection, id).await
+    }
+
+    /// Pushes every addition of a product variant to any wishlist of the tenant. Requires role: `admin`.
+    async fn any_wishlist_item_added<'a>(
+        &self,
+        ctx: &Context<'a>,
+    ) -> Result<impl Stream<Item = WishlistItemAdded>> {
+        authorize_admin(ctx)?;
+        let db_client = ctx.data::<Database>()?;
+        let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+        let pipeline = [doc! {"$match": {
+            "operationType": {"$in": ["insert", "update", "replace"]},
+            "fullDocument.tenant_id": &tenant_id(ctx)?.0,
+        }}];
+        let options = ChangeStreamOptions::builder()
+            .full_document(Some(FullDocumentType::WhenAvailable))
+            .full_document_before_change(Some(FullDocumentBeforeChangeType::WhenAvailable))
+            .build();
+        let change_stream = match collection.watch(pipeline, options).await {
+            Ok(change_stream) => change_stream,
+            Err(_) => return Err(Error::new("Watching wishlists failed in MongoDB.")),
+        };
+        Ok(change_stream
+            .take_while(|event| future::ready(event.is_ok()))
+            .flat_map(|event| stream::iter(event.map(added_items).unwrap_or_default())))
+    }
+}
+
+/// Watches a wishlist and yields it whenever it is modified.
+///
+/// The stream ends when the wishlist is deleted or the change stream fails.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `id` - UUID of wishlist to watch.
+pub async fn watch_wishlist(
+    collection: &Collection<Wishlist>,
+    id: Uuid,
+) -> Result<impl Stream<Item = Wishlist>> {
+    let pipeline = [doc! {"$match": {"documentKey._id": id}}];
+    let options = ChangeStreamOptions::builder()
+        .full_document(Some(FullDocumentType::UpdateLookup))
+        .build();
+    let change_stream = match collection.watch(pipeline, options).await {
+        Ok(change_stream) => change_stream,
+        Err(_) => {
+            let message = format!("Watching wishlist of UUID: `{}` failed in MongoDB.", id);
+            return Err(Error::new(message));
+        }
+    };
+    Ok(change_stream
+        .take_while(|event| {
+            future::ready(matches!(
+                event,
+                Ok(event) if event.operation_type != OperationType::Delete
+            ))
+        })
+        .filter_map(|event| future::ready(event.ok().and_then(|event| event.full_document))))
+}
+
+/// Determines the product variants added to a wishlist by a change.
+///
+/// Compares the wishlist before and after the change, all product variants of inserted wishlists are added.
+/// Changes without the wishlist before or after the change are skipped.
+///
+/// * `event` - Change of the change stream of wishlists.
+fn added_items(event: ChangeStreamEvent<Wishlist>) -> Vec<WishlistItemAdded> {
+    let wishlist = match event.full_document {
+        Some(wishlist) => wishlist,
+        None => return vec![],
+    };
+    let previous_product_variants: HashSet<ProductVariant> =
+        match (event.operation_type, event.full_document_before_change) {
+            (OperationType::Insert, _) => HashSet::new(),
+            (_, Some(previous_wishlist)) => previous_wishlist.internal_product_variants,
+            (_, None) => return vec![],
+        };
+    wishlist
+        .internal_product_variants
+        .difference(&previous_product_variants)
+        .map(|product_variant| WishlistItemAdded {
+            user_id: wishlist.user._id,
+            wishlist_id: wishlist._id,
+            product_variant_id: product_variant._id,
+        })
+        .collect()
+}
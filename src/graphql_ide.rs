@@ -0,0 +1,114 @@
+use async_graphql::http::GraphiQLSource;
+use axum::http::HeaderMap;
+
+use crate::settings::{GraphQLIde, PathPrefix, Settings};
+
+/// Script of the embeddable Apollo Sandbox.
+const APOLLO_SANDBOX_SCRIPT_URL: &str =
+    "https://embeddable-sandbox.cdn.apollographql.com/_latest/embeddable-sandbox.umd.production.min.js";
+
+/// HTML page of the GraphQL IDE, rendered per request to advertise the endpoint URLs under which the request reached the service.
+#[derive(Clone)]
+pub struct GraphQLIdePage {
+    graphql_ide: GraphQLIde,
+    public_path_prefix: PathPrefix,
+    graphql_path: PathPrefix,
+    endpoint: Option<String>,
+}
+
+impl GraphQLIdePage {
+    /// Constructs the page of the GraphQL IDE of the service settings, `None` if no IDE is served.
+    ///
+    /// * `settings` - Service settings defining the IDE, the GraphQL path and the advertised endpoint.
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        match settings.graphql_ide {
+            GraphQLIde::None => None,
+            graphql_ide => Some(Self {
+                graphql_ide,
+                public_path_prefix: settings.public_path_prefix.clone(),
+                graphql_path: settings.graphql_path.clone(),
+                endpoint: settings.graphql_ide_endpoint.clone(),
+            }),
+        }
+    }
+
+    /// Renders the page for a request.
+    ///
+    /// The configured endpoint is advertised if set. Otherwise the endpoint is the GraphQL path,
+    /// prefixed with the `X-Forwarded-Prefix` header of the reverse proxy or, if it is not set or invalid, the public path prefix.
+    /// The subscription endpoint is `/ws` below the endpoint.
+    ///
+    /// * `headers` - Header map containing headers of request.
+    pub fn render(&self, headers: &HeaderMap) -> String {
+        let endpoint = match &self.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => {
+                let path_prefix = headers
+                    .get("X-Forwarded-Prefix")
+                    .and_then(|path_prefix| path_prefix.to_str().ok())
+                    .and_then(|path_prefix| path_prefix.parse::<PathPrefix>().ok())
+                    .unwrap_or_else(|| self.public_path_prefix.clone());
+                format!(
+                    "{}{}",
+                    path_prefix.0,
+                    graphql_endpoint_path(&self.graphql_path)
+                )
+            }
+        };
+        let subscription_endpoint = format!("{}/ws", endpoint.trim_end_matches('/'));
+        render_graphql_ide(self.graphql_ide, &endpoint, &subscription_endpoint).unwrap_or_default()
+    }
+}
+
+/// Returns the route of the GraphQL endpoint, `/` if it is served at the root path.
+///
+/// * `graphql_path` - Path under which the GraphQL endpoint is served.
+pub fn graphql_endpoint_path(graphql_path: &PathPrefix) -> String {
+    match graphql_path.0.is_empty() {
+        true => "/".to_string(),
+        false => graphql_path.0.clone(),
+    }
+}
+
+/// Renders the HTML page of a GraphQL IDE, `None` if no IDE is served.
+///
+/// The endpoint URLs are resolved relative to the origin of the page if they are paths.
+///
+/// * `graphql_ide` - GraphQL IDE to render.
+/// * `endpoint` - URL of the GraphQL endpoint.
+/// * `subscription_endpoint` - URL of the GraphQL endpoint of subscriptions over WebSocket connections.
+fn render_graphql_ide(
+    graphql_ide: GraphQLIde,
+    endpoint: &str,
+    subscription_endpoint: &str,
+) -> Option<String> {
+    match graphql_ide {
+        GraphQLIde::GraphiQL => Some(
+            GraphiQLSource::build()
+                .endpoint(endpoint)
+                .subscription_endpoint(subscription_endpoint)
+                .finish(),
+        ),
+        GraphQLIde::ApolloSandbox => Some(format!(
+            r##"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8">
+    <title>Apollo Sandbox</title>
+  </head>
+  <body style="margin: 0; overflow: hidden;">
+    <div id="sandbox" style="width: 100vw; height: 100vh;"></div>
+    <script src="{}"></script>
+    <script>
+      new window.EmbeddedSandbox({{
+        target: "#sandbox",
+        initialEndpoint: new URL("{}", window.location.origin).href,
+      }});
+    </script>
+  </body>
+</html>"##,
+            APOLLO_SANDBOX_SCRIPT_URL, endpoint
+        )),
+        GraphQLIde::None => None,
+    }
+}
@@ -0,0 +1,220 @@
+use async_graphql::{Error, Result};
+use bson::{doc, Document};
+use futures::TryStreamExt;
+use log::{info, warn};
+use mongodb::{
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+    Collection,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{record_wishlist_snapshots, AuditEntry};
+use crate::cache::{wishlist_key, StateCache};
+use crate::dapr_client::DaprClient;
+use crate::graphql::model::{date_time::DateTime, uuid::Uuid, wishlist::Wishlist};
+use crate::metrics::catalog_drift_metrics::record_catalog_drift;
+use crate::service_invocation::product_variant_exists_in_catalog;
+
+/// Settings of the drift detection against the catalog.
+#[derive(Clone)]
+pub struct CatalogDriftDetection {
+    /// Dapr client used to invoke the catalog service.
+    pub dapr_client: DaprClient,
+    /// Dapr app id of the catalog service.
+    pub catalog_app_id: String,
+    /// Maximum number of product variants checked per run.
+    pub sample_size: u64,
+    /// Whether references to product variants missing in the catalog are removed from wishlists.
+    pub auto_remove: bool,
+    /// Number of consecutive checks a product variant must be missing in the catalog before its references are removed.
+    pub auto_remove_after_misses: u64,
+}
+
+/// MongoDB collection of the product variants found missing in the catalog, see `CatalogDriftMiss`.
+pub const CATALOG_DRIFT_MISS_COLLECTION: &str = "catalog_drift_misses";
+
+/// Product variant referenced by wishlists which was missing in the catalog when it was last checked.
+///
+/// Removed as soon as a check finds the product variant in the catalog again.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogDriftMiss {
+    /// UUID of the product variant.
+    pub _id: Uuid,
+    /// Number of consecutive checks in which the product variant was missing in the catalog.
+    pub consecutive_misses: u64,
+    /// Timestamp of the last check in which the product variant was missing in the catalog.
+    pub last_missed_at: DateTime,
+}
+
+/// Checks a random sample of the product variants referenced by wishlists against the catalog service.
+///
+/// Product variants missing in the catalog are logged and recorded as drift metric.
+/// If enabled, references to product variants missing in the catalog in the configured number of consecutive checks
+/// are removed from all wishlists of the collection, so a transient catalog inconsistency does not remove references.
+/// A run fails without removing references if the catalog service can not be reached or responds with errors.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `miss_collection` - MongoDB collection counting the consecutive misses of product variants.
+/// * `audit_collection` - MongoDB collection of audit entries, recording the wishlists whose references are removed.
+/// * `state_cache` - Cache of wishlists, invalidated for wishlists whose references are removed.
+/// * `drift_detection` - Settings of the drift detection.
+pub async fn detect_catalog_drift(
+    collection: &Collection<Wishlist>,
+    miss_collection: &Collection<CatalogDriftMiss>,
+    audit_collection: &Collection<AuditEntry>,
+    state_cache: &StateCache,
+    drift_detection: &CatalogDriftDetection,
+) -> Result<()> {
+    let sample_size = drift_detection.sample_size as i64;
+    let pipeline = vec![
+        doc! {"$match": {"item_count": {"$gt": 0}}},
+        doc! {"$sample": {"size": sample_size}},
+        doc! {"$unwind": "$internal_product_variants"},
+        doc! {"$group": {"_id": "$internal_product_variants._id"}},
+        doc! {"$limit": sample_size},
+    ];
+    let message = "Sampling product variants of wishlists failed in MongoDB.";
+    let sampled_ids: Vec<Uuid> = match collection.aggregate(pipeline, None).await {
+        Ok(cursor) => cursor
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|_| Error::new(message))?
+            .into_iter()
+            .filter_map(|document| document.get("_id").cloned())
+            .filter_map(|id| bson::from_bson(id).ok())
+            .collect(),
+        Err(_) => return Err(Error::new(message)),
+    };
+    let mut missing_ids = vec![];
+    let mut present_ids = vec![];
+    for id in &sampled_ids {
+        match product_variant_exists_in_catalog(
+            &drift_detection.dapr_client,
+            &drift_detection.catalog_app_id,
+            *id,
+        )
+        .await?
+        {
+            true => present_ids.push(*id),
+            false => missing_ids.push(*id),
+        }
+    }
+    if !present_ids.is_empty() {
+        miss_collection
+            .delete_many(doc! {"_id": {"$in": present_ids}}, None)
+            .await
+            .map_err(|_| Error::new("Resetting misses of product variants failed in MongoDB."))?;
+    }
+    record_catalog_drift(sampled_ids.len() as u64, missing_ids.len() as u64);
+    info!(
+        "Checked {} product variants of wishlists against the catalog, {} are missing.",
+        sampled_ids.len(),
+        missing_ids.len()
+    );
+    if missing_ids.is_empty() {
+        return Ok(());
+    }
+    for id in &missing_ids {
+        warn!(
+            "Product variant of id: `{}` is referenced by wishlists, but missing in the catalog.",
+            id
+        );
+    }
+    let removable_ids = record_misses(
+        miss_collection,
+        &missing_ids,
+        drift_detection.auto_remove_after_misses,
+    )
+    .await?;
+    if drift_detection.auto_remove && !removable_ids.is_empty() {
+        remove_product_variants(collection, audit_collection, state_cache, &removable_ids).await?;
+        miss_collection
+            .delete_many(doc! {"_id": {"$in": &removable_ids}}, None)
+            .await
+            .map_err(|_| Error::new("Resetting misses of product variants failed in MongoDB."))?;
+    }
+    Ok(())
+}
+
+/// Counts a miss of each product variant missing in the catalog.
+///
+/// Returns the UUIDs of the product variants which were missing in at least the given number of consecutive checks.
+///
+/// * `miss_collection` - MongoDB collection counting the consecutive misses of product variants.
+/// * `missing_ids` - UUIDs of product variants missing in the catalog.
+/// * `min_consecutive_misses` - Number of consecutive misses required to return a product variant.
+async fn record_misses(
+    miss_collection: &Collection<CatalogDriftMiss>,
+    missing_ids: &[Uuid],
+    min_consecutive_misses: u64,
+) -> Result<Vec<Uuid>> {
+    let options = FindOneAndUpdateOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+    let mut removable_ids = vec![];
+    for id in missing_ids {
+        let miss = miss_collection
+            .find_one_and_update(
+                doc! {"_id": id},
+                doc! {
+                    "$inc": {"consecutive_misses": 1},
+                    "$set": {"last_missed_at": DateTime::now()},
+                },
+                options.clone(),
+            )
+            .await
+            .map_err(|_| Error::new("Counting misses of product variants failed in MongoDB."))?;
+        if miss.is_some_and(|miss| miss.consecutive_misses >= min_consecutive_misses) {
+            removable_ids.push(*id);
+        }
+    }
+    Ok(removable_ids)
+}
+
+/// Removes references to product variants from all wishlists of the collection.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `audit_collection` - MongoDB collection of audit entries, recording snapshots of the affected wishlists.
+/// * `state_cache` - Cache of wishlists, invalidated for the affected wishlists.
+/// * `ids` - UUIDs of product variants to remove.
+async fn remove_product_variants(
+    collection: &Collection<Wishlist>,
+    audit_collection: &Collection<AuditEntry>,
+    state_cache: &StateCache,
+    ids: &[Uuid],
+) -> Result<()> {
+    let message = "Removing product variants missing in the catalog failed in MongoDB.";
+    let filter = doc! {"internal_product_variants._id": {"$in": ids.to_vec()}};
+    let affected_wishlist_ids: Vec<Uuid> = collection
+        .distinct("_id", filter.clone(), None)
+        .await
+        .map_err(|_| Error::new(message))?
+        .into_iter()
+        .filter_map(|id| bson::from_bson(id).ok())
+        .collect();
+    let update = vec![
+        doc! {"$set": {
+            "internal_product_variants": {"$filter": {
+                "input": "$internal_product_variants",
+                "cond": {"$not": [{"$in": ["$$this._id", ids.to_vec()]}]},
+            }},
+            "last_updated_at": DateTime::now(),
+        }},
+        Wishlist::product_variant_dependent_fields_stage(),
+    ];
+    collection
+        .update_many(filter, update, None)
+        .await
+        .map_err(|_| Error::new(message))?;
+    for id in &affected_wishlist_ids {
+        state_cache.invalidate(&wishlist_key(*id)).await;
+    }
+    record_wishlist_snapshots(collection, audit_collection, &affected_wishlist_ids, None).await;
+    info!(
+        "Removed {} product variants missing in the catalog from {} wishlists.",
+        ids.len(),
+        affected_wishlist_ids.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use async_graphql::{Error, Result};
+use bson::doc;
+use log::info;
+use mongodb::Collection;
+
+use crate::graphql::model::wishlist::Wishlist;
+
+/// Reconciles the denormalized item counts of wishlists with their product variants.
+///
+/// Only updates wishlists whose item count differs from the number of their product variants.
+///
+/// * `collection` - MongoDB collection of wishlists to reconcile.
+pub async fn reconcile_item_counts(collection: &Collection<Wishlist>) -> Result<()> {
+    let filter = doc! {"$expr": {"$ne": ["$item_count", {"$size": "$internal_product_variants"}]}};
+    let update = vec![doc! {"$set": {"item_count": {"$size": "$internal_product_variants"}}}];
+    match collection.update_many(filter, update, None).await {
+        Ok(result) => {
+            info!(
+                "Reconciled item counts of {} wishlists.",
+                result.modified_count
+            );
+            Ok(())
+        }
+        Err(_) => Err(Error::new(
+            "Reconciling item counts of wishlists failed in MongoDB.",
+        )),
+    }
+}
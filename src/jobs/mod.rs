@@ -0,0 +1,6 @@
+pub mod catalog_drift_detection;
+pub mod item_count_reconciliation;
+pub mod projection_reconciliation;
+pub mod scheduler;
+pub mod wishlist_digest;
+pub mod wishlist_expiration;
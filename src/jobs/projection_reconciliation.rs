@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use async_graphql::{Error, Result};
+use bson::{doc, Document};
+use log::{info, warn};
+use mongodb::{
+    error::{BulkWriteFailure, ErrorKind},
+    options::InsertManyOptions,
+    Collection, Database,
+};
+
+use crate::dapr_client::DaprClient;
+use crate::graphql::idempotency::DUPLICATE_KEY_ERROR_CODE;
+use crate::graphql::model::uuid::Uuid;
+use crate::graphql::mutation_payload_structs::ReconcileForeignProjectionsPayload;
+use crate::service_invocation::{
+    list_product_variant_ids_in_catalog, list_user_ids_in_user_service,
+};
+use crate::settings::Settings;
+
+/// Number of UUIDs checked against the local projection in a single MongoDB query.
+const RECONCILIATION_CHUNK_SIZE: usize = 1000;
+
+/// UUIDs of the users and product variants of the source services.
+pub struct ForeignIds {
+    /// UUIDs of the users of the user service.
+    pub user_ids: Vec<Uuid>,
+    /// UUIDs of the product variants of the catalog service.
+    pub product_variant_ids: Vec<Uuid>,
+}
+
+/// Retrieves the UUIDs of all users and product variants from the user and catalog service.
+///
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `settings` - Service settings defining the Dapr app ids of the user and catalog service.
+pub async fn fetch_foreign_ids(
+    dapr_client: &DaprClient,
+    settings: &Settings,
+) -> Result<ForeignIds> {
+    Ok(ForeignIds {
+        user_ids: list_user_ids_in_user_service(dapr_client, &settings.user_app_id).await?,
+        product_variant_ids: list_product_variant_ids_in_catalog(
+            dapr_client,
+            &settings.catalog_app_id,
+        )
+        .await?,
+    })
+}
+
+/// Adds the users and product variants missing in the local projections of a database.
+///
+/// Projected entities missing in the source services are kept, as they may still be referenced by wishlists.
+/// Metadata of added product variants is filled by later catalog events.
+///
+/// * `db_client` - MongoDB database whose projections are reconciled.
+/// * `foreign_ids` - UUIDs of the users and product variants of the source services.
+pub async fn reconcile_foreign_projections(
+    db_client: &Database,
+    foreign_ids: &ForeignIds,
+) -> Result<ReconcileForeignProjectionsPayload> {
+    let added_user_count = insert_missing_ids(
+        &db_client.collection::<Document>("users"),
+        &foreign_ids.user_ids,
+    )
+    .await?;
+    let added_product_variant_count = insert_missing_ids(
+        &db_client.collection::<Document>("product_variants"),
+        &foreign_ids.product_variant_ids,
+    )
+    .await?;
+    info!(
+        "Reconciled foreign projections of database: `{}`, added {} users and {} product variants.",
+        db_client.name(),
+        added_user_count,
+        added_product_variant_count
+    );
+    Ok(ReconcileForeignProjectionsPayload {
+        added_user_count,
+        added_product_variant_count,
+    })
+}
+
+/// Spawns the reconciliation of the foreign projections of all databases, run once at startup.
+///
+/// Failures are logged, projections are still updated by events.
+///
+/// * `databases` - MongoDB databases of all tenants.
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `settings` - Service settings defining the Dapr app ids of the user and catalog service.
+pub fn spawn_startup_reconciliation(
+    databases: Vec<Database>,
+    dapr_client: DaprClient,
+    settings: Settings,
+) {
+    tokio::spawn(async move {
+        let foreign_ids = match fetch_foreign_ids(&dapr_client, &settings).await {
+            Ok(foreign_ids) => foreign_ids,
+            Err(error) => {
+                warn!("Reconciling foreign projections failed: {}", error.message);
+                return;
+            }
+        };
+        for database in &databases {
+            if let Err(error) = reconcile_foreign_projections(database, &foreign_ids).await {
+                warn!("Reconciling foreign projections failed: {}", error.message);
+            }
+        }
+    });
+}
+
+/// Inserts documents of the UUIDs missing in a projection and returns the number of inserted documents.
+///
+/// Inserts are unordered, documents inserted concurrently by events are skipped instead of failing the chunk.
+///
+/// * `collection` - MongoDB collection of the projection.
+/// * `ids` - UUIDs which should be present in the projection.
+async fn insert_missing_ids(collection: &Collection<Document>, ids: &[Uuid]) -> Result<u64> {
+    let message = format!(
+        "Reconciling projection: `{}` failed in MongoDB.",
+        collection.name()
+    );
+    let mut inserted_count = 0;
+    for chunk in ids.chunks(RECONCILIATION_CHUNK_SIZE) {
+        let existing_ids: HashSet<Uuid> = collection
+            .distinct("_id", doc! {"_id": {"$in": chunk.to_vec()}}, None)
+            .await
+            .map_err(|_| Error::new(&message))?
+            .into_iter()
+            .filter_map(|id| bson::from_bson(id).ok())
+            .collect();
+        let missing_documents: Vec<Document> = chunk
+            .iter()
+            .filter(|id| !existing_ids.contains(id))
+            .map(|id| doc! {"_id": *id})
+            .collect();
+        if missing_documents.is_empty() {
+            continue;
+        }
+        let missing_count = missing_documents.len() as u64;
+        let options = InsertManyOptions::builder().ordered(false).build();
+        match collection.insert_many(missing_documents, options).await {
+            Ok(result) => inserted_count += result.inserted_ids.len() as u64,
+            Err(error) => match error.kind.as_ref() {
+                ErrorKind::BulkWrite(BulkWriteFailure {
+                    write_errors: Some(write_errors),
+                    write_concern_error: None,
+                    ..
+                }) if write_errors
+                    .iter()
+                    .all(|write_error| write_error.code == DUPLICATE_KEY_ERROR_CODE) =>
+                {
+                    inserted_count += missing_count - write_errors.len() as u64;
+                }
+                _ => return Err(Error::new(&message)),
+            },
+        }
+    }
+    Ok(inserted_count)
+}
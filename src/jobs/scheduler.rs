@@ -0,0 +1,28 @@
+use std::{future::Future, time::Duration};
+
+use async_graphql::Result;
+use log::{info, warn};
+
+/// Spawns a job which runs periodically on the tokio runtime.
+///
+/// The first run starts immediately. Failed runs are logged and do not stop the job.
+///
+/// * `name` - Name of job used for logging.
+/// * `period` - Duration between the starts of two runs.
+/// * `job` - Function creating the future of a single run.
+pub fn spawn_periodic_job<F, Fut>(name: &'static str, period: Duration, job: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match job().await {
+                Ok(()) => info!("Job `{}` finished.", name),
+                Err(error) => warn!("Job `{}` failed: {}", name, error.message),
+            }
+        }
+    });
+}
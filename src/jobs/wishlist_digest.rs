@@ -0,0 +1,390 @@
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{Error, Result};
+use bson::doc;
+use futures::TryStreamExt;
+use log::{info, warn};
+use mongodb::{
+    options::{FindOptions, UpdateOptions},
+    Collection,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::dapr_client::DaprClient;
+use crate::event::outgoing_events::{
+    DigestAvailabilityChangeEventData, DigestPriceDropEventData, WishlistDigestEntryEventData,
+    WishlistDigestEventData, WISHLIST_DIGEST_TOPIC,
+};
+use crate::graphql::model::{
+    date_time::DateTime, product_variant_metadata::ProductVariantMetadata, uuid::Uuid,
+    wishlist::Wishlist,
+};
+use crate::tenant::TenantId;
+
+/// Name of the MongoDB collection of users who enabled the wishlist digest.
+pub const DIGEST_SUBSCRIPTION_COLLECTION: &str = "digest_subscriptions";
+
+/// Duration in milliseconds for which a replica claims a digest subscription while publishing its digest.
+const DIGEST_CLAIM_LEASE_MILLIS: i64 = 600000;
+
+/// Subscription of a user to the periodic digest of their wishlist activity.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DigestSubscription {
+    /// Identifier of the tenant and UUID of the user, separated by `:`.
+    pub _id: String,
+    /// Identifier of the tenant of the user.
+    pub tenant_id: String,
+    /// UUID of the subscribed user.
+    pub user_id: Uuid,
+    /// End of the period of the last digest, or the timestamp when the digest was enabled.
+    pub last_digest_at: DateTime,
+}
+
+/// Identifier of the digest subscription of a user.
+///
+/// * `tenant_id` - Tenant of the user.
+/// * `user_id` - UUID of the user.
+fn subscription_id(tenant_id: &TenantId, user_id: Uuid) -> String {
+    format!("{}:{}", tenant_id.0, user_id)
+}
+
+/// Enables or disables the wishlist digest of a user.
+///
+/// Enabling an enabled digest keeps its period, so no digest is skipped or repeated.
+///
+/// * `collection` - MongoDB collection of digest subscriptions.
+/// * `tenant_id` - Tenant of the user.
+/// * `user_id` - UUID of the user.
+/// * `enabled` - Whether the digest is enabled.
+pub async fn set_digest_enabled(
+    collection: &Collection<DigestSubscription>,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+    enabled: bool,
+) -> Result<()> {
+    let id = subscription_id(tenant_id, user_id);
+    let result = match enabled {
+        true => {
+            let update_options = UpdateOptions::builder().upsert(true).build();
+            let subscription = DigestSubscription {
+                _id: id.clone(),
+                tenant_id: tenant_id.0.clone(),
+                user_id,
+                last_digest_at: DateTime::now(),
+            };
+            collection
+                .update_one(
+                    doc! {"_id": &id},
+                    doc! {"$setOnInsert": bson::to_document(&subscription)?},
+                    update_options,
+                )
+                .await
+                .map(|_| ())
+        }
+        false => collection
+            .delete_one(doc! {"_id": &id}, None)
+            .await
+            .map(|_| ()),
+    };
+    result.map_err(|_| Error::new("Updating wishlist digest failed in MongoDB."))
+}
+
+/// Returns whether the wishlist digest of a user is enabled.
+///
+/// * `collection` - MongoDB collection of digest subscriptions.
+/// * `tenant_id` - Tenant of the user.
+/// * `user_id` - UUID of the user.
+pub async fn is_digest_enabled(
+    collection: &Collection<DigestSubscription>,
+    tenant_id: &TenantId,
+    user_id: Uuid,
+) -> Result<bool> {
+    match collection
+        .count_documents(doc! {"_id": subscription_id(tenant_id, user_id)}, None)
+        .await
+    {
+        Ok(count) => Ok(count > 0),
+        Err(_) => Err(Error::new("Retrieving wishlist digest failed in MongoDB.")),
+    }
+}
+
+/// Publishes a digest event for each subscribed user whose digest period has passed.
+///
+/// A subscription is claimed for `DIGEST_CLAIM_LEASE_MILLIS` before its digest is built, so multiple replicas do not publish the same period.
+/// Its `last_digest_at` is only advanced after Dapr acknowledged the published digest, a failed digest is retried by the next run.
+/// A digest contains the wishlists created, updated or archived within the period, the occasions within the next period,
+/// and the price drops and availability changes of the product variants of all wishlists of the user within the period.
+/// Users without activity are skipped. Failures of a user are logged and do not stop the run.
+///
+/// * `wishlist_collection` - MongoDB collection of wishlists.
+/// * `subscription_collection` - MongoDB collection of digest subscriptions of the same database.
+/// * `product_variant_collection` - MongoDB collection of product variant metadata of the same database.
+/// * `dapr_client` - Dapr client used to publish the events.
+/// * `period_millis` - Duration of a digest period in milliseconds.
+pub async fn publish_wishlist_digests(
+    wishlist_collection: &Collection<Wishlist>,
+    subscription_collection: &Collection<DigestSubscription>,
+    product_variant_collection: &Collection<ProductVariantMetadata>,
+    dapr_client: &DaprClient,
+    period_millis: i64,
+) -> Result<()> {
+    let current_timestamp = DateTime::now();
+    let due_timestamp = DateTime::from_millis(current_timestamp.timestamp_millis() - period_millis);
+    let message = "Publishing wishlist digests failed in MongoDB.";
+    let due_subscriptions: Vec<DigestSubscription> = match subscription_collection
+        .find(doc! {"last_digest_at": {"$lte": due_timestamp}}, None)
+        .await
+    {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let mut published_count = 0;
+    for subscription in due_subscriptions {
+        match publish_wishlist_digest(
+            wishlist_collection,
+            subscription_collection,
+            product_variant_collection,
+            dapr_client,
+            &subscription,
+            current_timestamp,
+            period_millis,
+        )
+        .await
+        {
+            Ok(true) => published_count += 1,
+            Ok(false) => {}
+            Err(error) => warn!(
+                "Publishing wishlist digest of user of id: `{}` failed: {}",
+                subscription.user_id, error.message
+            ),
+        }
+    }
+    info!("Published {} wishlist digests.", published_count);
+    Ok(())
+}
+
+/// Claims the subscription of a user, publishes its digest and advances its `last_digest_at`.
+///
+/// Returns whether a digest was published, `false` if the subscription is claimed by another replica or there is nothing to report.
+/// Releases the claim without advancing `last_digest_at` if the digest can not be built or Dapr does not acknowledge its publication.
+///
+/// * `wishlist_collection` - MongoDB collection of wishlists.
+/// * `subscription_collection` - MongoDB collection of digest subscriptions.
+/// * `product_variant_collection` - MongoDB collection of product variant metadata.
+/// * `dapr_client` - Dapr client used to publish the event.
+/// * `subscription` - Due digest subscription of the user.
+/// * `period_end` - End of the digest period.
+/// * `period_millis` - Duration of a digest period in milliseconds.
+async fn publish_wishlist_digest(
+    wishlist_collection: &Collection<Wishlist>,
+    subscription_collection: &Collection<DigestSubscription>,
+    product_variant_collection: &Collection<ProductVariantMetadata>,
+    dapr_client: &DaprClient,
+    subscription: &DigestSubscription,
+    period_end: DateTime,
+    period_millis: i64,
+) -> Result<bool> {
+    let message = "Claiming wishlist digest failed in MongoDB.";
+    let claimed_until =
+        DateTime::from_millis(period_end.timestamp_millis() + DIGEST_CLAIM_LEASE_MILLIS);
+    let claim = subscription_collection
+        .update_one(
+            doc! {
+                "_id": &subscription._id,
+                "last_digest_at": subscription.last_digest_at,
+                "$or": [
+                    {"claimed_until": {"$exists": false}},
+                    {"claimed_until": {"$lt": period_end}},
+                ],
+            },
+            doc! {"$set": {"claimed_until": claimed_until}},
+            None,
+        )
+        .await
+        .map_err(|_| Error::new(message))?;
+    if claim.modified_count == 0 {
+        return Ok(false);
+    }
+    let result = match build_digest(
+        wishlist_collection,
+        product_variant_collection,
+        subscription,
+        period_end,
+        period_millis,
+    )
+    .await
+    {
+        Ok(Some(event_data)) => dapr_client
+            .publish_event(WISHLIST_DIGEST_TOPIC, &event_data)
+            .await
+            .map(|_| true),
+        Ok(None) => Ok(false),
+        Err(error) => Err(error),
+    };
+    let update = match result {
+        Ok(_) => doc! {
+            "$set": {"last_digest_at": period_end},
+            "$unset": {"claimed_until": ""},
+        },
+        Err(_) => doc! {"$unset": {"claimed_until": ""}},
+    };
+    subscription_collection
+        .update_one(doc! {"_id": &subscription._id}, update, None)
+        .await
+        .map_err(|_| Error::new("Updating wishlist digest failed in MongoDB."))?;
+    result
+}
+
+/// Aggregates the wishlist activity of a user since their last digest, `None` if there is nothing to report.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `product_variant_collection` - MongoDB collection of product variant metadata.
+/// * `subscription` - Digest subscription of the user.
+/// * `period_end` - End of the digest period.
+/// * `period_millis` - Duration of a digest period in milliseconds, also used as horizon of upcoming occasions.
+async fn build_digest(
+    collection: &Collection<Wishlist>,
+    product_variant_collection: &Collection<ProductVariantMetadata>,
+    subscription: &DigestSubscription,
+    period_end: DateTime,
+    period_millis: i64,
+) -> Result<Option<WishlistDigestEventData>> {
+    let period_start = subscription.last_digest_at;
+    let occasion_horizon = DateTime::from_millis(period_end.timestamp_millis() + period_millis);
+    let filter = doc! {
+        "tenant_id": &subscription.tenant_id,
+        "user._id": subscription.user_id,
+    };
+    let find_options = FindOptions::builder()
+        .sort(doc! {"last_updated_at": -1, "_id": 1})
+        .build();
+    let message = format!(
+        "Retrieving wishlists of user of id: `{}` failed in MongoDB.",
+        subscription.user_id
+    );
+    let wishlists: Vec<Wishlist> = match collection.find(filter, find_options).await {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let product_variant_ids: HashSet<Uuid> = wishlists
+        .iter()
+        .flat_map(|wishlist| wishlist.internal_product_variants.iter())
+        .map(|product_variant| product_variant._id)
+        .collect();
+    let changed_metadata = find_changed_metadata(
+        product_variant_collection,
+        &product_variant_ids,
+        period_start,
+        period_end,
+    )
+    .await?;
+    let in_period = |date_time: Option<DateTime>| {
+        date_time.is_some_and(|date_time| date_time > period_start && date_time <= period_end)
+    };
+    let entries: Vec<WishlistDigestEntryEventData> = wishlists
+        .into_iter()
+        .map(|wishlist| {
+            let mut price_drops = vec![];
+            let mut availability_changes = vec![];
+            for product_variant in &wishlist.internal_product_variants {
+                let metadata = match changed_metadata.get(&product_variant._id) {
+                    Some(metadata) => metadata,
+                    None => continue,
+                };
+                if let (Some(price_drop), Some(current_price)) =
+                    (&metadata.last_price_drop, &metadata.current_price)
+                {
+                    if in_period(Some(price_drop.dropped_at)) {
+                        price_drops.push(DigestPriceDropEventData {
+                            product_variant_id: metadata._id,
+                            previous_price: price_drop.previous_price.clone(),
+                            current_price: current_price.clone(),
+                        });
+                    }
+                }
+                if let Some(available) = metadata
+                    .available
+                    .filter(|_| in_period(metadata.availability_changed_at))
+                {
+                    availability_changes.push(DigestAvailabilityChangeEventData {
+                        product_variant_id: metadata._id,
+                        available,
+                    });
+                }
+            }
+            WishlistDigestEntryEventData {
+                wishlist_id: wishlist._id,
+                name: wishlist.name,
+                item_count: wishlist.item_count,
+                created: in_period(Some(wishlist.created_at)),
+                updated: in_period(Some(wishlist.last_updated_at)),
+                archived: in_period(wishlist.archived_at),
+                occasion_date: wishlist.occasion_date.filter(|occasion_date| {
+                    *occasion_date >= period_end && *occasion_date < occasion_horizon
+                }),
+                price_drops,
+                availability_changes,
+            }
+        })
+        .filter(|entry| {
+            entry.created
+                || entry.updated
+                || entry.archived
+                || entry.occasion_date.is_some()
+                || !entry.price_drops.is_empty()
+                || !entry.availability_changes.is_empty()
+        })
+        .collect();
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(WishlistDigestEventData {
+        user_id: subscription.user_id,
+        period_start,
+        period_end,
+        wishlists: entries,
+    }))
+}
+
+/// Retrieves the metadata of the product variants whose price dropped or whose availability changed within a period.
+///
+/// * `collection` - MongoDB collection of product variant metadata.
+/// * `ids` - UUIDs of the product variants to check.
+/// * `period_start` - Start of the period, exclusive.
+/// * `period_end` - End of the period, inclusive.
+async fn find_changed_metadata(
+    collection: &Collection<ProductVariantMetadata>,
+    ids: &HashSet<Uuid>,
+    period_start: DateTime,
+    period_end: DateTime,
+) -> Result<HashMap<Uuid, ProductVariantMetadata>> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let period = doc! {"$gt": period_start, "$lte": period_end};
+    let filter = doc! {
+        "_id": {"$in": ids.iter().copied().collect::<Vec<Uuid>>()},
+        "$or": [
+            {"last_price_drop.dropped_at": &period},
+            {"availability_changed_at": &period},
+        ],
+    };
+    let message = "Retrieving product variants of digest failed in MongoDB.";
+    let metadata: Vec<ProductVariantMetadata> = match collection.find(filter, None).await {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    Ok(metadata
+        .into_iter()
+        .map(|metadata| (metadata._id, metadata))
+        .collect())
+}
@@ -0,0 +1,67 @@
+use async_graphql::{Error, Result};
+use bson::doc;
+use futures::TryStreamExt;
+use log::{info, warn};
+use mongodb::Collection;
+
+use crate::cache::{wishlist_key, StateCache};
+use crate::event::event_batcher::EventBatcher;
+use crate::event::outgoing_events::{WishlistExpiredEventData, WISHLIST_EXPIRED_TOPIC};
+use crate::graphql::model::{date_time::DateTime, wishlist::Wishlist};
+
+/// Archives expired wishlists and publishes an event for each archived wishlist.
+///
+/// A wishlist is expired if its expiration timestamp has passed and it is not archived yet.
+/// Events are published in batches, failed event publications are logged.
+///
+/// * `collection` - MongoDB collection of wishlists.
+/// * `event_batcher` - Event batcher used to publish the events.
+/// * `state_cache` - Cache of wishlists, from which archived wishlists are removed.
+pub async fn archive_expired_wishlists(
+    collection: &Collection<Wishlist>,
+    event_batcher: &EventBatcher,
+    state_cache: &StateCache,
+) -> Result<()> {
+    let current_timestamp = DateTime::now();
+    let expired_filter = doc! {
+        "expires_at": {"$lte": current_timestamp},
+        "archived_at": null,
+    };
+    let message = "Archiving expired wishlists failed in MongoDB.";
+    let expired_wishlists: Vec<Wishlist> = match collection.find(expired_filter, None).await {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|_| Error::new(message))?,
+        Err(_) => return Err(Error::new(message)),
+    };
+    let mut archived_count = 0;
+    for wishlist in expired_wishlists {
+        let result = collection
+            .update_one(
+                doc! {"_id": wishlist._id, "archived_at": null},
+                doc! {"$set": {"archived_at": current_timestamp, "last_updated_at": current_timestamp}},
+                None,
+            )
+            .await
+            .map_err(|_| Error::new(message))?;
+        if result.modified_count == 0 {
+            continue;
+        }
+        archived_count += 1;
+        state_cache.invalidate(&wishlist_key(wishlist._id)).await;
+        let event_data = WishlistExpiredEventData {
+            id: wishlist._id,
+            user_id: wishlist.user._id,
+            expires_at: wishlist.expires_at.unwrap_or(current_timestamp),
+        };
+        if let Err(error) = event_batcher.publish(WISHLIST_EXPIRED_TOPIC, &event_data) {
+            warn!(
+                "Publishing expiration of wishlist of id: `{}` failed: {}",
+                wishlist._id, error.message
+            );
+        }
+    }
+    info!("Archived {} expired wishlists.", archived_count);
+    Ok(())
+}
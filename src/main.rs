@@ -1,151 +1,907 @@
-use std::{collections::HashSet, env, fs::File, io::Write};
+use std::{
+    fs::File,
+    io::{self, Write},
+    net::TcpListener,
+    path::Path,
+    process::ExitCode,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
 use async_graphql::{
-    extensions::Logger, http::GraphiQLSource, EmptySubscription, SDLExportOptions, Schema,
+    dataloader::DataLoader, http::ALL_WEBSOCKET_PROTOCOLS, Data, Request, SDLExportOptions, Schema,
 };
 
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 
 use axum::{
-    extract::State,
+    extract::{State, WebSocketUpgrade},
     http::{header::HeaderMap, StatusCode},
     response::{self, IntoResponse},
-    routing::{get, post},
+    routing::{get, post, MethodRouter},
     Router, Server,
 };
-use clap::{arg, command, Parser};
+use clap::Parser;
+
+use event::{
+    aggregate_locks::AggregateLocks,
+    change_data_capture::spawn_change_data_capture,
+    event_batcher::EventBatcher,
+    failed_events::FAILED_EVENT_COLLECTION,
+    http_event_service::{list_topic_subscriptions, on_topic_event, HttpEventServiceState},
+};
+
+use log::{error, info, warn, Level};
+use metrics::{
+    event_metrics::EventMetrics, graphql_metrics::GraphQLMetrics,
+    mongodb_command_metrics::MongoDbCommandMetrics, runtime_metrics::register_runtime_metrics,
+};
+use mongodb::{bson::doc, options::ClientOptions, Client, Collection, Database};
+use opentelemetry::{
+    global,
+    trace::{FutureExt, TraceContextExt, Tracer},
+    KeyValue,
+};
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    metrics::MeterProvider as SdkMeterProvider,
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{self, Sampler},
+    Resource,
+};
+use tokio::sync::Semaphore;
+use tower_http::catch_panic::CatchPanicLayer;
 
-use event::http_event_service::{list_topic_subscriptions, on_topic_event, HttpEventServiceState};
+mod audit;
+use audit::AuditEntry;
 
-use log::{info, Level};
-use mongodb::{bson::DateTime, options::ClientOptions, Client, Collection, Database};
+mod api_key;
+use api_key::ApiKey;
 
-use bson::Uuid;
+mod authentication;
+use authentication::{AuthenticationError, Principal, RequestAuthenticator};
 
 mod authorization;
-use authorization::AuthorizedUserHeader;
+
+mod cache;
+use cache::StateCache;
+
+mod calendar;
+use calendar::{calendar, CalendarState};
+
+mod content_filter;
+use content_filter::ContentPolicy;
+
+mod dapr_client;
+
+mod database_indexes;
+use database_indexes::{create_indexes, enable_change_stream_images};
+
+use dapr_client::DaprClient;
 
 mod event;
 mod graphql;
+mod graphql_ide;
+use graphql_ide::{graphql_endpoint_path, GraphQLIdePage};
+mod service_invocation;
+
+mod metrics;
+
+mod jobs;
+use jobs::{
+    catalog_drift_detection::{
+        detect_catalog_drift, CatalogDriftDetection, CatalogDriftMiss,
+        CATALOG_DRIFT_MISS_COLLECTION,
+    },
+    item_count_reconciliation::reconcile_item_counts,
+    projection_reconciliation::spawn_startup_reconciliation,
+    scheduler::spawn_periodic_job,
+    wishlist_digest::{
+        publish_wishlist_digests, DigestSubscription, DIGEST_SUBSCRIPTION_COLLECTION,
+    },
+    wishlist_expiration::archive_expired_wishlists,
+};
+
+#[cfg(feature = "fault-injection")]
+mod fault_injection;
+#[cfg(feature = "fault-injection")]
+use fault_injection::FaultInjector;
+#[cfg(feature = "fault-injection")]
+use graphql::extensions::fault_injection::FaultInjection;
+
+mod oidc;
+use oidc::OidcAuthenticator;
+
+mod panic_handling;
+use panic_handling::handle_panic;
+
+mod secrets;
+use secrets::load_secret;
+
+mod settings;
+use settings::{Settings, TracesSampler};
+
+mod sse;
+use sse::{wishlist_updates, SseState};
+
+mod schema_check;
+use schema_check::check_schema;
+
+mod shutdown;
+use shutdown::drain_on_shutdown;
+
+mod startup_error;
+use startup_error::StartupError;
+
+mod status;
+
+mod tenant;
+use status::{status, StatusState};
+use tenant::{assign_default_tenant, resolve_tenant_id, DatabaseRouter, TenantId};
+
+mod wishlist_export;
+use wishlist_export::{export_wishlist, WishlistExportState};
 
 use graphql::{
-    model::{foreign_types::ProductVariant, user::User, wishlist::Wishlist},
+    data_loaders::ObjectLoader,
+    extensions::{
+        operation_allow_list::OperationAllowList, operation_logger::OperationLogger,
+        operation_metrics::OperationMetrics, panic_guard::PanicGuard,
+        response_size_budget::ResponseSizeBudget, validation_error_code::ValidationErrorCode,
+    },
+    idempotency::IdempotencyKey,
+    model::{
+        failed_event::FailedEvent, foreign_types::ProductVariant, ownable::Ownable,
+        product_variant_metadata::ProductVariantMetadata, user::User, wishlist::Wishlist,
+    },
     mutation::Mutation,
+    mutation_validation::{MaxLengthValidator, MutationValidators},
     query::Query,
+    subscription::Subscription,
 };
 
-/// Builds the GraphiQL frontend.
-async fn graphiql() -> impl IntoResponse {
-    response::Html(GraphiQLSource::build().endpoint("/").finish())
-}
+/// Port of the HTTP server.
+const PORT: u16 = 8080;
 
 /// Establishes database connection and returns the client.
-async fn db_connection() -> Client {
-    let uri = match env::var_os("MONGODB_URI") {
-        Some(uri) => uri.into_string().unwrap(),
-        None => panic!("$MONGODB_URI is not set."),
-    };
+///
+/// Verifies that MongoDB is reachable with a `ping` command.
+///
+/// * `dapr_client` - Dapr client used to load the MongoDB URI from the secret store.
+/// * `settings` - Service settings defining the secret store and the durability of writes.
+async fn db_connection(
+    dapr_client: &DaprClient,
+    settings: &Settings,
+) -> Result<Client, StartupError> {
+    let uri = load_secret(dapr_client, settings, "MONGODB_URI")
+        .await
+        .ok_or_else(|| StartupError::Config("$MONGODB_URI is not set.".to_string()))?;
 
     // Parse a connection string into an options struct.
-    let mut client_options = ClientOptions::parse(uri).await.unwrap();
+    let mut client_options = ClientOptions::parse(uri).await.map_err(|error| {
+        StartupError::Config(format!("$MONGODB_URI could not be parsed: {}.", error))
+    })?;
 
     // Manually set an option.
     client_options.app_name = Some("Wishlist".to_string());
 
+    // Apply the durability settings to all collections, overriding the MongoDB URI.
+    if let Some(retry_writes) = settings.mongodb_retry_writes {
+        client_options.retry_writes = Some(retry_writes);
+    }
+    if settings.mongodb_write_acknowledgment.is_some() || settings.mongodb_journal.is_some() {
+        let mut write_concern = client_options.write_concern.take().unwrap_or_default();
+        if let Some(write_acknowledgment) = &settings.mongodb_write_acknowledgment {
+            write_concern.w = Some(write_acknowledgment.0.clone());
+        }
+        if let Some(journal) = settings.mongodb_journal {
+            write_concern.journal = Some(journal);
+        }
+        client_options.write_concern = Some(write_concern);
+    }
+
+    // Record durations of MongoDB operations.
+    client_options.command_event_handler = Some(Arc::new(MongoDbCommandMetrics::new()));
+
     // Get a handle to the deployment.
-    Client::with_options(client_options).unwrap()
+    let client = Client::with_options(client_options).map_err(|error| {
+        StartupError::Config(format!("MongoDB client could not be created: {}.", error))
+    })?;
+    client
+        .database("admin")
+        .run_command(doc! {"ping": 1}, None)
+        .await
+        .map_err(|error| StartupError::DatabaseUnreachable(format!("{}.", error)))?;
+    Ok(client)
+}
+
+/// Initializes the OpenTelemetry meter provider exporting metrics via OTLP.
+///
+/// The OTLP endpoint is configured with the standard `$OTEL_EXPORTER_OTLP_ENDPOINT` variable.
+/// If the exporter can not be built, metrics are recorded by the default no-op meter provider.
+///
+/// * `settings` - Service settings defining export interval, timeout and headers.
+fn init_otlp(settings: &Settings) -> Option<SdkMeterProvider> {
+    let timeout = Duration::from_millis(settings.otlp_metric_export_timeout_millis);
+    let exporter = opentelemetry_otlp::new_exporter()
+        .http()
+        .with_timeout(timeout)
+        .with_headers(settings.otlp_headers.0.clone());
+    let maybe_meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(runtime::Tokio)
+        .with_exporter(exporter)
+        .with_period(Duration::from_millis(
+            settings.otlp_metric_export_interval_millis,
+        ))
+        .with_timeout(timeout)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            "wishlist",
+        )]))
+        .build();
+    match maybe_meter_provider {
+        Ok(meter_provider) => {
+            global::set_meter_provider(meter_provider.clone());
+            Some(meter_provider)
+        }
+        Err(error) => {
+            warn!(
+                "Building OTLP metrics exporter failed, metrics are not exported: {}",
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Initializes the OpenTelemetry tracer provider exporting traces via OTLP.
+///
+/// Traces are sampled according to the sampler of the service settings.
+/// If the exporter can not be built, spans are recorded by the default no-op tracer provider.
+///
+/// * `settings` - Service settings defining export headers and sampling.
+fn init_otlp_tracing(settings: &Settings) {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let ratio = settings.traces_sampler_ratio;
+    let sampler = match settings.traces_sampler {
+        TracesSampler::AlwaysOn => Sampler::AlwaysOn,
+        TracesSampler::AlwaysOff => Sampler::AlwaysOff,
+        TracesSampler::TraceIdRatio => Sampler::TraceIdRatioBased(ratio),
+        TracesSampler::ParentBasedAlwaysOn => Sampler::ParentBased(Box::new(Sampler::AlwaysOn)),
+        TracesSampler::ParentBasedAlwaysOff => Sampler::ParentBased(Box::new(Sampler::AlwaysOff)),
+        TracesSampler::ParentBasedTraceIdRatio => {
+            Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(ratio)))
+        }
+    };
+    let exporter = opentelemetry_otlp::new_exporter()
+        .http()
+        .with_timeout(Duration::from_millis(
+            settings.otlp_metric_export_timeout_millis,
+        ))
+        .with_headers(settings.otlp_headers.0.clone());
+    let trace_config = trace::config()
+        .with_sampler(sampler)
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            "wishlist",
+        )]));
+    let result = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(trace_config)
+        .install_batch(runtime::Tokio);
+    if let Err(error) = result {
+        warn!(
+            "Building OTLP trace exporter failed, traces are not exported: {}",
+            error
+        );
+    }
+}
+
+/// Loads the allowed GraphQL documents of the operation allow-list execution mode.
+///
+/// Fails if the directory can not be read.
+///
+/// * `directory` - Directory containing the allowed GraphQL documents.
+fn load_operation_allow_list(directory: &str) -> Result<OperationAllowList, StartupError> {
+    match OperationAllowList::from_directory(Path::new(directory)) {
+        Ok(operation_allow_list) => {
+            info!(
+                "Operation allow-list active with {} documents of: `{}`.",
+                operation_allow_list.len(),
+                directory
+            );
+            Ok(operation_allow_list)
+        }
+        Err(error) => Err(StartupError::Config(format!(
+            "Loading operation allow-list of: `{}` failed: {}.",
+            directory, error
+        ))),
+    }
+}
+
+/// Returns the service state of the event handler, which is shared with GraphQL to reprocess failed events.
+///
+/// * `databases` - MongoDB databases of all tenants, to which product variants and users are replicated.
+/// * `db_client` - MongoDB default database, which stores failed events.
+/// * `state_cache` - Cache of wishlists, invalidated when wishlists are suspended by events.
+/// * `dapr_client` - Dapr client used to publish target price notifications.
+/// * `settings` - Service settings defining the catalog currency and the maximum number of events in progress.
+/// * `fault_injector` - Injector of faults of chaos experiments into the event handler.
+fn build_event_service_state(
+    databases: &[Database],
+    db_client: &Database,
+    state_cache: StateCache,
+    dapr_client: DaprClient,
+    settings: &Settings,
+    #[cfg(feature = "fault-injection")] fault_injector: FaultInjector,
+) -> HttpEventServiceState {
+    let product_variant_collections: Vec<mongodb::Collection<ProductVariant>> = databases
+        .iter()
+        .map(|db_client| db_client.collection::<ProductVariant>("product_variants"))
+        .collect();
+    let user_collections: Vec<mongodb::Collection<User>> = databases
+        .iter()
+        .map(|db_client| db_client.collection::<User>("users"))
+        .collect();
+    let wishlist_collections: Vec<mongodb::Collection<Wishlist>> = databases
+        .iter()
+        .map(|db_client| db_client.collection::<Wishlist>("wishlists"))
+        .collect();
+
+    HttpEventServiceState {
+        product_variant_collections,
+        user_collections,
+        wishlist_collections,
+        state_cache,
+        catalog_currency: settings.catalog_currency.clone(),
+        event_metrics: EventMetrics::new(),
+        failed_event_collection: db_client.collection::<FailedEvent>(FAILED_EVENT_COLLECTION),
+        aggregate_locks: AggregateLocks::new(),
+        in_flight_permits: Arc::new(Semaphore::new(settings.max_in_flight_events as usize)),
+        draining: Arc::new(AtomicBool::new(false)),
+        dapr_client,
+        #[cfg(feature = "fault-injection")]
+        fault_injector,
+    }
 }
 
 /// Returns Router that establishes connection to Dapr.
 ///
 /// Adds endpoints to define pub/sub interaction with Dapr.
 ///
-/// * `db_client` - MongoDB database client.
-async fn build_dapr_router(db_client: Database) -> Router {
-    let product_variant_collection: mongodb::Collection<ProductVariant> =
-        db_client.collection::<ProductVariant>("product_variants");
-    let user_collection: mongodb::Collection<User> = db_client.collection::<User>("users");
-
+/// * `event_service_state` - Service state of the event handler.
+async fn build_dapr_router(event_service_state: HttpEventServiceState) -> Router {
     // Define routes.
-    let app = Router::new()
+    Router::new()
         .route("/dapr/subscribe", get(list_topic_subscriptions))
         .route("/on-topic-event", post(on_topic_event))
-        .with_state(HttpEventServiceState {
-            product_variant_collection,
-            user_collection,
-        });
-    app
+        .with_state(event_service_state)
 }
 
-/// Command line argument to toggle schema generation instead of service execution.
+/// Command line arguments to toggle schema generation or checking instead of service execution.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Generates GraphQL schema in `./schemas/wishlist.graphql`.
     #[arg(long)]
     generate_schema: bool,
+    /// Checks that `./schemas/wishlist.graphql` matches the generated GraphQL schema, prints a diff otherwise.
+    #[arg(long, conflicts_with = "generate_schema")]
+    check_schema: bool,
 }
 
-/// Activates logger and parses argument for optional schema generation. Otherwise starts gRPC and GraphQL server.
+/// Activates logger and parses arguments for optional schema generation or checking. Otherwise starts gRPC and GraphQL server.
+///
+/// Exits with the exit code of the startup error if the service can not be started, see `StartupError`.
 #[tokio::main]
-async fn main() -> std::io::Result<()> {
-    simple_logger::init_with_level(Level::Warn).unwrap();
+async fn main() -> ExitCode {
+    if let Err(error) = simple_logger::init_with_level(Level::Warn) {
+        eprintln!("Initializing logger failed: {}", error);
+    }
 
     let args = Args::parse();
-    if args.generate_schema {
-        let schema = Schema::build(Query, Mutation, EmptySubscription).finish();
-        let mut file = File::create("./schemas/wishlist.graphql")?;
-        let sdl_export_options = SDLExportOptions::new().federation();
-        let schema_sdl = schema.sdl_with_options(sdl_export_options);
-        file.write_all(schema_sdl.as_bytes())?;
-        info!("GraphQL schema: ./schemas/wishlist.graphql was successfully generated!");
-    } else {
-        start_service().await;
+    let result = match (args.generate_schema, args.check_schema) {
+        (true, _) => generate_schema(),
+        (_, true) => check_schema(&schema_sdl(), "./schemas/wishlist.graphql").map(|()| {
+            info!("GraphQL schema: ./schemas/wishlist.graphql is up to date.");
+        }),
+        _ => start_service().await,
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            error!("{}", error);
+            error.exit_code()
+        }
     }
+}
+
+/// Generates the federated GraphQL schema as SDL.
+fn schema_sdl() -> String {
+    let schema = Schema::build(Query, Mutation, Subscription)
+        .register_output_type::<Ownable>()
+        .finish();
+    let sdl_export_options = SDLExportOptions::new().federation();
+    schema.sdl_with_options(sdl_export_options)
+}
+
+/// Generates the GraphQL schema in `./schemas/wishlist.graphql`.
+fn generate_schema() -> Result<(), StartupError> {
+    File::create("./schemas/wishlist.graphql")
+        .and_then(|mut file| file.write_all(schema_sdl().as_bytes()))
+        .map_err(|error| {
+            StartupError::Io(format!(
+                "Writing GraphQL schema: ./schemas/wishlist.graphql failed: {}.",
+                error
+            ))
+        })?;
+    info!("GraphQL schema: ./schemas/wishlist.graphql was successfully generated!");
     Ok(())
 }
 
+/// State of the GraphQL handler.
+#[derive(Clone)]
+struct GraphQLState {
+    /// GraphQL schema used by handler.
+    schema: Schema<Query, Mutation, Subscription>,
+    /// Authenticator of the callers of requests.
+    authenticator: RequestAuthenticator,
+    /// Tenant of requests which do not specify a tenant.
+    default_tenant_id: TenantId,
+    /// Resolver of the MongoDB database of the tenant of a request.
+    database_router: DatabaseRouter,
+}
+
 /// Describes the handler for GraphQL requests.
 ///
-/// Parses the `Authorized-User` header and writes it in the context data of the specfic request.
-/// Then executes the GraphQL schema with the request.
+/// Writes the request data derived from the headers in the context data of the specific request, see `request_data`.
+/// Responds with the status code of `request_data` if the tenant of the request is rejected.
+/// Then executes the GraphQL schema with the request in a span continuing the trace of the W3C `traceparent` header.
 ///
-/// * `schema` - GraphQL schema used by handler.
+/// * `state` - GraphQL schema and authenticator used by handler.
 /// * `headers` - Header map containing headers of request.
 /// * `request` - GraphQL request.
 async fn graphql_handler(
-    State(schema): State<Schema<Query, Mutation, EmptySubscription>>,
+    State(state): State<GraphQLState>,
     headers: HeaderMap,
     request: GraphQLRequest,
-) -> GraphQLResponse {
+) -> Result<GraphQLResponse, (StatusCode, String)> {
     let mut request = request.into_inner();
-    if let Ok(authenticate_user_header) = AuthorizedUserHeader::try_from(&headers) {
-        request = request.data(authenticate_user_header);
+    request.data = request_data(&state, &headers, Some(&request)).await?;
+    let parent_context = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(&headers))
+    });
+    let span = global::tracer("wishlist").start_with_context("graphql_request", &parent_context);
+    Ok(state
+        .schema
+        .execute(request)
+        .with_context(parent_context.with_span(span))
+        .await
+        .into())
+}
+
+/// Describes the handler for GraphQL subscriptions over WebSocket connections.
+///
+/// Authenticates the subscriber once when the connection is established, see `request_data`.
+/// Responds with the status code of `request_data` instead of upgrading if the tenant of the connection is rejected.
+///
+/// * `state` - GraphQL schema and authenticator used by handler.
+/// * `headers` - Header map containing headers of the connection request.
+/// * `protocol` - GraphQL WebSocket sub-protocol of the connection.
+/// * `upgrade` - Upgrade of the connection to a WebSocket connection.
+async fn graphql_subscription_handler(
+    State(state): State<GraphQLState>,
+    headers: HeaderMap,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let data = request_data(&state, &headers, None).await?;
+    Ok(upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, state.schema, protocol)
+                .with_data(data)
+                .serve()
+        }))
+}
+
+/// Derives the context data of a request from its headers.
+///
+/// Authenticates the caller with the shared `RequestAuthenticator` and parses the `Idempotency-Key` header.
+/// If the authentication fails, its `AuthenticationError` is kept, so resolvers report why the caller is unauthenticated.
+/// The tenant of the request is read from the credentials, the `X-Tenant-Id` header or defaults to the configured default tenant.
+/// Returns the HTTP status code and the reason if the tenant is rejected, see `resolve_tenant_id`.
+/// Resolvers of the request access the MongoDB database of the tenant, entities are loaded in batches per request.
+///
+/// * `state` - GraphQL schema and authenticator used by handler.
+/// * `headers` - Header map containing headers of request.
+/// * `request` - GraphQL request, `None` for subscriptions over WebSocket connections.
+async fn request_data(
+    state: &GraphQLState,
+    headers: &HeaderMap,
+    request: Option<&Request>,
+) -> Result<Data, (StatusCode, String)> {
+    let mut data = Data::default();
+    let mut credential_tenant_id = None;
+    match state.authenticator.authenticate(headers, request).await {
+        Ok(authentication) => {
+            match authentication.principal {
+                Principal::User(authorized_user_header) => data.insert(authorized_user_header),
+                Principal::ApiKey(api_key_principal) => data.insert(api_key_principal),
+            }
+            credential_tenant_id = authentication.credential_tenant_id;
+        }
+        Err(error) => {
+            if let AuthenticationError::Rejected(message) = &error {
+                info!("Rejected credentials: {}", message);
+            }
+            data.insert(error);
+        }
+    }
+    let tenant_id = resolve_tenant_id(headers, credential_tenant_id, &state.default_tenant_id)
+        .map_err(|(status_code, message)| {
+            info!("Rejected tenant identifier: {}", message);
+            (status_code, message)
+        })?;
+    let db_client = state.database_router.database(&tenant_id);
+    data.insert(DataLoader::new(
+        ObjectLoader::<Wishlist>::new(db_client),
+        tokio::spawn,
+    ));
+    data.insert(DataLoader::new(
+        ObjectLoader::<User>::new(db_client),
+        tokio::spawn,
+    ));
+    data.insert(DataLoader::new(
+        ObjectLoader::<ProductVariantMetadata>::new(db_client),
+        tokio::spawn,
+    ));
+    data.insert(db_client.clone());
+    data.insert(tenant_id);
+    if let Some(idempotency_key) = headers
+        .get("Idempotency-Key")
+        .and_then(|idempotency_key| idempotency_key.to_str().ok())
+    {
+        data.insert(IdempotencyKey(idempotency_key.to_string()));
     }
-    schema.execute(request).await.into()
+    Ok(data)
 }
 
-/// Starts wishlist service on port 8000.
-async fn start_service() {
-    let client = db_connection().await;
-    let db_client: Database = client.database("wishlist-database");
+/// Builds the validators applied to wishlist mutations before they are stored.
+///
+/// Deployments register their custom validators here, see `MutationValidator`.
+///
+/// * `settings` - Service settings defining the built-in validators.
+fn build_mutation_validators(settings: &Settings) -> MutationValidators {
+    MutationValidators::default().with(MaxLengthValidator {
+        max_name_length: settings.max_wishlist_name_length,
+        max_description_length: settings.max_wishlist_description_length,
+    })
+}
+
+/// Spawns the periodic background jobs of the wishlist service.
+///
+/// Returns the batcher of the events of the jobs, which is flushed on shutdown.
+///
+/// * `databases` - MongoDB databases of all tenants.
+/// * `dapr_client` - Dapr client used to publish events of jobs.
+/// * `settings` - Service settings defining the job intervals.
+fn spawn_jobs(
+    databases: &[Database],
+    dapr_client: &DaprClient,
+    settings: &Settings,
+) -> EventBatcher {
+    let wishlist_collections: Vec<Collection<Wishlist>> = databases
+        .iter()
+        .map(|db_client| db_client.collection::<Wishlist>("wishlists"))
+        .collect();
+    let reconciliation_collections = wishlist_collections.clone();
+    spawn_periodic_job(
+        "item_count_reconciliation",
+        Duration::from_secs(settings.item_count_reconciliation_interval_secs),
+        move || {
+            let wishlist_collections = reconciliation_collections.clone();
+            async move {
+                for wishlist_collection in &wishlist_collections {
+                    reconcile_item_counts(wishlist_collection).await?;
+                }
+                Ok(())
+            }
+        },
+    );
+    let event_batcher = EventBatcher::spawn(
+        dapr_client.clone(),
+        settings.event_batch_max_size,
+        Duration::from_millis(settings.event_batch_flush_interval_millis),
+    );
+    let digest_collections: Vec<(
+        Collection<Wishlist>,
+        Collection<DigestSubscription>,
+        Collection<ProductVariantMetadata>,
+    )> = databases
+        .iter()
+        .map(|db_client| {
+            (
+                db_client.collection::<Wishlist>("wishlists"),
+                db_client.collection::<DigestSubscription>(DIGEST_SUBSCRIPTION_COLLECTION),
+                db_client.collection::<ProductVariantMetadata>("product_variants"),
+            )
+        })
+        .collect();
+    let digest_dapr_client = dapr_client.clone();
+    let digest_period_millis = settings.wishlist_digest_period_secs as i64 * 1000;
+    spawn_periodic_job(
+        "wishlist_digest",
+        Duration::from_secs(settings.wishlist_digest_interval_secs),
+        move || {
+            let digest_collections = digest_collections.clone();
+            let dapr_client = digest_dapr_client.clone();
+            async move {
+                for (wishlist_collection, subscription_collection, product_variant_collection) in
+                    &digest_collections
+                {
+                    publish_wishlist_digests(
+                        wishlist_collection,
+                        subscription_collection,
+                        product_variant_collection,
+                        &dapr_client,
+                        digest_period_millis,
+                    )
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    );
+    let state_cache = StateCache::new(dapr_client.clone(), settings);
+    let drift_detection_collections: Vec<(
+        Collection<Wishlist>,
+        Collection<CatalogDriftMiss>,
+        Collection<AuditEntry>,
+    )> = databases
+        .iter()
+        .map(|db_client| {
+            (
+                db_client.collection::<Wishlist>("wishlists"),
+                db_client.collection::<CatalogDriftMiss>(CATALOG_DRIFT_MISS_COLLECTION),
+                db_client.collection::<AuditEntry>("audit_entries"),
+            )
+        })
+        .collect();
+    let drift_detection_state_cache = state_cache.clone();
+    let drift_detection = CatalogDriftDetection {
+        dapr_client: dapr_client.clone(),
+        catalog_app_id: settings.catalog_app_id.clone(),
+        sample_size: settings.catalog_drift_sample_size,
+        auto_remove: settings.catalog_drift_auto_remove,
+        auto_remove_after_misses: settings.catalog_drift_auto_remove_after_misses,
+    };
+    spawn_periodic_job(
+        "catalog_drift_detection",
+        Duration::from_secs(settings.catalog_drift_detection_interval_secs),
+        move || {
+            let drift_detection_collections = drift_detection_collections.clone();
+            let state_cache = drift_detection_state_cache.clone();
+            let drift_detection = drift_detection.clone();
+            async move {
+                for (wishlist_collection, miss_collection, audit_collection) in
+                    &drift_detection_collections
+                {
+                    detect_catalog_drift(
+                        wishlist_collection,
+                        miss_collection,
+                        audit_collection,
+                        &state_cache,
+                        &drift_detection,
+                    )
+                    .await?;
+                }
+                Ok(())
+            }
+        },
+    );
+    let expiration_event_batcher = event_batcher.clone();
+    spawn_periodic_job(
+        "wishlist_expiration",
+        Duration::from_secs(settings.wishlist_expiration_interval_secs),
+        move || {
+            let wishlist_collections = wishlist_collections.clone();
+            let event_batcher = expiration_event_batcher.clone();
+            let state_cache = state_cache.clone();
+            async move {
+                for wishlist_collection in &wishlist_collections {
+                    archive_expired_wishlists(wishlist_collection, &event_batcher, &state_cache)
+                        .await?;
+                }
+                Ok(())
+            }
+        },
+    );
+    event_batcher
+}
+
+/// Starts wishlist service on port 8080.
+///
+/// Returns when the server fails or after a graceful shutdown, or fails right away if the service can not be started.
+async fn start_service() -> Result<(), StartupError> {
+    let mut settings = Settings::from_env().map_err(StartupError::InvalidSettings)?;
+    let dapr_client = DaprClient::from_env();
+    if let Some(otlp_headers) =
+        load_secret(&dapr_client, &settings, "OTEL_EXPORTER_OTLP_HEADERS").await
+    {
+        settings.otlp_headers = otlp_headers.parse().map_err(|error| {
+            StartupError::Config(format!("Loading OTLP headers failed: {}", error))
+        })?;
+    }
+    let _meter_provider = init_otlp(&settings);
+    register_runtime_metrics();
+    init_otlp_tracing(&settings);
+    let client = db_connection(&dapr_client, &settings).await?;
+    let database_router =
+        DatabaseRouter::new(&client, "wishlist-database", &settings.tenant_databases);
+    let db_client: Database = database_router.default_database().clone();
+    let databases = database_router.databases();
 
-    let schema = Schema::build(Query, Mutation, EmptySubscription)
-        .extension(Logger)
+    let default_tenant_id = TenantId::parse(&settings.default_tenant_id).map_err(|error| {
+        StartupError::Config(format!("$DEFAULT_TENANT_ID is invalid: {}", error.message))
+    })?;
+    assign_default_tenant(&db_client, &default_tenant_id).await;
+    for database in &databases {
+        create_indexes(database, &settings).await;
+        enable_change_stream_images(database).await;
+    }
+    let event_batcher = spawn_jobs(&databases, &dapr_client, &settings);
+    if settings.reconcile_projections_on_startup {
+        spawn_startup_reconciliation(databases.clone(), dapr_client.clone(), settings.clone());
+    }
+    if settings.change_data_capture_enabled {
+        for database in &databases {
+            spawn_change_data_capture(database.clone(), dapr_client.clone());
+        }
+    }
+    let status_router = Router::new()
+        .route("/status", get(status))
+        .with_state(StatusState::new(db_client.clone(), dapr_client.clone()));
+
+    let oidc_authenticator = match &settings.oidc_issuer_url {
+        Some(oidc_issuer_url) => {
+            match OidcAuthenticator::discover(oidc_issuer_url, &settings).await {
+                Ok(oidc_authenticator) => Some(Arc::new(oidc_authenticator)),
+                Err(error) => {
+                    return Err(StartupError::DependencyUnreachable(format!(
+                        "Discovering OpenID Connect issuer: `{}` failed: {}",
+                        oidc_issuer_url, error.message
+                    )))
+                }
+            }
+        }
+        None => None,
+    };
+    let authenticator = RequestAuthenticator {
+        oidc_authenticator,
+        api_key_collection: db_client.collection::<ApiKey>("api_keys"),
+        default_tenant_id: default_tenant_id.clone(),
+    };
+    let mut schema_builder = Schema::build(Query, Mutation, Subscription)
+        .register_output_type::<Ownable>()
+        .extension(OperationLogger {
+            level: settings.operation_log_level,
+        })
+        .extension(OperationMetrics {
+            metrics: GraphQLMetrics::new(),
+            record_operation_names: settings.operation_allow_list_dir.is_some(),
+        })
+        .extension(ValidationErrorCode)
+        .extension(PanicGuard)
+        .extension(ResponseSizeBudget {
+            max_nodes: settings.max_response_nodes,
+            default_page_size: settings.default_page_size,
+        });
+    #[cfg(feature = "fault-injection")]
+    let fault_injector = FaultInjector::spawn(
+        settings.experiment_config_url.clone(),
+        Duration::from_millis(settings.experiment_config_poll_interval_millis),
+    );
+    #[cfg(feature = "fault-injection")]
+    {
+        schema_builder = schema_builder.extension(FaultInjection(fault_injector.clone()));
+    }
+    if let Some(operation_allow_list_dir) = &settings.operation_allow_list_dir {
+        schema_builder =
+            schema_builder.extension(load_operation_allow_list(operation_allow_list_dir)?);
+    }
+    let state_cache = StateCache::new(dapr_client.clone(), &settings);
+    let event_service_state = build_event_service_state(
+        &databases,
+        &db_client,
+        state_cache.clone(),
+        dapr_client.clone(),
+        &settings,
+        #[cfg(feature = "fault-injection")]
+        fault_injector,
+    );
+    let shutdown = drain_on_shutdown(
+        event_service_state.clone(),
+        u32::try_from(settings.max_in_flight_events).unwrap_or(u32::MAX),
+        event_batcher,
+        Duration::from_secs(settings.shutdown_drain_timeout_secs),
+    );
+    let graphql_ide_page = GraphQLIdePage::from_settings(&settings);
+    let graphql_path = graphql_endpoint_path(&settings.graphql_path);
+    let subscription_path = format!("{}/ws", settings.graphql_path.0);
+    let permissive_roles = settings.permissive_roles.clone();
+    let schema = schema_builder
+        .data(client)
         .data(db_client.clone())
+        .data(database_router.clone())
+        .data(state_cache)
+        .data(event_service_state.clone())
+        .data(ContentPolicy::from_settings(&settings))
+        .data(build_mutation_validators(&settings))
+        .data(dapr_client)
+        .data(settings)
         .enable_federation()
         .finish();
 
+    let mut graphql_route: MethodRouter<GraphQLState> = post(graphql_handler);
+    if let Some(graphql_ide_page) = graphql_ide_page.clone() {
+        graphql_route = graphql_route.get(|headers: HeaderMap| async move {
+            response::Html(graphql_ide_page.render(&headers))
+        });
+    }
     let graphiql = Router::new()
-        .route("/", get(graphiql).post(graphql_handler))
+        .route(&graphql_path, graphql_route)
+        .route(&subscription_path, get(graphql_subscription_handler))
         .route("/health", get(StatusCode::OK))
-        .with_state(schema);
-    let dapr_router = build_dapr_router(db_client).await;
-    let app = Router::new().merge(graphiql).merge(dapr_router);
+        .with_state(GraphQLState {
+            schema,
+            authenticator: authenticator.clone(),
+            default_tenant_id: default_tenant_id.clone(),
+            database_router: database_router.clone(),
+        });
+    let sse_router = Router::new()
+        .route("/sse/wishlists/:id", get(wishlist_updates))
+        .with_state(SseState {
+            authenticator: authenticator.clone(),
+            database_router: database_router.clone(),
+            default_tenant_id: default_tenant_id.clone(),
+            permissive_roles: permissive_roles.clone(),
+        });
+    let export_router = Router::new()
+        .route("/export/wishlists/:id", get(export_wishlist))
+        .with_state(WishlistExportState {
+            authenticator,
+            database_router: database_router.clone(),
+            default_tenant_id,
+            permissive_roles,
+        });
+    let calendar_router = Router::new()
+        .route("/calendar/:file_name", get(calendar))
+        .with_state(CalendarState { database_router });
+    let dapr_router = build_dapr_router(event_service_state).await;
+    let app = Router::new()
+        .merge(graphiql)
+        .merge(sse_router)
+        .merge(export_router)
+        .merge(calendar_router)
+        .merge(dapr_router)
+        .merge(status_router)
+        .layer(CatchPanicLayer::custom(handle_panic));
 
-    info!("GraphiQL IDE: http://0.0.0.0:8080");
-    Server::bind(&"0.0.0.0:8080".parse().unwrap())
+    let listener = TcpListener::bind(("0.0.0.0", PORT)).map_err(|error| match error.kind() {
+        io::ErrorKind::AddrInUse => StartupError::PortInUse(PORT),
+        _ => StartupError::Io(format!("Binding port {} failed: {}.", PORT, error)),
+    })?;
+    if graphql_ide_page.is_some() {
+        info!("GraphQL IDE: http://0.0.0.0:{}{}", PORT, graphql_path);
+    }
+    Server::from_tcp(listener)
+        .map_err(|error| StartupError::Io(format!("Starting HTTP server failed: {}.", error)))?
         .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown)
         .await
-        .unwrap();
+        .map_err(|error| StartupError::Io(format!("HTTP server failed: {}.", error)))
 }
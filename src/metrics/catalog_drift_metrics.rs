@@ -0,0 +1,21 @@
+use opentelemetry::global;
+
+/// Records a drift detection run against the catalog in the counters `catalog_drift_checked_total` and `catalog_drift_missing_total`.
+///
+/// The ratio of both counters estimates the share of wishlist items referencing product variants missing in the catalog.
+///
+/// * `checked_count` - Number of sampled product variants checked against the catalog.
+/// * `missing_count` - Number of sampled product variants missing in the catalog.
+pub fn record_catalog_drift(checked_count: u64, missing_count: u64) {
+    let meter = global::meter("wishlist");
+    meter
+        .u64_counter("catalog_drift_checked_total")
+        .with_description("Number of product variants of wishlists checked against the catalog.")
+        .init()
+        .add(checked_count, &[]);
+    meter
+        .u64_counter("catalog_drift_missing_total")
+        .with_description("Number of product variants of wishlists missing in the catalog.")
+        .init()
+        .add(missing_count, &[]);
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram, Unit},
+    KeyValue,
+};
+
+/// Metrics of the processing of incoming events, with the attribute `topic`.
+///
+/// Records the counters `events_received_total`, `event_failures_total` and `events_rejected_total`
+/// and the histograms `event_handler_duration_seconds` and `event_lag_seconds`.
+#[derive(Clone)]
+pub struct EventMetrics {
+    received: Counter<u64>,
+    failures: Counter<u64>,
+    rejected: Counter<u64>,
+    handler_duration: Histogram<f64>,
+    lag: Histogram<f64>,
+}
+
+impl EventMetrics {
+    /// Constructs the event metrics with instruments of the global meter provider.
+    pub fn new() -> Self {
+        let meter = global::meter("wishlist");
+        Self {
+            received: meter
+                .u64_counter("events_received_total")
+                .with_description("Number of received events.")
+                .init(),
+            failures: meter
+                .u64_counter("event_failures_total")
+                .with_description("Number of events whose handling failed.")
+                .init(),
+            rejected: meter
+                .u64_counter("events_rejected_total")
+                .with_description(
+                    "Number of events rejected for retry as too many events were in progress or the service was shutting down.",
+                )
+                .init(),
+            handler_duration: meter
+                .f64_histogram("event_handler_duration_seconds")
+                .with_description("Duration of handling events.")
+                .with_unit(Unit::new("s"))
+                .init(),
+            lag: meter
+                .f64_histogram("event_lag_seconds")
+                .with_description(
+                    "Duration between the publication of events and the start of their handling.",
+                )
+                .with_unit(Unit::new("s"))
+                .init(),
+        }
+    }
+
+    /// Records a received event and its lag.
+    ///
+    /// * `topic` - Topic of event.
+    /// * `lag` - Duration between the publication of the event and now, `None` if the event has no timestamp.
+    pub fn record_received(&self, topic: &str, lag: Option<Duration>) {
+        let attributes = [KeyValue::new("topic", topic.to_string())];
+        self.received.add(1, &attributes);
+        if let Some(lag) = lag {
+            self.lag.record(lag.as_secs_f64(), &attributes);
+        }
+    }
+
+    /// Records a handled event.
+    ///
+    /// * `topic` - Topic of event.
+    /// * `duration` - Duration of handling the event.
+    /// * `succeeded` - Whether the event was handled successfully.
+    pub fn record_handled(&self, topic: &str, duration: Duration, succeeded: bool) {
+        let attributes = [KeyValue::new("topic", topic.to_string())];
+        self.handler_duration
+            .record(duration.as_secs_f64(), &attributes);
+        if !succeeded {
+            self.failures.add(1, &attributes);
+        }
+    }
+
+    /// Records an event which was rejected for retry, as too many events were in progress or the service was shutting down.
+    ///
+    /// * `topic` - Topic of event.
+    pub fn record_rejected(&self, topic: &str) {
+        self.rejected
+            .add(1, &[KeyValue::new("topic", topic.to_string())]);
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram, Unit},
+    KeyValue,
+};
+
+/// Metrics of executed GraphQL operations, with the attributes `operation_name` and `type`.
+///
+/// Records the counter `graphql_requests_total` and the histogram `graphql_request_duration_seconds`.
+#[derive(Clone)]
+pub struct GraphQLMetrics {
+    requests: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+impl GraphQLMetrics {
+    /// Constructs the GraphQL metrics with instruments of the global meter provider.
+    pub fn new() -> Self {
+        let meter = global::meter("wishlist");
+        Self {
+            requests: meter
+                .u64_counter("graphql_requests_total")
+                .with_description("Number of executed GraphQL operations.")
+                .init(),
+            duration: meter
+                .f64_histogram("graphql_request_duration_seconds")
+                .with_description("Duration of executing GraphQL operations.")
+                .with_unit(Unit::new("s"))
+                .init(),
+        }
+    }
+
+    /// Records an executed operation.
+    ///
+    /// * `operation_name` - Name of operation, `anonymous` for operations without name.
+    /// * `operation_type` - Type of operation: `query`, `mutation` or `subscription`.
+    /// * `duration` - Duration of executing the operation.
+    pub fn record_executed(&self, operation_name: &str, operation_type: &str, duration: Duration) {
+        let attributes = [
+            KeyValue::new("operation_name", operation_name.to_string()),
+            KeyValue::new("type", operation_type.to_string()),
+        ];
+        self.requests.add(1, &attributes);
+        self.duration.record(duration.as_secs_f64(), &attributes);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod catalog_drift_metrics;
+pub mod event_metrics;
+pub mod graphql_metrics;
+pub mod mongodb_command_metrics;
+pub mod panic_metrics;
+pub mod runtime_metrics;
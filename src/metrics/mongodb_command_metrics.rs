@@ -0,0 +1,76 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use mongodb::event::command::{
+    CommandEventHandler, CommandFailedEvent, CommandStartedEvent, CommandSucceededEvent,
+};
+use opentelemetry::{
+    global,
+    metrics::{Histogram, Unit},
+    KeyValue,
+};
+
+/// MongoDB command event handler recording the duration of MongoDB operations.
+///
+/// Records the histogram `mongodb_operation_duration_seconds` with the attributes `operation` and `collection`.
+pub struct MongoDbCommandMetrics {
+    operation_duration: Histogram<f64>,
+    /// Collections of started commands, referenced by request id.
+    started_command_collections: Mutex<HashMap<i32, String>>,
+}
+
+impl MongoDbCommandMetrics {
+    /// Constructs the command event handler with a histogram of the global meter provider.
+    pub fn new() -> Self {
+        let meter = global::meter("wishlist");
+        let operation_duration = meter
+            .f64_histogram("mongodb_operation_duration_seconds")
+            .with_description("Duration of MongoDB operations.")
+            .with_unit(Unit::new("s"))
+            .init();
+        Self {
+            operation_duration,
+            started_command_collections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the duration of a finished command.
+    ///
+    /// * `request_id` - Request id of command.
+    /// * `command_name` - Name of command, used as operation.
+    /// * `duration` - Duration of command.
+    fn record(&self, request_id: i32, command_name: String, duration: Duration) {
+        let collection = self
+            .started_command_collections
+            .lock()
+            .ok()
+            .and_then(|mut collections| collections.remove(&request_id))
+            .unwrap_or_default();
+        let attributes = [
+            KeyValue::new("operation", command_name),
+            KeyValue::new("collection", collection),
+        ];
+        self.operation_duration
+            .record(duration.as_secs_f64(), &attributes);
+    }
+}
+
+impl CommandEventHandler for MongoDbCommandMetrics {
+    /// Remembers the collection of a started command.
+    ///
+    /// The collection is the string value of the command name key, e.g. `{"find": "wishlists"}`.
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        if let Ok(collection) = event.command.get_str(&event.command_name) {
+            if let Ok(mut collections) = self.started_command_collections.lock() {
+                collections.insert(event.request_id, collection.to_string());
+            }
+        }
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.record(event.request_id, event.command_name, event.duration);
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.record(event.request_id, event.command_name, event.duration);
+    }
+}
@@ -0,0 +1,12 @@
+use opentelemetry::{global, KeyValue};
+
+/// Records a caught panic in the counter `panics_total` with the attribute `source`.
+///
+/// * `source` - Part of the service the panic was caught in, e.g. `http` or `resolver`.
+pub fn record_panic(source: &'static str) {
+    global::meter("wishlist")
+        .u64_counter("panics_total")
+        .with_description("Number of panics caught while handling requests.")
+        .init()
+        .add(1, &[KeyValue::new("source", source)]);
+}
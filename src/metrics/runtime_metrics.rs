@@ -0,0 +1,110 @@
+use std::fs;
+
+use opentelemetry::{global, metrics::Unit};
+use tokio::runtime::Handle;
+
+/// Registers gauges of the tokio runtime and the process, observed on each export of the global meter provider.
+///
+/// Records the gauges `tokio_workers`, `tokio_alive_tasks` and `tokio_global_queue_depth`,
+/// the counters `tokio_worker_busy_seconds_total` and `tokio_worker_parks_total` summed over all workers,
+/// and the gauges `process_resident_memory_bytes`, `process_virtual_memory_bytes` and `process_open_fds`.
+/// The busy duration is the time workers spent polling tasks, poll time histograms require `--cfg tokio_unstable`.
+/// Process gauges are read from `/proc/self` and are not observed on other platforms.
+///
+/// Must be called within the tokio runtime.
+pub fn register_runtime_metrics() {
+    let meter = global::meter("wishlist");
+    let runtime_metrics = Handle::current().metrics();
+    let workers_metrics = runtime_metrics.clone();
+    meter
+        .u64_observable_gauge("tokio_workers")
+        .with_description("Number of worker threads of the tokio runtime.")
+        .with_callback(move |observer| observer.observe(workers_metrics.num_workers() as u64, &[]))
+        .init();
+    let alive_tasks_metrics = runtime_metrics.clone();
+    meter
+        .u64_observable_gauge("tokio_alive_tasks")
+        .with_description("Number of tasks alive in the tokio runtime.")
+        .with_callback(move |observer| {
+            observer.observe(alive_tasks_metrics.num_alive_tasks() as u64, &[])
+        })
+        .init();
+    let queue_depth_metrics = runtime_metrics.clone();
+    meter
+        .u64_observable_gauge("tokio_global_queue_depth")
+        .with_description("Number of tasks scheduled in the global queue of the tokio runtime.")
+        .with_callback(move |observer| {
+            observer.observe(queue_depth_metrics.global_queue_depth() as u64, &[])
+        })
+        .init();
+    let busy_metrics = runtime_metrics.clone();
+    meter
+        .f64_observable_counter("tokio_worker_busy_seconds_total")
+        .with_description("Duration the worker threads of the tokio runtime spent polling tasks.")
+        .with_unit(Unit::new("s"))
+        .with_callback(move |observer| {
+            let busy_duration = (0..busy_metrics.num_workers())
+                .map(|worker| {
+                    busy_metrics
+                        .worker_total_busy_duration(worker)
+                        .as_secs_f64()
+                })
+                .sum();
+            observer.observe(busy_duration, &[])
+        })
+        .init();
+    meter
+        .u64_observable_counter("tokio_worker_parks_total")
+        .with_description(
+            "Number of times the worker threads of the tokio runtime parked for lack of tasks.",
+        )
+        .with_callback(move |observer| {
+            let park_count = (0..runtime_metrics.num_workers())
+                .map(|worker| runtime_metrics.worker_park_count(worker))
+                .sum();
+            observer.observe(park_count, &[])
+        })
+        .init();
+    meter
+        .u64_observable_gauge("process_resident_memory_bytes")
+        .with_description("Resident memory size of the process.")
+        .with_unit(Unit::new("By"))
+        .with_callback(|observer| {
+            if let Some(resident_memory) = read_status_bytes("VmRSS") {
+                observer.observe(resident_memory, &[])
+            }
+        })
+        .init();
+    meter
+        .u64_observable_gauge("process_virtual_memory_bytes")
+        .with_description("Virtual memory size of the process.")
+        .with_unit(Unit::new("By"))
+        .with_callback(|observer| {
+            if let Some(virtual_memory) = read_status_bytes("VmSize") {
+                observer.observe(virtual_memory, &[])
+            }
+        })
+        .init();
+    meter
+        .u64_observable_gauge("process_open_fds")
+        .with_description("Number of open file descriptors of the process.")
+        .with_callback(|observer| {
+            if let Ok(entries) = fs::read_dir("/proc/self/fd") {
+                observer.observe(entries.count() as u64, &[])
+            }
+        })
+        .init();
+}
+
+/// Reads a memory size in bytes from `/proc/self/status`, which lists sizes in kB, e.g. `VmRSS:  1024 kB`.
+///
+/// * `key` - Key of the memory size.
+fn read_status_bytes(key: &str) -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+        .and_then(|value| value.trim().strip_suffix("kB"))
+        .and_then(|kilobytes| kilobytes.trim().parse::<u64>().ok())
+        .map(|kilobytes| kilobytes * 1024)
+}
@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+use async_graphql::{Error, Result};
+use jsonwebtoken::{
+    decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, TokenData, Validation,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::authorization::AuthorizedUserHeader;
+use crate::graphql::model::uuid::Uuid;
+use crate::settings::Settings;
+use crate::tenant::TenantId;
+
+/// Minimum interval between two fetches of the signing keys, so tokens with unknown keys can not flood the issuer.
+const MIN_JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Authenticates users with OpenID Connect access tokens, for deployments outside of the MiSArch gateway.
+///
+/// Tokens are validated with the signing keys of the issuer, which are refetched when a token references an unknown key,
+/// at most once per `MIN_JWKS_REFETCH_INTERVAL`.
+pub struct OidcAuthenticator {
+    http_client: reqwest::Client,
+    issuer: String,
+    audience: Option<String>,
+    roles_claim: String,
+    tenant_claim: String,
+    jwks_uri: String,
+    jwks: RwLock<SigningKeys>,
+}
+
+/// Signing keys of the issuer and the time they were fetched.
+struct SigningKeys {
+    key_set: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Subset of the OpenID Connect discovery document.
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    jwks_uri: String,
+}
+
+impl OidcAuthenticator {
+    /// Discovers the configuration and signing keys of the OpenID Connect issuer.
+    ///
+    /// * `issuer_url` - URL of the OpenID Connect issuer.
+    /// * `settings` - Service settings defining audience, roles claim and tenant claim.
+    pub async fn discover(issuer_url: &str, settings: &Settings) -> Result<Self> {
+        let http_client = reqwest::Client::new();
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer_url.trim_end_matches('/')
+        );
+        let discovery_document: DiscoveryDocument = http_client
+            .get(discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let jwks = fetch_jwks(&http_client, &discovery_document.jwks_uri).await?;
+        Ok(Self {
+            http_client,
+            issuer: discovery_document.issuer,
+            audience: settings.oidc_audience.clone(),
+            roles_claim: settings.oidc_roles_claim.clone(),
+            tenant_claim: settings.oidc_tenant_claim.clone(),
+            jwks_uri: discovery_document.jwks_uri,
+            jwks: RwLock::new(SigningKeys {
+                key_set: jwks,
+                fetched_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// Validates an access token and maps its claims to an `Authorized-User` header and the tenant of the user.
+    ///
+    /// The `sub` claim must contain the UUID of the user. Roles are read from the configured roles claim.
+    /// The tenant is read from the configured tenant claim and is `None` if the token has no such claim.
+    ///
+    /// * `token` - Access token of the `Authorization: Bearer` HTTP header.
+    pub async fn authenticate(
+        &self,
+        token: &str,
+    ) -> Result<(AuthorizedUserHeader, Option<TenantId>)> {
+        let header = decode_header(token)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(Error::new("Symmetric token signatures are not supported."));
+        }
+        let key_id = header
+            .kid
+            .ok_or_else(|| Error::new("Token does not reference a signing key."))?;
+        let decoding_key = self.decoding_key(&key_id).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let token_data: TokenData<Value> = decode(token, &decoding_key, &validation)?;
+        let claims = token_data.claims;
+        let id = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .and_then(|sub| Uuid::parse_str(sub).ok())
+            .ok_or_else(|| Error::new("Claim `sub` of token is not a UUID."))?;
+        let role_names: Vec<&str> = self
+            .roles_claim
+            .split('.')
+            .try_fold(&claims, |claim, segment| claim.get(segment))
+            .and_then(Value::as_array)
+            .map(|roles| roles.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let tenant_id = self
+            .tenant_claim
+            .split('.')
+            .try_fold(&claims, |claim, segment| claim.get(segment))
+            .and_then(Value::as_str)
+            .map(TenantId::parse)
+            .transpose()?;
+        Ok((
+            AuthorizedUserHeader::from_role_names(id, &role_names),
+            tenant_id,
+        ))
+    }
+
+    /// Returns the decoding key of a signing key, refetching the signing keys if the key is unknown.
+    ///
+    /// Unknown keys are rejected without refetching if the signing keys were fetched within `MIN_JWKS_REFETCH_INTERVAL`.
+    ///
+    /// * `key_id` - Key id of the signing key.
+    async fn decoding_key(&self, key_id: &str) -> Result<DecodingKey> {
+        if let Some(jwk) = self.jwks.read().await.key_set.find(key_id) {
+            return Ok(DecodingKey::from_jwk(jwk)?);
+        }
+        let mut signing_keys = self.jwks.write().await;
+        if signing_keys.key_set.find(key_id).is_none()
+            && signing_keys.fetched_at.elapsed() >= MIN_JWKS_REFETCH_INTERVAL
+        {
+            signing_keys.fetched_at = Instant::now();
+            signing_keys.key_set = fetch_jwks(&self.http_client, &self.jwks_uri).await?;
+        }
+        match signing_keys.key_set.find(key_id) {
+            Some(jwk) => Ok(DecodingKey::from_jwk(jwk)?),
+            None => {
+                let message = format!("Signing key: `{}` of token is unknown.", key_id);
+                Err(Error::new(message))
+            }
+        }
+    }
+}
+
+/// Fetches the signing keys of an OpenID Connect issuer.
+///
+/// * `http_client` - HTTP client used to fetch the keys.
+/// * `jwks_uri` - URL of the JSON web key set of the issuer.
+async fn fetch_jwks(http_client: &reqwest::Client, jwks_uri: &str) -> Result<JwkSet> {
+    Ok(http_client
+        .get(jwks_uri)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
@@ -0,0 +1,54 @@
+use std::any::Any;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::error;
+use serde_json::json;
+
+use crate::graphql::model::uuid::Uuid;
+use crate::metrics::panic_metrics::record_panic;
+
+/// Error code of errors caused by panics.
+pub const INTERNAL: &str = "INTERNAL";
+
+/// Message of errors caused by panics, which does not reveal details of the panic.
+pub const INTERNAL_ERROR_MESSAGE: &str = "Internal server error.";
+
+/// Logs and counts a caught panic and returns the correlation UUID identifying it in the logs.
+///
+/// * `source` - Part of the service the panic was caught in, e.g. `http` or `resolver`.
+/// * `location` - Description of what was handled when the panic occurred.
+/// * `panic` - Payload of the panic.
+pub fn report_panic(source: &'static str, location: &str, panic: &(dyn Any + Send)) -> Uuid {
+    let correlation_id = Uuid::new();
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    error!(
+        "Panic while handling {} with correlation UUID: `{}`: {}",
+        location, correlation_id, message
+    );
+    record_panic(source);
+    correlation_id
+}
+
+/// Responds to a panic of an HTTP handler with status 500 and a GraphQL error containing the correlation UUID.
+///
+/// Used by the catch-panic layer, so the panic does not tear down the connection.
+///
+/// * `panic` - Payload of the panic.
+pub fn handle_panic(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let correlation_id = report_panic("http", "HTTP request", panic.as_ref());
+    let body = json!({
+        "errors": [{
+            "message": INTERNAL_ERROR_MESSAGE,
+            "extensions": {"code": INTERNAL, "correlationId": correlation_id},
+        }]
+    });
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+}
@@ -0,0 +1,106 @@
+use std::fs;
+
+use crate::startup_error::StartupError;
+
+/// Number of unchanged lines shown around changed lines of the diff.
+const CONTEXT_LINES: usize = 2;
+
+/// Compares the generated GraphQL schema with the committed schema file.
+///
+/// Fails with a diff of the lines if they diverge, lines only in the committed schema are prefixed with `-`,
+/// lines only in the generated schema with `+`.
+///
+/// * `schema_sdl` - Generated GraphQL schema.
+/// * `path` - Path of the committed schema file.
+pub fn check_schema(schema_sdl: &str, path: &str) -> Result<(), StartupError> {
+    let committed_sdl = fs::read_to_string(path).map_err(|error| {
+        StartupError::Io(format!(
+            "Reading GraphQL schema: {} failed: {}.",
+            path, error
+        ))
+    })?;
+    match committed_sdl == schema_sdl {
+        true => Ok(()),
+        false => Err(StartupError::SchemaMismatch(diff_lines(
+            &committed_sdl,
+            schema_sdl,
+        ))),
+    }
+}
+
+/// Builds a line diff of two texts based on their longest common subsequence of lines.
+///
+/// Unchanged lines are only shown around changed lines, skipped lines are marked with `...`.
+///
+/// * `old` - Old text.
+/// * `new` - New text.
+fn diff_lines(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut common_lengths = vec![vec![0usize; new_lines.len() + 1]; old_lines.len() + 1];
+    for i in (0..old_lines.len()).rev() {
+        for j in (0..new_lines.len()).rev() {
+            common_lengths[i][j] = match old_lines[i] == new_lines[j] {
+                true => common_lengths[i + 1][j + 1] + 1,
+                false => common_lengths[i + 1][j].max(common_lengths[i][j + 1]),
+            };
+        }
+    }
+    let mut diff: Vec<(char, &str)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old_lines.len() || j < new_lines.len() {
+        if i < old_lines.len() && j < new_lines.len() && old_lines[i] == new_lines[j] {
+            diff.push((' ', old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_lines.len()
+            && (j == new_lines.len() || common_lengths[i + 1][j] >= common_lengths[i][j + 1])
+        {
+            diff.push(('-', old_lines[i]));
+            i += 1;
+        } else {
+            diff.push(('+', new_lines[j]));
+            j += 1;
+        }
+    }
+    let changed: Vec<bool> = diff.iter().map(|(marker, _)| *marker != ' ').collect();
+    let shown: Vec<bool> = (0..diff.len())
+        .map(|index| {
+            let start = index.saturating_sub(CONTEXT_LINES);
+            let end = (index + CONTEXT_LINES + 1).min(diff.len());
+            changed[start..end].iter().any(|changed| *changed)
+        })
+        .collect();
+    let mut lines = vec![];
+    for (index, (marker, line)) in diff.iter().enumerate() {
+        if shown[index] {
+            lines.push(format!("{} {}", marker, line));
+        } else if index == 0 || shown[index - 1] {
+            lines.push("...".to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lines_marks_removed_and_added_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc");
+        assert_eq!(diff, "  a\n- b\n+ x\n  c");
+    }
+
+    #[test]
+    fn diff_lines_collapses_unchanged_lines_outside_of_context() {
+        let diff = diff_lines("1\n2\n3\n4\n5\n6\n7", "1\n2\n3\n4\n5\n6\n8");
+        assert_eq!(diff, "...\n  5\n  6\n- 7\n+ 8");
+    }
+
+    #[test]
+    fn diff_lines_of_identical_schemas_contains_no_changes() {
+        let diff = diff_lines("a\nb", "a\nb");
+        assert_eq!(diff, "...");
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+
+use log::warn;
+
+use crate::dapr_client::DaprClient;
+use crate::settings::Settings;
+
+/// Loads a credential from the Dapr secret store, falling back to the environment variable of the same name.
+///
+/// The key of the secret defaults to the name of the environment variable and can be overridden
+/// with the environment variable `<name>_SECRET_KEY`. The value is read from the field named like the key.
+///
+/// * `dapr_client` - Dapr client used to access the secret store.
+/// * `settings` - Service settings defining the secret store.
+/// * `name` - Name of the environment variable of the credential.
+pub async fn load_secret(
+    dapr_client: &DaprClient,
+    settings: &Settings,
+    name: &str,
+) -> Option<String> {
+    if let Some(secret_store) = &settings.secret_store {
+        let key = env::var(format!("{}_SECRET_KEY", name)).unwrap_or(name.to_string());
+        match dapr_client.get_secret(secret_store, &key).await {
+            Ok(mut secret) => match secret.remove(&key) {
+                Some(value) => return Some(value),
+                None => warn!(
+                    "Secret: `{}` of secret store: `{}` has no value of the same name, falling back to environment variable: `{}`.",
+                    key, secret_store, name
+                ),
+            },
+            Err(error) => warn!(
+                "{} Falling back to environment variable: `{}`.",
+                error.message, name
+            ),
+        }
+    }
+    env::var(name).ok()
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::graphql::model::uuid::Uuid;
+use async_graphql::{Error, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::dapr_client::DaprClient;
+
+/// GraphQL query of the catalog service retrieving a product variant.
+const PRODUCT_VARIANT_QUERY: &str = "query ($id: UUID!) { productVariant(id: $id) { id } }";
+
+/// Number of entities retrieved per query when listing all entities of a service.
+const LIST_PAGE_SIZE: u64 = 100;
+
+/// Maximum number of pages retrieved when listing all entities of a service, guards against services which never end a listing.
+const MAX_LIST_PAGES: usize = 10000;
+
+/// GraphQL response of the catalog service.
+#[derive(Deserialize, Debug)]
+struct ProductVariantResponse {
+    data: Option<ProductVariantResponseData>,
+    #[serde(default)]
+    errors: Vec<GraphQLResponseError>,
+}
+
+/// Error contained in GraphQL response of another service.
+#[derive(Deserialize, Debug)]
+struct GraphQLResponseError {
+    message: String,
+}
+
+/// Data of GraphQL response of the catalog service.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ProductVariantResponseData {
+    product_variant: Option<ProductVariantResponseNode>,
+}
+
+/// Product variant contained in GraphQL response of the catalog service.
+#[derive(Deserialize, Debug)]
+struct ProductVariantResponseNode {
+    id: Uuid,
+}
+
+/// Checks if a product variant exists in the catalog service via Dapr service invocation.
+///
+/// Fails if the response contains errors or no data, so failures of the catalog service are not mistaken for missing product variants.
+///
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `catalog_app_id` - Dapr app id of the catalog service.
+/// * `id` - UUID of product variant.
+pub async fn product_variant_exists_in_catalog(
+    dapr_client: &DaprClient,
+    catalog_app_id: &str,
+    id: Uuid,
+) -> Result<bool> {
+    let body = json!({
+        "query": PRODUCT_VARIANT_QUERY,
+        "variables": { "id": id.to_string() },
+    });
+    let response: ProductVariantResponse = dapr_client
+        .invoke_service(catalog_app_id, "graphql", &body)
+        .await?;
+    if let Some(error) = response.errors.first() {
+        let message = format!(
+            "Retrieving product variant of id: `{}` from the catalog failed: {}",
+            id, error.message
+        );
+        return Err(Error::new(message));
+    }
+    let data = response.data.ok_or_else(|| {
+        let message = format!(
+            "Retrieving product variant of id: `{}` from the catalog returned no data.",
+            id
+        );
+        Error::new(message)
+    })?;
+    let exists = data
+        .product_variant
+        .is_some_and(|product_variant| product_variant.id == id);
+    Ok(exists)
+}
+
+/// GraphQL response of a connection query of another service, with the connection as only field.
+#[derive(Deserialize, Debug)]
+struct ConnectionResponse {
+    data: Option<HashMap<String, ConnectionResponseData>>,
+}
+
+/// Connection contained in GraphQL response of a connection query.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionResponseData {
+    has_next_page: bool,
+    nodes: Vec<ConnectionResponseNode>,
+}
+
+/// Entity contained in GraphQL response of a connection query.
+#[derive(Deserialize, Debug)]
+struct ConnectionResponseNode {
+    id: Uuid,
+}
+
+/// Lists the UUIDs of all product variants of the catalog service via Dapr service invocation.
+///
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `catalog_app_id` - Dapr app id of the catalog service.
+pub async fn list_product_variant_ids_in_catalog(
+    dapr_client: &DaprClient,
+    catalog_app_id: &str,
+) -> Result<Vec<Uuid>> {
+    list_ids(dapr_client, catalog_app_id, "productVariants").await
+}
+
+/// Lists the UUIDs of all users of the user service via Dapr service invocation.
+///
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `user_app_id` - Dapr app id of the user service.
+pub async fn list_user_ids_in_user_service(
+    dapr_client: &DaprClient,
+    user_app_id: &str,
+) -> Result<Vec<Uuid>> {
+    list_ids(dapr_client, user_app_id, "users").await
+}
+
+/// Lists the UUIDs of all entities of a connection query of another service, page by page.
+///
+/// Stops at the first empty page, even if the service reports a next page, and fails after `MAX_LIST_PAGES` pages.
+///
+/// * `dapr_client` - Dapr client used for service invocation.
+/// * `app_id` - Dapr app id of the service.
+/// * `connection` - Name of the connection query, which accepts `first` and `skip`.
+async fn list_ids(dapr_client: &DaprClient, app_id: &str, connection: &str) -> Result<Vec<Uuid>> {
+    let query = format!(
+        "query ($first: Int!, $skip: Int!) {{ {}(first: $first, skip: $skip) {{ hasNextPage nodes {{ id }} }} }}",
+        connection
+    );
+    let mut ids = vec![];
+    for _ in 0..MAX_LIST_PAGES {
+        let body = json!({
+            "query": query,
+            "variables": { "first": LIST_PAGE_SIZE, "skip": ids.len() },
+        });
+        let response: ConnectionResponse =
+            dapr_client.invoke_service(app_id, "graphql", &body).await?;
+        let page = response
+            .data
+            .and_then(|mut data| data.remove(connection))
+            .ok_or_else(|| {
+                let message = format!(
+                    "Listing `{}` of service: `{}` returned no data.",
+                    connection, app_id
+                );
+                Error::new(message)
+            })?;
+        if page.nodes.is_empty() {
+            return Ok(ids);
+        }
+        ids.extend(page.nodes.into_iter().map(|node| node.id));
+        if !page.has_next_page {
+            return Ok(ids);
+        }
+    }
+    let message = format!(
+        "Listing `{}` of service: `{}` exceeded the maximum of {} pages.",
+        connection, app_id, MAX_LIST_PAGES
+    );
+    Err(Error::new(message))
+}
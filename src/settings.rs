@@ -0,0 +1,735 @@
+use std::{collections::HashMap, env, str::FromStr};
+
+use log::Level;
+use mongodb::options::Acknowledgment;
+use reqwest::Url;
+use tokio::sync::Semaphore;
+
+use crate::authorization::PermissiveRoles;
+use crate::tenant::TenantId;
+
+/// Service settings read from environment variables.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// Whether product variants missing in the local projection are looked up in the catalog service.
+    pub catalog_fallback_validation: bool,
+    /// Dapr app id of the catalog service.
+    pub catalog_app_id: String,
+    /// Dapr app id of the user service.
+    pub user_app_id: String,
+    /// Whether the users and product variants missing in the local projections are added from the user and catalog service at startup.
+    pub reconcile_projections_on_startup: bool,
+    /// ISO 4217 code of the currency of catalog prices, used for catalog events which do not specify a currency.
+    pub catalog_currency: String,
+    /// Strictness of the existence checks of product variants and users referenced in mutations.
+    pub validation_strictness: ValidationStrictness,
+    /// Interval in seconds in which the item counts of wishlists are reconciled.
+    pub item_count_reconciliation_interval_secs: u64,
+    /// Interval in seconds in which product variants of wishlists are checked against the catalog.
+    pub catalog_drift_detection_interval_secs: u64,
+    /// Maximum number of product variants checked against the catalog per run.
+    pub catalog_drift_sample_size: u64,
+    /// Whether references to product variants missing in the catalog are removed from wishlists.
+    pub catalog_drift_auto_remove: bool,
+    /// Number of consecutive checks a product variant must be missing in the catalog before its references are removed.
+    pub catalog_drift_auto_remove_after_misses: u64,
+    /// Interval in milliseconds in which metrics are exported via OTLP.
+    pub otlp_metric_export_interval_millis: u64,
+    /// Timeout in milliseconds of a metric export via OTLP.
+    pub otlp_metric_export_timeout_millis: u64,
+    /// Headers sent with OTLP exports.
+    pub otlp_headers: OtlpHeaders,
+    /// Sampler deciding which traces are recorded.
+    pub traces_sampler: TracesSampler,
+    /// Ratio of sampled traces used by ratio-based samplers.
+    pub traces_sampler_ratio: f64,
+    /// Directory of GraphQL documents which are allowed to execute. If set, all other documents are rejected.
+    pub operation_allow_list_dir: Option<String>,
+    /// Duration in seconds for which results of mutations are stored for their idempotency keys.
+    pub idempotency_key_ttl_secs: u64,
+    /// Maximum number of wishlists or calendar tokens a user creates within the creation throttle window. Admins are not throttled.
+    pub creation_throttle_limit: u64,
+    /// Duration in seconds of the sliding window of the creation throttle.
+    pub creation_throttle_window_secs: u64,
+    /// Number of entities retrieved by connection queries which do not specify a page size.
+    pub default_page_size: u64,
+    /// Maximum number of entities retrieved by a single connection query.
+    pub max_page_size: u64,
+    /// Maximum number of entities skipped by a single connection query.
+    pub max_offset: u64,
+    /// Maximum estimated number of nodes of a GraphQL response, accounting for the page sizes of nested connections.
+    pub max_response_nodes: u64,
+    /// Maximum size in bytes of files uploaded to mutations, e.g. CSV imports.
+    pub max_upload_bytes: u64,
+    /// Maximum number of wishlists per user, unlimited if `None`.
+    pub max_wishlists_per_user: Option<u64>,
+    /// Maximum number of product variants per wishlist, unlimited if `None`.
+    pub max_items_per_wishlist: Option<u64>,
+    /// Hosts which cover image URLs of wishlists may point to. All hosts are allowed if empty.
+    pub cover_image_allowed_hosts: StringList,
+    /// Icons which wishlists may use. Any single emoji is allowed if empty.
+    pub allowed_icons: StringList,
+    /// Interval in seconds in which expired wishlists are archived.
+    pub wishlist_expiration_interval_secs: u64,
+    /// Interval in seconds in which due wishlist digests are published.
+    pub wishlist_digest_interval_secs: u64,
+    /// Duration in seconds of the period covered by a wishlist digest.
+    pub wishlist_digest_period_secs: u64,
+    /// Whether an event is published when a wishlist reaches one of the milestone sizes.
+    pub wishlist_milestone_events_enabled: bool,
+    /// Numbers of product variants at which a wishlist reaches a milestone.
+    pub wishlist_milestone_sizes: MilestoneSizes,
+    /// Number of outbound events of a topic which are published together in a bulk operation.
+    pub event_batch_max_size: usize,
+    /// Interval in milliseconds in which batched outbound events are published.
+    pub event_batch_flush_interval_millis: u64,
+    /// Maximum number of incoming events handled concurrently, further events are rejected for redelivery by Dapr.
+    pub max_in_flight_events: u64,
+    /// Maximum duration in seconds to wait for events in progress on shutdown, before the server stops anyway.
+    pub shutdown_drain_timeout_secs: u64,
+    /// Name of the Dapr state store used to cache wishlists and product variant lookups. Caching is disabled if unset.
+    pub cache_state_store: Option<String>,
+    /// Time to live in seconds of cached values.
+    pub cache_ttl_secs: u64,
+    /// Name of the Dapr secret store from which credentials are loaded. Credentials are read from environment variables if unset.
+    pub secret_store: Option<String>,
+    /// Whether failed writes are retried once. Uses the setting of the MongoDB URI if unset, which defaults to retrying.
+    pub mongodb_retry_writes: Option<bool>,
+    /// Number of MongoDB replica set members, `majority` or a tag set which must acknowledge writes. Uses the setting of the MongoDB URI if unset.
+    pub mongodb_write_acknowledgment: Option<WriteAcknowledgment>,
+    /// Whether writes are only acknowledged once written to the on-disk journal. Uses the setting of the MongoDB URI if unset.
+    pub mongodb_journal: Option<bool>,
+    /// URL of the OpenID Connect issuer. If set, users are authenticated with access tokens instead of the `Authorized-User` header.
+    pub oidc_issuer_url: Option<String>,
+    /// Expected audience of OpenID Connect access tokens. The audience is not validated if unset.
+    pub oidc_audience: Option<String>,
+    /// Dot-separated path of the claim containing the roles of the user in OpenID Connect access tokens.
+    pub oidc_roles_claim: String,
+    /// Dot-separated path of the claim containing the tenant identifier in OpenID Connect access tokens.
+    pub oidc_tenant_claim: String,
+    /// Whether changes of wishlist documents are published to the change-data-capture topic.
+    pub change_data_capture_enabled: bool,
+    /// Tenant of requests which do not specify a tenant, and of documents stored before tenants were introduced.
+    pub default_tenant_id: String,
+    /// Names of dedicated MongoDB databases of tenants. Tenants without dedicated database share the default database.
+    pub tenant_databases: TenantDatabases,
+    /// Maximum number of characters of wishlist names. Unlimited if unset.
+    pub max_wishlist_name_length: Option<usize>,
+    /// Maximum number of characters of wishlist descriptions. Unlimited if unset.
+    pub max_wishlist_description_length: Option<usize>,
+    /// Words which user-provided text of wishlists must not contain, matched case-insensitively.
+    pub content_filter_words: StringList,
+    /// URL of an external moderation service checking user-provided text of wishlists. Not called if unset.
+    pub content_moderation_url: Option<String>,
+    /// Roles which permit access to the wishlists of all users.
+    pub permissive_roles: PermissiveRoles,
+    /// Handling of user-provided text found offending by the content filters.
+    pub content_filter_action: ContentFilterAction,
+    /// Log level of the structured line logged per GraphQL operation, the logger emits levels up to `WARN`.
+    pub operation_log_level: Level,
+    /// GraphQL IDE served at the GraphQL endpoint.
+    pub graphql_ide: GraphQLIde,
+    /// Path prefix under which a reverse proxy exposes the service, used for the endpoint URLs of the GraphQL IDE.
+    pub public_path_prefix: PathPrefix,
+    /// Path under which the GraphQL endpoint and the GraphQL IDE are served, the root path if empty.
+    pub graphql_path: PathPrefix,
+    /// Endpoint URL advertised by the GraphQL IDE, overriding the URL derived from the path prefix and the GraphQL path.
+    pub graphql_ide_endpoint: Option<String>,
+    /// URL of the fault configuration of the MiSArch experiment-config sidecar. No faults are injected if unset.
+    #[cfg(feature = "fault-injection")]
+    pub experiment_config_url: Option<String>,
+    /// Interval in milliseconds in which the fault configuration is polled.
+    #[cfg(feature = "fault-injection")]
+    pub experiment_config_poll_interval_millis: u64,
+}
+
+impl Settings {
+    /// Reads settings from environment variables, using defaults for unset variables.
+    ///
+    /// Validates the settings, see `validate`.
+    /// Fails with all problems found, including variables set to values which cannot be parsed.
+    pub fn from_env() -> Result<Self, Vec<String>> {
+        let mut env = EnvReader::default();
+        let settings = Self {
+            catalog_fallback_validation: env.or_default("CATALOG_FALLBACK_VALIDATION", false),
+            catalog_app_id: env.or_default("CATALOG_APP_ID", "catalog".to_string()),
+            catalog_drift_detection_interval_secs: env
+                .or_default("CATALOG_DRIFT_DETECTION_INTERVAL_SECS", 3600),
+            catalog_drift_sample_size: env.or_default("CATALOG_DRIFT_SAMPLE_SIZE", 50),
+            catalog_drift_auto_remove: env.or_default("CATALOG_DRIFT_AUTO_REMOVE", false),
+            catalog_drift_auto_remove_after_misses: env
+                .or_default("CATALOG_DRIFT_AUTO_REMOVE_AFTER_MISSES", 3),
+            user_app_id: env.or_default("USER_APP_ID", "user".to_string()),
+            reconcile_projections_on_startup: env
+                .or_default("RECONCILE_PROJECTIONS_ON_STARTUP", false),
+            catalog_currency: env.or_default("CATALOG_CURRENCY", "EUR".to_string()),
+            validation_strictness: env.or_default("VALIDATION_STRICTNESS", Default::default()),
+            item_count_reconciliation_interval_secs: env
+                .or_default("ITEM_COUNT_RECONCILIATION_INTERVAL_SECS", 3600),
+            otlp_metric_export_interval_millis: env.or_default("OTEL_METRIC_EXPORT_INTERVAL", 5000),
+            otlp_metric_export_timeout_millis: env.or_default("OTEL_METRIC_EXPORT_TIMEOUT", 30000),
+            otlp_headers: env.or_default("OTEL_EXPORTER_OTLP_HEADERS", Default::default()),
+            traces_sampler: env.or_default("OTEL_TRACES_SAMPLER", Default::default()),
+            traces_sampler_ratio: env.or_default("OTEL_TRACES_SAMPLER_ARG", 1.0),
+            operation_allow_list_dir: env.optional("OPERATION_ALLOW_LIST_DIR"),
+            idempotency_key_ttl_secs: env.or_default("IDEMPOTENCY_KEY_TTL_SECS", 86400),
+            creation_throttle_limit: env.or_default("CREATION_THROTTLE_LIMIT", 10),
+            creation_throttle_window_secs: env.or_default("CREATION_THROTTLE_WINDOW_SECS", 3600),
+            default_page_size: env.or_default("DEFAULT_PAGE_SIZE", 20),
+            max_page_size: env.or_default("MAX_PAGE_SIZE", 100),
+            max_offset: env.or_default("MAX_PAGINATION_OFFSET", 10000),
+            max_response_nodes: env.or_default("MAX_RESPONSE_NODES", 50000),
+            max_upload_bytes: env.or_default("MAX_UPLOAD_BYTES", 1048576),
+            max_wishlists_per_user: env.optional("MAX_WISHLISTS_PER_USER"),
+            max_items_per_wishlist: env.optional("MAX_ITEMS_PER_WISHLIST"),
+            cover_image_allowed_hosts: env
+                .or_default("COVER_IMAGE_ALLOWED_HOSTS", Default::default()),
+            allowed_icons: env.or_default("WISHLIST_ALLOWED_ICONS", Default::default()),
+            wishlist_expiration_interval_secs: env
+                .or_default("WISHLIST_EXPIRATION_INTERVAL_SECS", 300),
+            wishlist_digest_interval_secs: env.or_default("WISHLIST_DIGEST_INTERVAL_SECS", 3600),
+            wishlist_digest_period_secs: env.or_default("WISHLIST_DIGEST_PERIOD_SECS", 604800),
+            wishlist_milestone_events_enabled: env
+                .or_default("WISHLIST_MILESTONE_EVENTS_ENABLED", false),
+            wishlist_milestone_sizes: env
+                .or_default("WISHLIST_MILESTONE_SIZES", Default::default()),
+            event_batch_max_size: env.or_default("EVENT_BATCH_MAX_SIZE", 100),
+            event_batch_flush_interval_millis: env
+                .or_default("EVENT_BATCH_FLUSH_INTERVAL_MILLIS", 1000),
+            max_in_flight_events: env.or_default("MAX_IN_FLIGHT_EVENTS", 200),
+            shutdown_drain_timeout_secs: env.or_default("SHUTDOWN_DRAIN_TIMEOUT_SECS", 25),
+            cache_state_store: env.optional("CACHE_STATE_STORE"),
+            cache_ttl_secs: env.or_default("CACHE_TTL_SECS", 60),
+            secret_store: env.optional("SECRET_STORE_NAME"),
+            mongodb_retry_writes: env.optional("MONGODB_RETRY_WRITES"),
+            mongodb_write_acknowledgment: env.optional("MONGODB_WRITE_CONCERN"),
+            mongodb_journal: env.optional("MONGODB_JOURNAL"),
+            oidc_issuer_url: env.optional("OIDC_ISSUER_URL"),
+            oidc_audience: env.optional("OIDC_AUDIENCE"),
+            oidc_roles_claim: env.or_default("OIDC_ROLES_CLAIM", "realm_access.roles".to_string()),
+            oidc_tenant_claim: env.or_default("OIDC_TENANT_CLAIM", "tenant_id".to_string()),
+            change_data_capture_enabled: env.or_default("CHANGE_DATA_CAPTURE_ENABLED", false),
+            default_tenant_id: env.or_default("DEFAULT_TENANT_ID", "default".to_string()),
+            tenant_databases: env.or_default("TENANT_DATABASES", Default::default()),
+            max_wishlist_name_length: env.optional("MAX_WISHLIST_NAME_LENGTH"),
+            max_wishlist_description_length: env.optional("MAX_WISHLIST_DESCRIPTION_LENGTH"),
+            content_filter_words: env.or_default("CONTENT_FILTER_WORDS", Default::default()),
+            content_moderation_url: env.optional("CONTENT_MODERATION_URL"),
+            content_filter_action: env.or_default("CONTENT_FILTER_ACTION", Default::default()),
+            operation_log_level: env.or_default("OPERATION_LOG_LEVEL", Level::Warn),
+            permissive_roles: env.or_default("PERMISSIVE_ROLES", Default::default()),
+            graphql_ide: env.or_default("GRAPHQL_IDE", Default::default()),
+            public_path_prefix: env.or_default("PUBLIC_PATH_PREFIX", Default::default()),
+            graphql_path: env.or_default("GRAPHQL_PATH", Default::default()),
+            graphql_ide_endpoint: env.optional("GRAPHQL_IDE_ENDPOINT"),
+            #[cfg(feature = "fault-injection")]
+            experiment_config_url: env.optional("EXPERIMENT_CONFIG_URL"),
+            #[cfg(feature = "fault-injection")]
+            experiment_config_poll_interval_millis: env
+                .or_default("EXPERIMENT_CONFIG_POLL_INTERVAL_MILLIS", 1000),
+        };
+        let mut problems = env.problems;
+        problems.extend(settings.validate());
+        match problems.is_empty() {
+            true => Ok(settings),
+            false => Err(problems),
+        }
+    }
+
+    /// Checks the settings for values which cannot work and settings which contradict each other.
+    ///
+    /// Returns all problems found, empty if the settings are valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for (key, value) in [
+            (
+                "ITEM_COUNT_RECONCILIATION_INTERVAL_SECS",
+                self.item_count_reconciliation_interval_secs,
+            ),
+            (
+                "CATALOG_DRIFT_DETECTION_INTERVAL_SECS",
+                self.catalog_drift_detection_interval_secs,
+            ),
+            ("CATALOG_DRIFT_SAMPLE_SIZE", self.catalog_drift_sample_size),
+            (
+                "CATALOG_DRIFT_AUTO_REMOVE_AFTER_MISSES",
+                self.catalog_drift_auto_remove_after_misses,
+            ),
+            (
+                "OTEL_METRIC_EXPORT_INTERVAL",
+                self.otlp_metric_export_interval_millis,
+            ),
+            ("CREATION_THROTTLE_LIMIT", self.creation_throttle_limit),
+            (
+                "CREATION_THROTTLE_WINDOW_SECS",
+                self.creation_throttle_window_secs,
+            ),
+            ("DEFAULT_PAGE_SIZE", self.default_page_size),
+            ("MAX_PAGE_SIZE", self.max_page_size),
+            ("MAX_RESPONSE_NODES", self.max_response_nodes),
+            ("MAX_UPLOAD_BYTES", self.max_upload_bytes),
+            (
+                "WISHLIST_EXPIRATION_INTERVAL_SECS",
+                self.wishlist_expiration_interval_secs,
+            ),
+            (
+                "WISHLIST_DIGEST_INTERVAL_SECS",
+                self.wishlist_digest_interval_secs,
+            ),
+            (
+                "WISHLIST_DIGEST_PERIOD_SECS",
+                self.wishlist_digest_period_secs,
+            ),
+            ("EVENT_BATCH_MAX_SIZE", self.event_batch_max_size as u64),
+            (
+                "EVENT_BATCH_FLUSH_INTERVAL_MILLIS",
+                self.event_batch_flush_interval_millis,
+            ),
+            ("MAX_IN_FLIGHT_EVENTS", self.max_in_flight_events),
+            #[cfg(feature = "fault-injection")]
+            (
+                "EXPERIMENT_CONFIG_POLL_INTERVAL_MILLIS",
+                self.experiment_config_poll_interval_millis,
+            ),
+        ] {
+            if value == 0 {
+                problems.push(format!("${} must be greater than 0.", key));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.traces_sampler_ratio) {
+            problems.push(format!(
+                "$OTEL_TRACES_SAMPLER_ARG must be between 0 and 1, is: `{}`.",
+                self.traces_sampler_ratio
+            ));
+        }
+        // Draining on shutdown acquires all permits at once, which is limited to `u32::MAX` permits.
+        let max_in_flight_permits = Semaphore::MAX_PERMITS.min(u32::MAX as usize) as u64;
+        if self.max_in_flight_events > max_in_flight_permits {
+            problems.push(format!(
+                "$MAX_IN_FLIGHT_EVENTS: `{}` exceeds the maximum number of permits: `{}`.",
+                self.max_in_flight_events, max_in_flight_permits
+            ));
+        }
+        if self.default_page_size > self.max_page_size {
+            problems.push(format!(
+                "$DEFAULT_PAGE_SIZE: `{}` exceeds $MAX_PAGE_SIZE: `{}`.",
+                self.default_page_size, self.max_page_size
+            ));
+        }
+        let otlp_endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok();
+        #[cfg(feature = "fault-injection")]
+        let experiment_config_url = self.experiment_config_url.as_deref();
+        #[cfg(not(feature = "fault-injection"))]
+        let experiment_config_url = None;
+        for (key, url) in [
+            ("OIDC_ISSUER_URL", self.oidc_issuer_url.as_deref()),
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", otlp_endpoint.as_deref()),
+            (
+                "CONTENT_MODERATION_URL",
+                self.content_moderation_url.as_deref(),
+            ),
+            ("EXPERIMENT_CONFIG_URL", experiment_config_url),
+        ] {
+            if let Some(Err(problem)) = url.map(validate_http_url) {
+                problems.push(format!(
+                    "${}: `{}` {}",
+                    key,
+                    url.unwrap_or_default(),
+                    problem
+                ));
+            }
+        }
+        for (key, tenant_id) in std::iter::once(("DEFAULT_TENANT_ID", &self.default_tenant_id))
+            .chain(
+                self.tenant_databases
+                    .0
+                    .keys()
+                    .map(|tenant_id| ("TENANT_DATABASES", tenant_id)),
+            )
+        {
+            if let Err(error) = TenantId::parse(tenant_id) {
+                problems.push(format!("${}: {}", key, error.message));
+            }
+        }
+        if self.oidc_audience.is_some() && self.oidc_issuer_url.is_none() {
+            problems.push(
+                "$OIDC_AUDIENCE is set without $OIDC_ISSUER_URL, access tokens are not used."
+                    .to_string(),
+            );
+        }
+        if self.catalog_currency.len() != 3
+            || !self
+                .catalog_currency
+                .chars()
+                .all(|c| c.is_ascii_uppercase())
+        {
+            problems.push(format!(
+                "$CATALOG_CURRENCY must be an uppercase ISO 4217 currency code, is: `{}`.",
+                self.catalog_currency
+            ));
+        }
+        if self.operation_log_level > Level::Warn {
+            problems.push(format!(
+                "$OPERATION_LOG_LEVEL must be `ERROR` or `WARN`, as the logger does not emit lower levels, is: `{}`.",
+                self.operation_log_level
+            ));
+        }
+        if self.catalog_fallback_validation
+            && self.validation_strictness == ValidationStrictness::Off
+        {
+            problems.push(
+                "$CATALOG_FALLBACK_VALIDATION is enabled with $VALIDATION_STRICTNESS: `OFF`, which skips all existence checks."
+                    .to_string(),
+            );
+        }
+        if matches!(
+            self.mongodb_write_acknowledgment,
+            Some(WriteAcknowledgment(Acknowledgment::Nodes(0)))
+        ) && self.mongodb_journal == Some(true)
+        {
+            problems.push(
+                "$MONGODB_JOURNAL is enabled with $MONGODB_WRITE_CONCERN: `0`, unacknowledged writes can not be journaled."
+                    .to_string(),
+            );
+        }
+        if self.cache_state_store.is_some() && self.cache_ttl_secs == 0 {
+            problems.push(
+                "$CACHE_STATE_STORE is set with $CACHE_TTL_SECS: `0`, cached values expire immediately."
+                    .to_string(),
+            );
+        }
+        problems
+    }
+}
+
+/// Trace sampler, named according to `$OTEL_TRACES_SAMPLER` of the OpenTelemetry specification.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum TracesSampler {
+    /// Records all traces.
+    AlwaysOn,
+    /// Records no traces.
+    AlwaysOff,
+    /// Records the ratio of traces defined in `$OTEL_TRACES_SAMPLER_ARG`.
+    TraceIdRatio,
+    /// Follows the sampling decision of the parent span, records all root traces.
+    #[default]
+    ParentBasedAlwaysOn,
+    /// Follows the sampling decision of the parent span, records no root traces.
+    ParentBasedAlwaysOff,
+    /// Follows the sampling decision of the parent span, records the ratio of root traces defined in `$OTEL_TRACES_SAMPLER_ARG`.
+    ParentBasedTraceIdRatio,
+}
+
+impl FromStr for TracesSampler {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always_on" => Ok(Self::AlwaysOn),
+            "always_off" => Ok(Self::AlwaysOff),
+            "traceidratio" => Ok(Self::TraceIdRatio),
+            "parentbased_always_on" => Ok(Self::ParentBasedAlwaysOn),
+            "parentbased_always_off" => Ok(Self::ParentBasedAlwaysOff),
+            "parentbased_traceidratio" => Ok(Self::ParentBasedTraceIdRatio),
+            _ => Err(format!("Unsupported traces sampler: `{}`.", s)),
+        }
+    }
+}
+
+/// Headers sent with OTLP exports, parsed from comma-separated `key=value` pairs.
+#[derive(Clone, Debug, Default)]
+pub struct OtlpHeaders(pub HashMap<String, String>);
+
+impl FromStr for OtlpHeaders {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => Ok((key.trim().to_string(), value.trim().to_string())),
+                None => Err(format!(
+                    "OTLP header: `{}` is not a `key=value` pair.",
+                    pair
+                )),
+            })
+            .collect::<Result<HashMap<String, String>, String>>()
+            .map(OtlpHeaders)
+    }
+}
+
+/// Mapping of tenant identifiers to names of MongoDB databases, parsed from comma-separated `tenant=database` pairs.
+#[derive(Clone, Debug, Default)]
+pub struct TenantDatabases(pub HashMap<String, String>);
+
+impl FromStr for TenantDatabases {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((tenant_id, database)) => {
+                    Ok((tenant_id.trim().to_string(), database.trim().to_string()))
+                }
+                None => Err(format!(
+                    "Tenant database: `{}` is not a `tenant=database` pair.",
+                    pair
+                )),
+            })
+            .collect::<Result<HashMap<String, String>, String>>()
+            .map(TenantDatabases)
+    }
+}
+
+/// List of values, parsed from comma-separated values.
+#[derive(Clone, Debug, Default)]
+pub struct StringList(pub Vec<String>);
+
+impl FromStr for StringList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(StringList(
+            s.split(',')
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect(),
+        ))
+    }
+}
+
+/// Numbers of product variants at which a wishlist reaches a milestone, parsed from comma-separated positive integers.
+///
+/// Sorted ascending without duplicates. Defaults to `10,50`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneSizes(pub Vec<u64>);
+
+impl Default for MilestoneSizes {
+    fn default() -> Self {
+        Self(vec![10, 50])
+    }
+}
+
+impl FromStr for MilestoneSizes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sizes = s
+            .split(',')
+            .map(str::trim)
+            .filter(|size| !size.is_empty())
+            .map(|size| match size.parse::<u64>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!(
+                    "Milestone size: `{}` is not a positive integer.",
+                    size
+                )),
+            })
+            .collect::<Result<Vec<u64>, String>>()?;
+        sizes.sort_unstable();
+        sizes.dedup();
+        Ok(MilestoneSizes(sizes))
+    }
+}
+
+impl MilestoneSizes {
+    /// Returns the milestone sizes crossed by a change of the number of product variants from `previous` to `current`.
+    ///
+    /// Only growing wishlists cross milestones, a wishlist shrinking below a milestone and growing again crosses it again.
+    ///
+    /// * `previous` - Number of product variants before the change.
+    /// * `current` - Number of product variants after the change.
+    pub fn crossed(&self, previous: u64, current: u64) -> impl Iterator<Item = u64> + '_ {
+        self.0
+            .iter()
+            .copied()
+            .filter(move |size| previous < *size && *size <= current)
+    }
+}
+
+/// Describes how failing existence checks of referenced entities are handled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum ValidationStrictness {
+    /// Rejects the mutation.
+    #[default]
+    Strict,
+    /// Logs a warning and continues the mutation.
+    Warn,
+    /// Skips the existence checks.
+    Off,
+}
+
+impl FromStr for ValidationStrictness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "STRICT" => Ok(Self::Strict),
+            "WARN" => Ok(Self::Warn),
+            "OFF" => Ok(Self::Off),
+            _ => Err(format!("Unknown validation strictness: `{}`.", s)),
+        }
+    }
+}
+
+/// Describes how user-provided text found offending by the content filters is handled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum ContentFilterAction {
+    /// Rejects the mutation.
+    #[default]
+    Reject,
+    /// Stores the text and flags the wishlist for review.
+    Flag,
+}
+
+impl FromStr for ContentFilterAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "REJECT" => Ok(Self::Reject),
+            "FLAG" => Ok(Self::Flag),
+            _ => Err(format!("Unknown content filter action: `{}`.", s)),
+        }
+    }
+}
+
+/// GraphQL IDE served at the GraphQL endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum GraphQLIde {
+    /// Serves GraphiQL.
+    #[default]
+    GraphiQL,
+    /// Serves the embedded Apollo Sandbox.
+    ApolloSandbox,
+    /// Serves no IDE.
+    None,
+}
+
+impl FromStr for GraphQLIde {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "GRAPHIQL" => Ok(Self::GraphiQL),
+            "APOLLO_SANDBOX" => Ok(Self::ApolloSandbox),
+            "NONE" => Ok(Self::None),
+            _ => Err(format!("Unknown GraphQL IDE: `{}`.", s)),
+        }
+    }
+}
+
+/// URL path prefix, normalized to start with `/` and to end without `/`. Empty if the service is exposed at the root path.
+///
+/// Consists of ASCII alphanumeric characters and `-`, `.`, `_`, `~` and `/`.
+#[derive(Clone, Debug, Default)]
+pub struct PathPrefix(pub String);
+
+impl FromStr for PathPrefix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments = s.trim().trim_matches('/');
+        if !segments
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._~/".contains(c))
+        {
+            return Err(format!("Path prefix: `{}` contains invalid characters.", s));
+        }
+        match segments.is_empty() {
+            true => Ok(PathPrefix(String::new())),
+            false => Ok(PathPrefix(format!("/{}", segments))),
+        }
+    }
+}
+
+/// Acknowledgment of MongoDB writes, parsed from `majority`, a number of replica set members or the name of a tag set.
+#[derive(Clone, Debug)]
+pub struct WriteAcknowledgment(pub Acknowledgment);
+
+impl FromStr for WriteAcknowledgment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Write acknowledgment must not be empty.".to_string());
+        }
+        if s.eq_ignore_ascii_case("majority") {
+            return Ok(WriteAcknowledgment(Acknowledgment::Majority));
+        }
+        match s.parse::<u32>() {
+            Ok(nodes) => Ok(WriteAcknowledgment(Acknowledgment::Nodes(nodes))),
+            Err(_) => Ok(WriteAcknowledgment(Acknowledgment::Custom(s.to_string()))),
+        }
+    }
+}
+
+/// Checks that a URL is an absolute HTTP(S) URL with a valid port.
+///
+/// * `url` - URL to check.
+fn validate_http_url(url: &str) -> Result<(), &'static str> {
+    let url = Url::parse(url).map_err(|_| "is not a valid URL.")?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must use the scheme `http` or `https`.");
+    }
+    if url.port() == Some(0) {
+        return Err("must not use port 0.");
+    }
+    Ok(())
+}
+
+/// Reader of environment variables, which collects the problems of all variables which cannot be parsed.
+#[derive(Default)]
+struct EnvReader {
+    /// Problems of variables set to values which cannot be parsed.
+    problems: Vec<String>,
+}
+
+impl EnvReader {
+    /// Reads and parses an optional environment variable.
+    ///
+    /// Records a problem and returns `None` if the value cannot be parsed.
+    ///
+    /// * `key` - Name of environment variable.
+    fn optional<T: FromStr>(&mut self, key: &str) -> Option<T> {
+        let value = env::var(key).ok()?;
+        match value.parse() {
+            Ok(parsed_value) => Some(parsed_value),
+            Err(_) => {
+                self.problems
+                    .push(format!("${} is set to an invalid value: `{}`.", key, value));
+                None
+            }
+        }
+    }
+
+    /// Reads and parses an environment variable.
+    ///
+    /// * `key` - Name of environment variable.
+    /// * `default` - Value used if the environment variable is not set or cannot be parsed.
+    fn or_default<T: FromStr>(&mut self, key: &str, default: T) -> T {
+        self.optional(key).unwrap_or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossed_returns_milestones_reached_by_growing_wishlist() {
+        let sizes = MilestoneSizes(vec![10, 50, 100]);
+        assert_eq!(sizes.crossed(9, 10).collect::<Vec<u64>>(), vec![10]);
+        assert_eq!(sizes.crossed(5, 60).collect::<Vec<u64>>(), vec![10, 50]);
+        assert_eq!(sizes.crossed(10, 49).count(), 0);
+    }
+
+    #[test]
+    fn crossed_returns_no_milestones_for_shrinking_wishlist() {
+        let sizes = MilestoneSizes(vec![10, 50]);
+        assert_eq!(sizes.crossed(60, 5).count(), 0);
+        assert_eq!(sizes.crossed(10, 10).count(), 0);
+    }
+}
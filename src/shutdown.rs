@@ -0,0 +1,69 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use log::{info, warn};
+use tokio::signal;
+
+use crate::event::{event_batcher::EventBatcher, http_event_service::HttpEventServiceState};
+
+/// Waits for a shutdown signal and drains the event handling of the service, then returns so the server stops.
+///
+/// After the signal, new events are rejected for redelivery while the events in progress are handled,
+/// at most for the drain timeout. Pending batched events are published afterwards, so no updates are lost during rolling updates.
+///
+/// * `event_service_state` - State of the event endpoint, whose events in progress are awaited.
+/// * `max_in_flight_events` - Maximum number of events in progress, the number of permits of the event endpoint.
+/// * `event_batcher` - Batcher of outbound events, which is flushed.
+/// * `drain_timeout` - Maximum duration to wait for the events in progress.
+pub async fn drain_on_shutdown(
+    event_service_state: HttpEventServiceState,
+    max_in_flight_events: u32,
+    event_batcher: EventBatcher,
+    drain_timeout: Duration,
+) {
+    shutdown_signal().await;
+    info!("Shutdown requested, draining events in progress.");
+    event_service_state.draining.store(true, Ordering::SeqCst);
+    let in_flight_permits = event_service_state.in_flight_permits.clone();
+    match tokio::time::timeout(
+        drain_timeout,
+        in_flight_permits.acquire_many_owned(max_in_flight_events),
+    )
+    .await
+    {
+        Ok(_) => info!("All events in progress were handled."),
+        Err(_) => warn!(
+            "Events in progress were not handled within {} seconds, shutting down anyway.",
+            drain_timeout.as_secs()
+        ),
+    }
+    event_batcher.flush().await;
+    info!("Published pending batched events, stopping server.");
+}
+
+/// Waits for `SIGTERM`, which orchestrators send on redeploys, or for `Ctrl+C`.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(error) = signal::ctrl_c().await {
+            warn!("Listening for Ctrl+C failed: {}", error);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(error) => {
+                warn!("Listening for SIGTERM failed: {}", error);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
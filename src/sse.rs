@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt};
+use mongodb::Collection;
+use serde::Serialize;
+
+use crate::authentication::RequestAuthenticator;
+use crate::authorization::PermissiveRoles;
+use crate::graphql::{
+    model::{date_time::DateTime, uuid::Uuid, wishlist::Wishlist},
+    query::query_object,
+    subscription::watch_wishlist,
+};
+use crate::tenant::{resolve_tenant_id, DatabaseRouter, TenantId};
+
+/// Interval of keep-alive comments, which prevent proxies from closing idle connections.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Service state of the Server-Sent Events endpoints.
+#[derive(Clone)]
+pub struct SseState {
+    /// Authenticator of the callers of requests, shared with the GraphQL endpoint.
+    pub authenticator: RequestAuthenticator,
+    /// Resolver of the MongoDB database of the tenant of a request.
+    pub database_router: DatabaseRouter,
+    /// Tenant of requests which do not specify a tenant.
+    pub default_tenant_id: TenantId,
+    /// Roles which permit access to the wishlists of all users.
+    pub permissive_roles: PermissiveRoles,
+}
+
+/// Public representation of a wishlist in Server-Sent Events, mirroring the fields of the GraphQL `Wishlist` type.
+///
+/// Internal fields of the wishlist document, e.g. its tenant or suspension, are not exposed.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct WishlistUpdatedEventData {
+    /// UUID of the wishlist.
+    id: Uuid,
+    /// UUID of the user owning the wishlist.
+    owner_id: Uuid,
+    /// Name of the wishlist.
+    name: String,
+    /// Description of the wishlist.
+    description: Option<String>,
+    /// Date of the occasion the wishlist is intended for.
+    occasion_date: Option<DateTime>,
+    /// URL of the cover image of the wishlist.
+    cover_image_url: Option<String>,
+    /// Icon of the wishlist.
+    icon: Option<String>,
+    /// Theme color of the wishlist.
+    color: Option<String>,
+    /// Timestamp after which the wishlist is archived.
+    expires_at: Option<DateTime>,
+    /// Timestamp when the wishlist was archived.
+    archived_at: Option<DateTime>,
+    /// Timestamp when the wishlist was created.
+    created_at: DateTime,
+    /// Timestamp when the wishlist was last updated.
+    last_updated_at: DateTime,
+    /// Number of product variants in the wishlist.
+    item_count: u64,
+    /// UUIDs of the product variants in the wishlist.
+    product_variant_ids: Vec<Uuid>,
+    /// UUIDs of the product variants which were marked as purchased.
+    purchased_product_variant_ids: Vec<Uuid>,
+}
+
+impl From<Wishlist> for WishlistUpdatedEventData {
+    fn from(wishlist: Wishlist) -> Self {
+        let mut product_variant_ids: Vec<Uuid> = wishlist
+            .internal_product_variants
+            .iter()
+            .map(|product_variant| product_variant._id)
+            .collect();
+        product_variant_ids.sort();
+        Self {
+            id: wishlist._id,
+            owner_id: wishlist.user._id,
+            name: wishlist.name,
+            description: wishlist.description,
+            occasion_date: wishlist.occasion_date,
+            cover_image_url: wishlist.cover_image_url,
+            icon: wishlist.icon,
+            color: wishlist.color,
+            expires_at: wishlist.expires_at,
+            archived_at: wishlist.archived_at,
+            created_at: wishlist.created_at,
+            last_updated_at: wishlist.last_updated_at,
+            item_count: wishlist.item_count,
+            product_variant_ids,
+            purchased_product_variant_ids: wishlist
+                .purchased_items
+                .iter()
+                .map(|purchased_item| purchased_item.product_variant_id)
+                .collect(),
+        }
+    }
+}
+
+/// Streams the wishlist of UUID as Server-Sent Events whenever it is modified.
+///
+/// Alternative to the `wishlistUpdated` subscription for clients which can not use WebSocket connections.
+/// The caller is authenticated once on connect like GraphQL requests, see `RequestAuthenticator`,
+/// and must own the wishlist, have a permissive role or an API key with a wishlist scope.
+/// Each event of type `wishlistUpdated` contains the public fields of the wishlist as JSON, see `WishlistUpdatedEventData`.
+/// The stream ends when the wishlist is deleted.
+///
+/// * `state` - Authenticator, database router and default tenant used by handler.
+/// * `id` - UUID of wishlist to watch.
+/// * `headers` - Header map containing headers of request.
+pub async fn wishlist_updates(
+    State(state): State<SseState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, (StatusCode, String)> {
+    let authentication = state
+        .authenticator
+        .authenticate(&headers, None)
+        .await
+        .map_err(|error| (StatusCode::UNAUTHORIZED, error.message().to_string()))?;
+    let tenant_id = resolve_tenant_id(
+        &headers,
+        authentication.credential_tenant_id,
+        &state.default_tenant_id,
+    )?;
+    let id = Uuid::parse_str(&id).map_err(|_| {
+        let message = format!("`{}` is not a valid UUID.", id);
+        (StatusCode::BAD_REQUEST, message)
+    })?;
+    let collection: Collection<Wishlist> = state
+        .database_router
+        .database(&tenant_id)
+        .collection::<Wishlist>("wishlists");
+    let wishlist = query_object(&collection, id)
+        .await
+        .and_then(|wishlist| tenant_id.check_wishlist(wishlist))
+        .map_err(|error| (StatusCode::NOT_FOUND, error.message))?;
+    authentication
+        .principal
+        .check_read_permissions(wishlist.user._id, &state.permissive_roles)
+        .map_err(|error| (StatusCode::FORBIDDEN, error.message))?;
+    let wishlists = watch_wishlist(&collection, id)
+        .await
+        .map_err(|error| (StatusCode::INTERNAL_SERVER_ERROR, error.message))?;
+    let events = wishlists.map(|wishlist| {
+        Event::default()
+            .event("wishlistUpdated")
+            .json_data(WishlistUpdatedEventData::from(wishlist))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL)))
+}
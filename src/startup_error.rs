@@ -0,0 +1,76 @@
+use std::{fmt, process::ExitCode};
+
+/// Error which prevents the wishlist service from starting, mapped to a distinct exit code.
+///
+/// Exit codes follow `sysexits.h`, so orchestrators can tell misconfigurations from unavailable dependencies.
+#[derive(Debug)]
+pub enum StartupError {
+    /// Settings, secrets or files referenced by settings are invalid. Exit code 78.
+    Config(String),
+    /// Settings read from environment variables are invalid, with all problems found. Exit code 78.
+    InvalidSettings(Vec<String>),
+    /// MongoDB can not be reached. Exit code 69.
+    DatabaseUnreachable(String),
+    /// A dependency other than MongoDB, e.g. the OpenID Connect issuer, can not be reached. Exit code 69.
+    DependencyUnreachable(String),
+    /// The port of the HTTP server is already in use. Exit code 75.
+    PortInUse(u16),
+    /// Reading or writing files or sockets failed. Exit code 74.
+    Io(String),
+    /// The generated GraphQL schema differs from the committed schema, with the diff of both. Exit code 65.
+    SchemaMismatch(String),
+}
+
+impl StartupError {
+    /// Returns the exit code of the process failing with the error.
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            StartupError::Config(_) | StartupError::InvalidSettings(_) => 78,
+            StartupError::DatabaseUnreachable(_) | StartupError::DependencyUnreachable(_) => 69,
+            StartupError::PortInUse(_) => 75,
+            StartupError::Io(_) => 74,
+            StartupError::SchemaMismatch(_) => 65,
+        };
+        ExitCode::from(code)
+    }
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::Config(message) => {
+                write!(f, "Invalid configuration: {} Check the environment variables and secrets of the service.", message)
+            }
+            StartupError::InvalidSettings(problems) => {
+                writeln!(
+                    f,
+                    "Invalid configuration, {} problems found:",
+                    problems.len()
+                )?;
+                for problem in problems {
+                    writeln!(f, "  - {}", problem)?;
+                }
+                write!(f, "Check the environment variables of the service.")
+            }
+            StartupError::DatabaseUnreachable(message) => write!(
+                f,
+                "MongoDB is unreachable: {} Check `$MONGODB_URI` and that MongoDB is running.",
+                message
+            ),
+            StartupError::DependencyUnreachable(message) => {
+                write!(f, "Dependency is unreachable: {}", message)
+            }
+            StartupError::PortInUse(port) => write!(
+                f,
+                "Port {} is already in use. Stop the other process listening on it.",
+                port
+            ),
+            StartupError::Io(message) => write!(f, "I/O failed: {}", message),
+            StartupError::SchemaMismatch(diff) => write!(
+                f,
+                "GraphQL schema differs from ./schemas/wishlist.graphql:\n{}\nRun with `--generate-schema` to update it.",
+                diff
+            ),
+        }
+    }
+}
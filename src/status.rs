@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    env,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{extract::State, Json};
+use bson::{doc, DateTime};
+use mongodb::Database;
+use reqwest::Url;
+use serde::Serialize;
+use tokio::{net::TcpStream, time::timeout};
+
+use crate::dapr_client::DaprClient;
+use crate::graphql::model::build_info::BuildInfo;
+
+/// Timeout of a single connectivity check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default OTLP/HTTP endpoint, according to the OpenTelemetry specification.
+const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4318";
+
+/// Service state of the status endpoint.
+#[derive(Clone)]
+pub struct StatusState {
+    pub db_client: Database,
+    pub dapr_client: DaprClient,
+    /// Timestamps of the last successful connectivity checks, referenced by dependency name.
+    pub last_successes: Arc<Mutex<HashMap<&'static str, DateTime>>>,
+}
+
+impl StatusState {
+    /// Constructs the state of the status endpoint without previous connectivity checks.
+    ///
+    /// * `db_client` - MongoDB database client to check.
+    /// * `dapr_client` - Dapr client to check the sidecar with.
+    pub fn new(db_client: Database, dapr_client: DaprClient) -> Self {
+        Self {
+            db_client,
+            dapr_client,
+            last_successes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Runs a connectivity check and updates the timestamp of the last success of the dependency.
+    ///
+    /// * `name` - Name of dependency.
+    /// * `check` - Connectivity check resolving to an error message on failure.
+    async fn check(
+        &self,
+        name: &'static str,
+        check: impl Future<Output = Result<(), String>>,
+    ) -> DependencyStatus {
+        let result = match timeout(CHECK_TIMEOUT, check).await {
+            Ok(result) => result,
+            Err(_) => Err("Connectivity check timed out.".to_string()),
+        };
+        let mut last_successes = self.last_successes.lock().unwrap();
+        if result.is_ok() {
+            last_successes.insert(name, DateTime::now());
+        }
+        DependencyStatus {
+            connected: result.is_ok(),
+            last_success_at: last_successes
+                .get(name)
+                .and_then(|last_success| last_success.try_to_rfc3339_string().ok()),
+            error: result.err(),
+        }
+    }
+}
+
+/// Build and connectivity of the wishlist service to its dependencies.
+#[derive(Serialize, Debug)]
+pub struct StatusResponse {
+    pub build: BuildInfo,
+    pub mongodb: DependencyStatus,
+    pub dapr: DependencyStatus,
+    pub otlp: DependencyStatus,
+}
+
+/// Connectivity to a dependency.
+#[derive(Serialize, Debug)]
+pub struct DependencyStatus {
+    /// Whether the last connectivity check succeeded.
+    pub connected: bool,
+    /// RFC3339 timestamp of the last successful connectivity check.
+    pub last_success_at: Option<String>,
+    /// Error of the last connectivity check.
+    pub error: Option<String>,
+}
+
+/// HTTP endpoint describing the build and the connectivity to MongoDB, the Dapr sidecar and the OTLP endpoint.
+///
+/// * `state` - Service state containing the clients to check.
+pub async fn status(State(state): State<StatusState>) -> Json<StatusResponse> {
+    let (mongodb, dapr, otlp) = futures::join!(
+        state.check("mongodb", check_mongodb(&state.db_client)),
+        state.check("dapr", check_dapr(&state.dapr_client)),
+        state.check("otlp", check_otlp()),
+    );
+    Json(StatusResponse {
+        build: BuildInfo::current(),
+        mongodb,
+        dapr,
+        otlp,
+    })
+}
+
+/// Checks the connectivity to MongoDB with a `ping` command.
+///
+/// * `db_client` - MongoDB database client.
+async fn check_mongodb(db_client: &Database) -> Result<(), String> {
+    db_client
+        .run_command(doc! {"ping": 1}, None)
+        .await
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+/// Checks the connectivity to the Dapr sidecar with its health endpoint.
+///
+/// * `dapr_client` - Dapr client.
+async fn check_dapr(dapr_client: &DaprClient) -> Result<(), String> {
+    dapr_client
+        .check_health()
+        .await
+        .map_err(|error| error.message)
+}
+
+/// Checks the connectivity to the OTLP endpoint by opening a TCP connection.
+///
+/// The OTLP endpoint is defined in `$OTEL_EXPORTER_OTLP_ENDPOINT`.
+async fn check_otlp() -> Result<(), String> {
+    let endpoint =
+        env::var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or(DEFAULT_OTLP_ENDPOINT.to_string());
+    let url = Url::parse(&endpoint).map_err(|error| error.to_string())?;
+    let host = url
+        .host_str()
+        .ok_or(format!("OTLP endpoint: `{}` has no host.", endpoint))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or(format!("OTLP endpoint: `{}` has no port.", endpoint))?;
+    TcpStream::connect((host, port))
+        .await
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
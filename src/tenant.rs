@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use async_graphql::{Context, Error, Result};
+use axum::http::{HeaderMap, StatusCode};
+use bson::{doc, Document};
+use log::{info, warn};
+use mongodb::{Client, Database};
+
+use crate::graphql::model::wishlist::Wishlist;
+use crate::settings::TenantDatabases;
+
+/// Name of the HTTP header containing the tenant identifier of a request.
+pub const TENANT_ID_HEADER: &str = "X-Tenant-Id";
+
+/// Maximum length of a tenant identifier.
+const MAX_TENANT_ID_LENGTH: usize = 64;
+
+/// Names of the MongoDB collections containing tenant-scoped documents.
+///
+/// Users and product variants are replicated from events of other services and shared by all tenants.
+const TENANT_SCOPED_COLLECTIONS: [&str; 3] = ["wishlists", "audit_entries", "idempotency_keys"];
+
+/// Identifier of the tenant, e.g. a shop, a request is performed for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantId(pub String);
+
+impl TenantId {
+    /// Parses a tenant identifier consisting of at most 64 ASCII alphanumeric characters, `-` or `_`.
+    ///
+    /// * `tenant_id` - Tenant identifier to parse.
+    pub fn parse(tenant_id: &str) -> Result<Self> {
+        let is_valid = !tenant_id.is_empty()
+            && tenant_id.len() <= MAX_TENANT_ID_LENGTH
+            && tenant_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        match is_valid {
+            true => Ok(Self(tenant_id.to_string())),
+            false => {
+                let message = format!("Tenant identifier: `{}` is invalid.", tenant_id);
+                Err(Error::new(message))
+            }
+        }
+    }
+
+    /// Restricts a MongoDB filter to the documents of the tenant.
+    ///
+    /// * `filter` - MongoDB filter to restrict.
+    pub fn scope(&self, mut filter: Document) -> Document {
+        filter.insert("tenant_id", &self.0);
+        filter
+    }
+
+    /// Checks if a wishlist belongs to the tenant.
+    ///
+    /// Wishlists of other tenants are reported as not found, so their existence is not revealed.
+    ///
+    /// * `wishlist` - Wishlist to check.
+    pub fn check_wishlist(&self, wishlist: Wishlist) -> Result<Wishlist> {
+        match wishlist.tenant_id == self.0 {
+            true => Ok(wishlist),
+            false => {
+                let message = format!("Wishlist with UUID: `{}` not found.", wishlist._id);
+                Err(Error::new(message))
+            }
+        }
+    }
+}
+
+/// Resolves the MongoDB database of a tenant.
+///
+/// Tenants without dedicated database share the default database, where their documents are separated by `tenant_id`.
+#[derive(Clone)]
+pub struct DatabaseRouter {
+    default_database: Database,
+    tenant_databases: HashMap<String, Database>,
+}
+
+impl DatabaseRouter {
+    /// Constructs a database router.
+    ///
+    /// * `client` - MongoDB client used to access the databases.
+    /// * `default_database_name` - Name of the database shared by tenants without dedicated database.
+    /// * `tenant_databases` - Names of the dedicated databases of tenants.
+    pub fn new(
+        client: &Client,
+        default_database_name: &str,
+        tenant_databases: &TenantDatabases,
+    ) -> Self {
+        let tenant_databases = tenant_databases
+            .0
+            .iter()
+            .map(|(tenant_id, database_name)| (tenant_id.clone(), client.database(database_name)))
+            .collect();
+        Self {
+            default_database: client.database(default_database_name),
+            tenant_databases,
+        }
+    }
+
+    /// Returns the database shared by tenants without dedicated database, which also contains tenant-independent data.
+    pub fn default_database(&self) -> &Database {
+        &self.default_database
+    }
+
+    /// Returns the database of a tenant.
+    ///
+    /// * `tenant_id` - Tenant to resolve the database of.
+    pub fn database(&self, tenant_id: &TenantId) -> &Database {
+        self.tenant_databases
+            .get(&tenant_id.0)
+            .unwrap_or(&self.default_database)
+    }
+
+    /// Returns all distinct databases, starting with the default database.
+    pub fn databases(&self) -> Vec<Database> {
+        let mut databases = vec![self.default_database.clone()];
+        for database in self.tenant_databases.values() {
+            if databases
+                .iter()
+                .all(|known_database| known_database.name() != database.name())
+            {
+                databases.push(database.clone());
+            }
+        }
+        databases
+    }
+}
+
+/// Returns the tenant a GraphQL context is performed for.
+///
+/// * `ctx` - GraphQL context containing the tenant identifier.
+pub fn tenant_id<'a>(ctx: &Context<'a>) -> Result<&'a TenantId> {
+    ctx.data::<TenantId>()
+        .map_err(|_| Error::new("Tenant identifier of request is not set."))
+}
+
+/// Determines the tenant of a request.
+///
+/// The tenant bound to the credentials of the request takes precedence, an `X-Tenant-Id` header naming another tenant is rejected.
+/// Returns the HTTP status code and the reason if the tenant is rejected.
+///
+/// * `headers` - Header map containing headers of request.
+/// * `credential_tenant_id` - Tenant bound to the access token or API key of the request.
+/// * `default_tenant_id` - Tenant of requests which do not specify a tenant.
+pub fn resolve_tenant_id(
+    headers: &HeaderMap,
+    credential_tenant_id: Option<TenantId>,
+    default_tenant_id: &TenantId,
+) -> Result<TenantId, (StatusCode, String)> {
+    let header_tenant_id = match headers.get(TENANT_ID_HEADER) {
+        Some(tenant_id) => {
+            let tenant_id = tenant_id.to_str().map_err(|_| {
+                let message = "X-Tenant-Id header could not be parsed.".to_string();
+                (StatusCode::BAD_REQUEST, message)
+            })?;
+            let tenant_id = TenantId::parse(tenant_id)
+                .map_err(|error| (StatusCode::BAD_REQUEST, error.message))?;
+            Some(tenant_id)
+        }
+        None => None,
+    };
+    match (credential_tenant_id, header_tenant_id) {
+        (Some(credential_tenant_id), Some(header_tenant_id))
+            if credential_tenant_id != header_tenant_id =>
+        {
+            let message = format!(
+                "Tenant identifier: `{}` of X-Tenant-Id header does not match the tenant of the credentials.",
+                header_tenant_id.0
+            );
+            Err((StatusCode::FORBIDDEN, message))
+        }
+        (Some(tenant_id), _) | (None, Some(tenant_id)) => Ok(tenant_id),
+        (None, None) => Ok(default_tenant_id.clone()),
+    }
+}
+
+/// Assigns documents stored before tenants were introduced to the default tenant.
+///
+/// Failures are logged, as documents without tenant are not visible to any tenant but otherwise unaffected.
+///
+/// * `db_client` - MongoDB database client.
+/// * `default_tenant_id` - Tenant of documents without tenant.
+pub async fn assign_default_tenant(db_client: &Database, default_tenant_id: &TenantId) {
+    for collection_name in TENANT_SCOPED_COLLECTIONS {
+        let collection = db_client.collection::<Document>(collection_name);
+        match collection
+            .update_many(
+                doc! {"tenant_id": {"$exists": false}},
+                doc! {"$set": {"tenant_id": &default_tenant_id.0}},
+                None,
+            )
+            .await
+        {
+            Ok(result) if result.modified_count > 0 => info!(
+                "Assigned {} documents of collection: `{}` to default tenant.",
+                result.modified_count, collection_name
+            ),
+            Ok(_) => {}
+            Err(error) => warn!(
+                "Assigning documents of collection: `{}` to default tenant failed: {}",
+                collection_name, error
+            ),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::IntoResponse,
+};
+use bson::doc;
+use futures::TryStreamExt;
+use mongodb::{Collection, Database};
+
+use crate::authentication::RequestAuthenticator;
+use crate::authorization::PermissiveRoles;
+use crate::graphql::{
+    model::{
+        date_time::DateTime,
+        product_variant_metadata::{Money, ProductVariantMetadata},
+        uuid::Uuid,
+        wishlist::Wishlist,
+    },
+    query::query_object,
+};
+use crate::tenant::{resolve_tenant_id, DatabaseRouter, TenantId};
+
+/// Template of the exported HTML document, the `{{…}}` placeholders are replaced by escaped content.
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{name}}</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+header { border-bottom: 4px solid {{color}}; margin-bottom: 1em; }
+table { width: 100%; border-collapse: collapse; }
+th, td { text-align: left; padding: 0.4em; border-bottom: 1px solid #ddd; vertical-align: middle; }
+td.price { text-align: right; white-space: nowrap; }
+img { max-width: 64px; max-height: 64px; }
+.purchased { color: #888; text-decoration: line-through; }
+footer { margin-top: 1em; font-size: 0.8em; color: #888; }
+@media print { body { margin: 0; } }
+</style>
+</head>
+<body>
+<header>
+<h1>{{name}}</h1>
+{{details}}
+</header>
+<table>
+<thead><tr><th></th><th>Item</th><th>Price</th></tr></thead>
+<tbody>
+{{items}}
+</tbody>
+</table>
+<footer>Exported at {{exported_at}}.</footer>
+</body>
+</html>
+"#;
+
+/// Color of the header border of wishlists without theme color.
+const DEFAULT_COLOR: &str = "#444";
+
+/// Service state of the wishlist export endpoint.
+#[derive(Clone)]
+pub struct WishlistExportState {
+    /// Authenticator of the callers of requests, shared with the GraphQL endpoint.
+    pub authenticator: RequestAuthenticator,
+    /// Resolver of the MongoDB database of the tenant of a request.
+    pub database_router: DatabaseRouter,
+    /// Tenant of requests which do not specify a tenant.
+    pub default_tenant_id: TenantId,
+    /// Roles which permit access to the wishlists of all users.
+    pub permissive_roles: PermissiveRoles,
+}
+
+/// HTTP endpoint rendering the wishlist of UUID as self-contained HTML document, suitable for printing or emailing.
+///
+/// Serves `/export/wishlists/{id}`, the document contains the product variants with their cached catalog metadata.
+/// The caller is authenticated like GraphQL requests, see `RequestAuthenticator`,
+/// and must own the wishlist, have a permissive role or an API key with a wishlist scope.
+///
+/// * `state` - Authenticator, database router and default tenant used by handler.
+/// * `id` - UUID of wishlist to export.
+/// * `headers` - Header map containing headers of request.
+pub async fn export_wishlist(
+    State(state): State<WishlistExportState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let authentication = state
+        .authenticator
+        .authenticate(&headers, None)
+        .await
+        .map_err(|error| (StatusCode::UNAUTHORIZED, error.message().to_string()))?;
+    let tenant_id = resolve_tenant_id(
+        &headers,
+        authentication.credential_tenant_id,
+        &state.default_tenant_id,
+    )?;
+    let id = Uuid::parse_str(&id).map_err(|_| {
+        let message = format!("`{}` is not a valid UUID.", id);
+        (StatusCode::BAD_REQUEST, message)
+    })?;
+    let db_client = state.database_router.database(&tenant_id);
+    let collection: Collection<Wishlist> = db_client.collection::<Wishlist>("wishlists");
+    let wishlist = query_object(&collection, id)
+        .await
+        .and_then(|wishlist| tenant_id.check_wishlist(wishlist))
+        .map_err(|error| (StatusCode::NOT_FOUND, error.message))?;
+    authentication
+        .principal
+        .check_read_permissions(wishlist.user._id, &state.permissive_roles)
+        .map_err(|error| (StatusCode::FORBIDDEN, error.message))?;
+    let metadata = find_metadata(db_client, &wishlist)
+        .await
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+    let content_disposition = format!("inline; filename=\"wishlist-{}.html\"", wishlist._id);
+    Ok((
+        [
+            (CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (CONTENT_DISPOSITION, content_disposition),
+        ],
+        render_wishlist(&wishlist, &metadata),
+    ))
+}
+
+/// Retrieves the cached catalog metadata of the product variants of a wishlist.
+///
+/// * `db_client` - MongoDB database of the tenant.
+/// * `wishlist` - Wishlist whose product variants are looked up.
+async fn find_metadata(
+    db_client: &Database,
+    wishlist: &Wishlist,
+) -> Result<HashMap<Uuid, ProductVariantMetadata>, String> {
+    let collection: Collection<ProductVariantMetadata> =
+        db_client.collection::<ProductVariantMetadata>("product_variants");
+    let ids: Vec<Uuid> = wishlist
+        .internal_product_variants
+        .iter()
+        .map(|product_variant| product_variant._id)
+        .collect();
+    let message = "Retrieving product variants of wishlist failed in MongoDB.".to_string();
+    let metadata: Vec<ProductVariantMetadata> =
+        match collection.find(doc! {"_id": {"$in": ids}}, None).await {
+            Ok(cursor) => cursor.try_collect().await.map_err(|_| message)?,
+            Err(_) => return Err(message),
+        };
+    Ok(metadata
+        .into_iter()
+        .map(|metadata| (metadata._id, metadata))
+        .collect())
+}
+
+/// Renders a wishlist as HTML document from the template.
+///
+/// Product variants without cached metadata are rendered by their UUID.
+///
+/// * `wishlist` - Wishlist to render.
+/// * `metadata` - Cached catalog metadata of the product variants of the wishlist.
+fn render_wishlist(
+    wishlist: &Wishlist,
+    metadata: &HashMap<Uuid, ProductVariantMetadata>,
+) -> String {
+    let mut details = vec![];
+    if let Some(description) = &wishlist.description {
+        details.push(format!("<p>{}</p>", escape_html(description)));
+    }
+    if let Some(occasion_date) = &wishlist.occasion_date {
+        details.push(format!("<p>Occasion: {}</p>", format_date(occasion_date)));
+    }
+    let items: Vec<String> = wishlist
+        .internal_product_variants
+        .iter()
+        .map(|product_variant| {
+            render_item(
+                product_variant._id,
+                metadata.get(&product_variant._id),
+                wishlist.is_purchased(product_variant._id),
+            )
+        })
+        .collect();
+    let color = wishlist.color.as_deref().unwrap_or(DEFAULT_COLOR);
+    let values = HashMap::from([
+        ("name", escape_html(&wishlist.name)),
+        ("color", escape_html(color)),
+        ("details", details.join("\n")),
+        ("items", items.join("\n")),
+        ("exported_at", format_date_time(&DateTime::now())),
+    ]);
+    fill_template(TEMPLATE, &values)
+}
+
+/// Replaces the `{{…}}` placeholders of a template in a single pass, so placeholders in values are kept verbatim.
+///
+/// * `template` - Template to fill, unknown placeholders are removed.
+/// * `values` - Values of the placeholders by name.
+fn fill_template(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        match rest[start..].find("}}") {
+            Some(end) => {
+                let name = &rest[start + 2..start + end];
+                filled.push_str(values.get(name).map(String::as_str).unwrap_or_default());
+                rest = &rest[start + end + 2..];
+            }
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Renders a product variant of a wishlist as table row.
+///
+/// * `id` - UUID of the product variant.
+/// * `metadata` - Cached catalog metadata of the product variant, if any.
+/// * `purchased` - Whether the product variant was marked as purchased.
+fn render_item(id: Uuid, metadata: Option<&ProductVariantMetadata>, purchased: bool) -> String {
+    let image = metadata
+        .and_then(|metadata| metadata.image_url.as_ref())
+        .map(|image_url| format!("<img src=\"{}\" alt=\"\">", escape_html(image_url)))
+        .unwrap_or_default();
+    let name = metadata
+        .and_then(|metadata| metadata.name.clone())
+        .unwrap_or_else(|| id.to_string());
+    let price = metadata
+        .and_then(|metadata| metadata.current_price.as_ref())
+        .map(format_money)
+        .unwrap_or_default();
+    let class = match purchased {
+        true => " class=\"purchased\"",
+        false => "",
+    };
+    format!(
+        "<tr{}><td>{}</td><td>{}</td><td class=\"price\">{}</td></tr>",
+        class,
+        image,
+        escape_html(&name),
+        escape_html(&price)
+    )
+}
+
+/// Formats an amount of money in the minor unit of its currency, e.g. `12.99 EUR`, `1299 JPY` or `12.990 KWD`.
+///
+/// * `money` - Amount of money to format.
+fn format_money(money: &Money) -> String {
+    let minor_unit_digits = minor_unit_digits(&money.currency);
+    if minor_unit_digits == 0 {
+        return format!("{} {}", money.amount, money.currency);
+    }
+    let minor_units_per_major_unit = 10u64.pow(minor_unit_digits);
+    format!(
+        "{}.{:0width$} {}",
+        money.amount / minor_units_per_major_unit,
+        money.amount % minor_units_per_major_unit,
+        money.currency,
+        width = minor_unit_digits as usize
+    )
+}
+
+/// Returns the number of digits of the minor unit of a currency according to ISO 4217.
+///
+/// Currencies not listed have two digits, e.g. cents.
+///
+/// * `currency` - ISO 4217 code of the currency.
+fn minor_unit_digits(currency: &str) -> u32 {
+    match currency.to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+/// Formats a timestamp as date, e.g. `2024-01-01`.
+///
+/// * `date_time` - Timestamp to format.
+fn format_date(date_time: &DateTime) -> String {
+    format_date_time(date_time).chars().take(10).collect()
+}
+
+/// Formats a timestamp as RFC 3339 UTC date-time.
+///
+/// * `date_time` - Timestamp to format.
+fn format_date_time(date_time: &DateTime) -> String {
+    date_time.0.try_to_rfc3339_string().unwrap_or_default()
+}
+
+/// Escapes text for HTML element content and attribute values.
+///
+/// * `text` - Text to escape.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}